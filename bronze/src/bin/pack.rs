@@ -0,0 +1,41 @@
+// Packs a directory of assets into a bronze archive.
+//
+//     cargo run -p bronze --bin pack -- <directory> <output>
+//
+// Entries are named by their path relative to `<directory>`, so a manifest packed along with
+// its assets is read back with `Manifest::load_from(vfs, "manifest.ron")`, where `vfs` has the
+// archive as a source.
+
+use std::{env, fs, process::exit};
+
+use bronze::vfs::{Archive, ArchiveWriter};
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let [directory, output] = args.as_slice() else {
+        eprintln!("usage: pack <directory> <output>");
+        exit(2);
+    };
+
+    let mut writer = ArchiveWriter::new();
+    let count = writer.add_dir(directory).unwrap_or_else(|error| {
+        eprintln!("{}: {}", directory, error);
+        exit(1);
+    });
+
+    if !writer.save(output) {
+        eprintln!("failed to write {}", output);
+        exit(1);
+    }
+
+    // Reading the index back catches a broken archive before it ships.
+    if let Err(error) = Archive::open(output) {
+        eprintln!("{}", error);
+        exit(1);
+    }
+
+    let size = fs::metadata(output)
+        .map(|metadata| metadata.len())
+        .unwrap_or(0);
+    println!("Packed {} files into {} ({} bytes)", count, output, size);
+}
@@ -0,0 +1,196 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    input::{InputManager, Key},
+    window::Window,
+};
+
+mod timestamp {
+    use super::*;
+
+    // Formats the current UTC time as `YYYY-MM-DD_HH-MM-SS_mmm`.
+    pub fn timestamp() -> String {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+
+        let secs = now.as_secs() as i64;
+        let (days, secs) = (secs.div_euclid(86400), secs.rem_euclid(86400));
+        let (year, month, day) = civil_from_days(days);
+
+        format!(
+            "{:04}-{:02}-{:02}_{:02}-{:02}-{:02}_{:03}",
+            year,
+            month,
+            day,
+            secs / 3600,
+            secs / 60 % 60,
+            secs % 60,
+            now.subsec_millis()
+        )
+    }
+
+    fn civil_from_days(days: i64) -> (i64, u32, u32) {
+        let z = days + 719468;
+        let era = z.div_euclid(146097);
+        let doe = z.rem_euclid(146097);
+        let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+        let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+        (year, month, day)
+    }
+}
+use timestamp::timestamp;
+
+mod recorder {
+    use super::*;
+
+    pub struct CaptureConfig {
+        pub directory: String,
+        pub screenshot_key: Option<Key>,
+        pub record_key: Option<Key>,
+        pub every_nth_frame: u32,
+    }
+
+    impl Default for CaptureConfig {
+        fn default() -> Self {
+            CaptureConfig {
+                directory: "screenshots".to_string(),
+                screenshot_key: None,
+                record_key: None,
+                every_nth_frame: 1,
+            }
+        }
+    }
+
+    struct Recording {
+        directory: PathBuf,
+        frame: u64,
+        saved: u64,
+    }
+
+    pub struct Capture {
+        config: CaptureConfig,
+        screenshot_requested: bool,
+        recording: Option<Recording>,
+        last_saved: Option<PathBuf>,
+    }
+
+    impl Capture {
+        pub fn new(config: CaptureConfig) -> Self {
+            Capture {
+                config,
+                screenshot_requested: false,
+                recording: None,
+                last_saved: None,
+            }
+        }
+
+        #[inline]
+        pub fn config(&self) -> &CaptureConfig {
+            &self.config
+        }
+
+        #[inline]
+        pub fn config_mut(&mut self) -> &mut CaptureConfig {
+            &mut self.config
+        }
+
+        #[inline]
+        pub fn last_saved(&self) -> Option<&Path> {
+            self.last_saved.as_deref()
+        }
+
+        #[inline]
+        pub fn is_recording(&self) -> bool {
+            self.recording.is_some()
+        }
+
+        #[inline]
+        pub fn screenshot(&mut self) {
+            self.screenshot_requested = true;
+        }
+
+        pub fn start_recording(&mut self) {
+            if self.recording.is_none() {
+                self.recording = Some(Recording {
+                    directory: Path::new(&self.config.directory)
+                        .join(format!("frames_{}", timestamp())),
+                    frame: 0,
+                    saved: 0,
+                });
+            }
+        }
+
+        pub fn stop_recording(&mut self) {
+            self.recording = None;
+        }
+
+        pub(crate) fn input(&mut self, input: &InputManager) {
+            if let Some(key) = self.config.screenshot_key {
+                if input.key_pressed(key) {
+                    self.screenshot();
+                }
+            }
+
+            if let Some(key) = self.config.record_key {
+                if input.key_pressed(key) {
+                    if self.is_recording() {
+                        self.stop_recording();
+                    } else {
+                        self.start_recording();
+                    }
+                }
+            }
+        }
+
+        // Called after the frame is drawn and before it is displayed.
+        pub(crate) fn capture(&mut self, window: &Window) {
+            if self.screenshot_requested {
+                self.screenshot_requested = false;
+
+                let path = Path::new(&self.config.directory)
+                    .join(format!("screenshot_{}.png", timestamp()));
+                save(window, path, &mut self.last_saved);
+            }
+
+            let every_nth_frame = self.config.every_nth_frame.max(1) as u64;
+
+            if let Some(recording) = &mut self.recording {
+                if recording.frame % every_nth_frame == 0 {
+                    let path = recording
+                        .directory
+                        .join(format!("frame_{:06}.png", recording.saved));
+                    recording.saved += 1;
+                    save(window, path, &mut self.last_saved);
+                }
+
+                recording.frame += 1;
+            }
+        }
+    }
+
+    fn save(window: &Window, path: PathBuf, last_saved: &mut Option<PathBuf>) {
+        if let Some(directory) = path.parent() {
+            if let Err(err) = fs::create_dir_all(directory) {
+                eprintln!("Failed to create {}: {}", directory.display(), err);
+                return;
+            }
+        }
+
+        if window.save_capture(&path.to_string_lossy()) {
+            *last_saved = Some(path);
+        } else {
+            eprintln!("Failed to save capture to {}", path.display());
+        }
+    }
+}
+pub use recorder::*;
@@ -2,12 +2,21 @@ use std::time::Duration;
 
 use sfml::window::Event;
 
-use crate::{game::Game, input::InputManager, timer::Timer, window::Window};
+use crate::{
+    capture::{Capture, CaptureConfig},
+    game::Game,
+    input::InputManager,
+    timer::Timer,
+    tween::TweenManager,
+    window::Window,
+};
 
 pub struct Engine {
     window: Window,
     input: InputManager,
     timer: Timer,
+    tweens: TweenManager,
+    capture: Capture,
 }
 
 impl Engine {
@@ -16,6 +25,8 @@ impl Engine {
             window,
             input: InputManager::new(),
             timer: Timer::new(),
+            tweens: TweenManager::new(),
+            capture: Capture::new(CaptureConfig::default()),
         }
     }
 
@@ -31,6 +42,22 @@ impl Engine {
         &self.input
     }
 
+    pub fn tweens(&self) -> &TweenManager {
+        &self.tweens
+    }
+
+    pub fn tweens_mut(&mut self) -> &mut TweenManager {
+        &mut self.tweens
+    }
+
+    pub fn capture(&self) -> &Capture {
+        &self.capture
+    }
+
+    pub fn capture_mut(&mut self) -> &mut Capture {
+        &mut self.capture
+    }
+
     pub fn run<G: Game>(&mut self, mut game: G) {
         self.timer.start();
 
@@ -44,9 +71,13 @@ impl Engine {
             }
 
             game.input(&self.input);
+            self.capture.input(&self.input);
 
             let frame_time = self.timer.reset().min(Duration::from_secs_f32(1.0 / 30.0));
 
+            self.tweens.update(frame_time);
+            self.window.update_cursor(frame_time);
+
             game.pre_update(&self);
             game.update(self, frame_time);
             game.post_update(&self);
@@ -55,6 +86,8 @@ impl Engine {
 
             self.window.clear();
             game.draw(&mut self.window.canvas());
+            self.capture.capture(&self.window);
+            self.window.draw_cursor(self.input.mouse.position());
             self.window.display();
         }
     }
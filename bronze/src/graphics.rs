@@ -1,9 +1,13 @@
 use std::rc::Rc;
 
-pub use sfml::graphics::Color;
+pub use sfml::graphics::{Color, PrimitiveType, Vertex};
 use sfml::{graphics::Transformable, system::Vector2f};
 
-use crate::{shape::Point, window::Canvas};
+use crate::{
+    math::{Transform2D, Vec2},
+    shape::Point,
+    window::Canvas,
+};
 
 type Position = Point;
 type X = f32;
@@ -14,7 +18,10 @@ type Scale = Vector2f;
 mod sprite {
     use super::*;
 
-    use sfml::graphics::{Sprite as SfmlSprite, Texture};
+    use sfml::{
+        graphics::{Sprite as SfmlSprite, Texture},
+        SfBox,
+    };
 
     use crate::{
         resources::Image,
@@ -190,26 +197,133 @@ mod sprite {
         }
     }
 
+    impl DrawArgs for Vec2 {
+        fn position(&self) -> Position {
+            Point::from(*self)
+        }
+
+        fn rotation(&self) -> Rotation {
+            0.0
+        }
+
+        fn scale(&self) -> Scale {
+            Scale::new(1.0, 1.0)
+        }
+
+        fn color(&self) -> Color {
+            Color::WHITE
+        }
+    }
+
+    impl DrawArgs for (Vec2, Rotation) {
+        fn position(&self) -> Position {
+            Point::from(self.0)
+        }
+
+        fn rotation(&self) -> Rotation {
+            self.1
+        }
+
+        fn scale(&self) -> Scale {
+            Scale::new(1.0, 1.0)
+        }
+
+        fn color(&self) -> Color {
+            Color::WHITE
+        }
+    }
+
+    impl DrawArgs for (Vec2, Color) {
+        fn position(&self) -> Position {
+            Point::from(self.0)
+        }
+
+        fn rotation(&self) -> Rotation {
+            0.0
+        }
+
+        fn scale(&self) -> Scale {
+            Scale::new(1.0, 1.0)
+        }
+
+        fn color(&self) -> Color {
+            self.1
+        }
+    }
+
+    impl DrawArgs for Transform2D {
+        fn position(&self) -> Position {
+            Point::from(Transform2D::position(self))
+        }
+
+        fn rotation(&self) -> Rotation {
+            self.rotation_degrees()
+        }
+
+        fn scale(&self) -> Scale {
+            self.scale_factors().into()
+        }
+
+        fn color(&self) -> Color {
+            Color::WHITE
+        }
+    }
+
+    impl DrawArgs for (Transform2D, Color) {
+        fn position(&self) -> Position {
+            Point::from(self.0.position())
+        }
+
+        fn rotation(&self) -> Rotation {
+            self.0.rotation_degrees()
+        }
+
+        fn scale(&self) -> Scale {
+            self.0.scale_factors().into()
+        }
+
+        fn color(&self) -> Color {
+            self.1
+        }
+    }
+
+    #[derive(Clone)]
+    enum Source {
+        Image(Rc<Image>),
+        Texture(Rc<SfBox<Texture>>),
+    }
+
     #[derive(Clone)]
     pub struct Sprite {
-        image: Rc<Image>,
+        source: Source,
     }
 
     impl Sprite {
         #[inline]
         pub fn new(image: &Rc<Image>) -> Self {
             Sprite {
-                image: Rc::clone(image),
+                source: Source::Image(Rc::clone(image)),
             }
         }
+
+        #[inline]
+        pub(crate) fn from_texture(texture: SfBox<Texture>) -> Self {
+            Sprite {
+                source: Source::Texture(Rc::new(texture)),
+            }
+        }
+
         #[inline]
         pub fn texture(&self) -> &Texture {
-            &self.image.texture()
+            match &self.source {
+                Source::Image(image) => image.texture(),
+                Source::Texture(texture) => texture,
+            }
         }
 
         #[inline]
         pub fn draw<Args: DrawArgs>(&self, target: &mut Canvas, args: Args) {
-            let mut sprite_data = SfmlSprite::with_texture(self.image.texture());
+            let mut sprite_data = SfmlSprite::with_texture(self.texture());
             sprite_data.set_position(args.position());
             sprite_data.set_rotation(args.rotation());
             sprite_data.set_scale(args.scale());
@@ -219,12 +333,12 @@ mod sprite {
 
         #[inline]
         pub fn draw_centered<Args: DrawArgs>(&self, target: &mut Canvas, args: Args) {
-            let size = self.image.size();
+            let size = self.size();
 
-            let x = args.position().x - size.x as f32 / 2.0;
-            let y = args.position().y - size.y as f32 / 2.0;
+            let x = args.position().x - size.x / 2.0;
+            let y = args.position().y - size.y / 2.0;
 
-            let mut sprite_data = SfmlSprite::with_texture(self.image.texture());
+            let mut sprite_data = SfmlSprite::with_texture(self.texture());
             sprite_data.set_position(Vector2f::new(x, y));
             sprite_data.set_rotation(args.rotation());
             sprite_data.set_scale(args.scale());
@@ -236,12 +350,12 @@ mod sprite {
         pub fn draw_on_bbox<B: BBox>(&self, target: &mut Canvas, bbox: &B) {
             let x = bbox.left();
             let y = bbox.top();
-            let size = self.image.size();
+            let size = self.size();
 
-            let scale_x = bbox.width() / size.x as f32;
-            let scale_y = bbox.height() / size.y as f32;
+            let scale_x = bbox.width() / size.x;
+            let scale_y = bbox.height() / size.y;
 
-            let mut sprite_data = SfmlSprite::with_texture(self.image.texture());
+            let mut sprite_data = SfmlSprite::with_texture(self.texture());
             sprite_data.set_position(Vector2f::new(x, y));
             sprite_data.set_scale(Vector2f::new(scale_x, scale_y));
             target.draw(&sprite_data);
@@ -266,10 +380,153 @@ mod sprite {
 }
 pub use sprite::*;
 
+mod markup {
+    use super::*;
+
+    use sfml::graphics::TextStyle;
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub(super) struct RunStyle {
+        pub color: Option<Color>,
+        pub size: u32,
+        pub style: TextStyle,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub(super) enum Item {
+        Char(char, RunStyle),
+        Icon(String, RunStyle),
+    }
+
+    // Unknown or malformed tags are kept as plain text.
+    pub(super) fn parse_markup(markup: &str, base: RunStyle) -> Vec<Item> {
+        let mut items = Vec::new();
+        let mut open: Vec<(&str, RunStyle)> = Vec::new();
+        let mut current = base;
+        let mut rest = markup;
+
+        while let Some(c) = rest.chars().next() {
+            if c == '[' {
+                if let Some(after) = rest.strip_prefix("[[") {
+                    items.push(Item::Char('[', current));
+                    rest = after;
+                    continue;
+                }
+
+                if let Some(end) = rest.find(']') {
+                    if apply_tag(&rest[1..end], &mut current, &mut open, &mut items) {
+                        rest = &rest[end + 1..];
+                        continue;
+                    }
+                }
+            }
+
+            items.push(Item::Char(c, current));
+            rest = &rest[c.len_utf8()..];
+        }
+
+        items
+    }
+
+    fn apply_tag<'m>(
+        tag: &'m str,
+        current: &mut RunStyle,
+        open: &mut Vec<(&'m str, RunStyle)>,
+        items: &mut Vec<Item>,
+    ) -> bool {
+        if let Some(name) = tag.strip_prefix('/') {
+            // Closing a tag also closes the ones opened inside it.
+            return match open.iter().rposition(|(tag, _)| *tag == name) {
+                Some(index) => {
+                    *current = open[index].1;
+                    open.truncate(index);
+                    true
+                }
+                None => false,
+            };
+        }
+
+        let (name, value) = match tag.split_once('=') {
+            Some((name, value)) => (name, Some(value)),
+            None => (tag, None),
+        };
+
+        let mut next = *current;
+
+        match (name, value) {
+            ("b", None) => next.style |= TextStyle::BOLD,
+            ("i", None) => next.style |= TextStyle::ITALIC,
+            ("u", None) => next.style |= TextStyle::UNDERLINED,
+            ("color", Some(value)) => match parse_color(value) {
+                Some(color) => next.color = Some(color),
+                None => return false,
+            },
+            ("size", Some(value)) => match value.parse() {
+                Ok(size) if size > 0 => next.size = size,
+                _ => return false,
+            },
+            ("icon", Some(value)) => {
+                items.push(Item::Icon(value.to_string(), *current));
+                return true;
+            }
+            _ => return false,
+        }
+
+        open.push((name, *current));
+        *current = next;
+        true
+    }
+
+    // Accepts the basic color names and `#rgb`, `#rgba`, `#rrggbb` or `#rrggbbaa`.
+    pub(super) fn parse_color(value: &str) -> Option<Color> {
+        if let Some(hex) = value.strip_prefix('#') {
+            let digits: Vec<u8> = hex
+                .chars()
+                .map(|c| c.to_digit(16).map(|digit| digit as u8))
+                .collect::<Option<_>>()?;
+
+            let channels: Vec<u8> = match digits.len() {
+                3 | 4 => digits.iter().map(|digit| digit * 17).collect(),
+                6 | 8 => digits
+                    .chunks(2)
+                    .map(|pair| pair[0] * 16 + pair[1])
+                    .collect(),
+                _ => return None,
+            };
+
+            let alpha = channels.get(3).copied().unwrap_or(255);
+            return Some(Color::rgba(channels[0], channels[1], channels[2], alpha));
+        }
+
+        match value.to_ascii_lowercase().as_str() {
+            "black" => Some(Color::BLACK),
+            "white" => Some(Color::WHITE),
+            "red" => Some(Color::RED),
+            "green" => Some(Color::GREEN),
+            "blue" => Some(Color::BLUE),
+            "yellow" => Some(Color::YELLOW),
+            "magenta" => Some(Color::MAGENTA),
+            "cyan" => Some(Color::CYAN),
+            "transparent" => Some(Color::TRANSPARENT),
+            _ => None,
+        }
+    }
+}
+use markup::*;
+
 mod text {
-    use sfml::graphics::Text as SfmlText;
+    use std::{cell::RefCell, time::Duration};
+
+    use sfml::graphics::{
+        vertex_array_bounds, FloatRect, Font as SfmlFont, Text as SfmlText, Texture,
+    };
+
+    pub use sfml::graphics::TextStyle;
 
-    use crate::resources::Font;
+    use crate::{
+        resources::{BitmapFont, Font, FontVersion, Glyph},
+        shape::{Movable, Rect},
+    };
 
     use super::*;
 
@@ -307,6 +564,20 @@ mod text {
         }
     }
 
+    impl WriteArgs for (Vec2, Color) {
+        fn position(&self) -> Position {
+            Point::from(self.0)
+        }
+
+        fn color(&self) -> Color {
+            self.1
+        }
+
+        fn rotation(&self) -> Rotation {
+            0.0
+        }
+    }
+
     impl WriteArgs for (X, Y, Color) {
         fn position(&self) -> Position {
             Point::new(self.0, self.1)
@@ -321,20 +592,109 @@ mod text {
         }
     }
 
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum HAlign {
+        Left,
+        Center,
+        Right,
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum VAlign {
+        Top,
+        Center,
+        Bottom,
+    }
+
+    const ITALIC_SHEAR: f32 = 0.209;
+    const ICON_CHAR: char = '\u{FFFC}';
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Page {
+        Vector(u32),
+        Bitmap(usize),
+        Icon(usize),
+        Solid,
+    }
+
+    struct Quad {
+        page: Page,
+        vertices: [Vertex; 4],
+        tint: Option<Color>,
+        outline: bool,
+        index: usize,
+    }
+
+    // Text laid out glyph by glyph, used for bitmap fonts, markup and partial reveals.
+    #[derive(Default)]
+    struct GlyphLayout {
+        quads: Vec<Quad>,
+        bounds: FloatRect,
+        count: usize,
+    }
+
     pub struct Text {
+        // Borrows the font kept alive by `font_version`, so it is declared first to be dropped
+        // first.
+        data: Option<RefCell<SfmlText<'static>>>,
+        font_version: FontVersion,
+        glyphs: Option<GlyphLayout>,
         font: Rc<Font>,
+        font_generation: usize,
         font_size: u32,
         string: String,
+        markup: bool,
+        icons: Vec<(String, Sprite)>,
+        visible: Option<usize>,
+        max_width: Option<f32>,
+        h_align: HAlign,
+        v_align: VAlign,
+        line_spacing: f32,
+        letter_spacing: f32,
+        style: TextStyle,
+        outline: (Color, f32),
+        origin: Vector2f,
     }
 
     impl Text {
         #[inline]
         pub fn new(font: &Rc<Font>, font_size: u32, string: String) -> Self {
-            Text {
+            let mut text = Text {
+                data: sfml_text(&font.version(), font_size),
+                font_version: font.version(),
+                glyphs: None,
                 font: Rc::clone(font),
+                font_generation: font.generation(),
                 font_size,
                 string,
-            }
+                markup: false,
+                icons: Vec::new(),
+                visible: None,
+                max_width: None,
+                h_align: HAlign::Left,
+                v_align: VAlign::Top,
+                line_spacing: 1.0,
+                letter_spacing: 1.0,
+                style: TextStyle::REGULAR,
+                outline: (Color::TRANSPARENT, 0.0),
+                origin: Vector2f::new(0.0, 0.0),
+            };
+            text.refresh();
+            text
+        }
+
+        // Supports `[color=yellow]`, `[color=#ffcc00]`, `[b]`, `[i]`, `[u]`, `[size=24]` and
+        // `[icon=name]`, each closed with `[/tag]` except icons. `[[` is a literal bracket.
+        #[inline]
+        pub fn with_markup(font: &Rc<Font>, font_size: u32, markup: &str) -> Self {
+            let mut text = Text::new(font, font_size, String::new());
+            text.set_markup(markup);
+            text
+        }
+
+        #[inline]
+        pub fn font(&self) -> &Rc<Font> {
+            &self.font
         }
 
         #[inline]
@@ -344,7 +704,36 @@ mod text {
 
         #[inline]
         pub fn set_string(&mut self, string: String) {
-            self.string = string;
+            self.sync_font();
+            if self.string != string || self.markup {
+                self.string = string;
+                self.markup = false;
+                self.refresh();
+            }
+        }
+
+        #[inline]
+        pub fn is_markup(&self) -> bool {
+            self.markup
+        }
+
+        #[inline]
+        pub fn set_markup(&mut self, markup: &str) {
+            self.sync_font();
+            if self.string != markup || !self.markup {
+                self.string = markup.to_string();
+                self.markup = true;
+                self.refresh();
+            }
+        }
+
+        // Icons are scaled to the font size of the run they appear in.
+        pub fn set_icon(&mut self, name: &str, sprite: Sprite) {
+            match self.icons.iter_mut().find(|(icon, _)| icon == name) {
+                Some((_, icon)) => *icon = sprite,
+                None => self.icons.push((name.to_string(), sprite)),
+            }
+            self.refresh();
         }
 
         #[inline]
@@ -355,16 +744,944 @@ mod text {
         #[inline]
         pub fn set_font_size(&mut self, font_size: u32) {
             self.font_size = font_size;
+            self.refresh();
+        }
+
+        #[inline]
+        pub fn max_width(&self) -> Option<f32> {
+            self.max_width
+        }
+
+        #[inline]
+        pub fn set_max_width(&mut self, max_width: Option<f32>) {
+            self.max_width = max_width;
+            self.refresh();
+        }
+
+        #[inline]
+        pub fn align(&self) -> (HAlign, VAlign) {
+            (self.h_align, self.v_align)
+        }
+
+        #[inline]
+        pub fn set_align(&mut self, h_align: HAlign, v_align: VAlign) {
+            self.h_align = h_align;
+            self.v_align = v_align;
+            self.refresh_origin();
+        }
+
+        #[inline]
+        pub fn line_spacing(&self) -> f32 {
+            self.line_spacing
+        }
+
+        #[inline]
+        pub fn set_line_spacing(&mut self, factor: f32) {
+            self.line_spacing = factor;
+            self.refresh();
+        }
+
+        #[inline]
+        pub fn letter_spacing(&self) -> f32 {
+            self.letter_spacing
+        }
+
+        #[inline]
+        pub fn set_letter_spacing(&mut self, factor: f32) {
+            self.letter_spacing = factor;
+            self.refresh();
+        }
+
+        #[inline]
+        pub fn style(&self) -> TextStyle {
+            self.style
+        }
+
+        #[inline]
+        pub fn set_style(&mut self, style: TextStyle) {
+            self.style = style;
+            self.refresh();
+        }
+
+        #[inline]
+        pub fn set_bold(&mut self, bold: bool) {
+            self.toggle_style(TextStyle::BOLD, bold);
+        }
+
+        #[inline]
+        pub fn set_italic(&mut self, italic: bool) {
+            self.toggle_style(TextStyle::ITALIC, italic);
+        }
+
+        #[inline]
+        pub fn set_underline(&mut self, underline: bool) {
+            self.toggle_style(TextStyle::UNDERLINED, underline);
+        }
+
+        fn toggle_style(&mut self, flag: TextStyle, enabled: bool) {
+            let mut style = self.style;
+            style.set(flag, enabled);
+            self.set_style(style);
+        }
+
+        // Outlines are only drawn for vector fonts.
+        #[inline]
+        pub fn outline(&self) -> (Color, f32) {
+            self.outline
+        }
+
+        #[inline]
+        pub fn set_outline(&mut self, color: Color, thickness: f32) {
+            self.outline = (color, thickness);
+            self.refresh();
+        }
+
+        // Number of characters and icons that can be revealed, line breaks excluded.
+        #[inline]
+        pub fn char_count(&self) -> usize {
+            match &self.glyphs {
+                Some(glyphs) => glyphs.count,
+                None => self.string.chars().filter(|&c| c != '\n').count(),
+            }
+        }
+
+        #[inline]
+        pub fn visible_chars(&self) -> Option<usize> {
+            self.visible
+        }
+
+        // Only the first `count` characters are drawn. The bounds and alignment still
+        // account for the whole text so it doesn't shift while being revealed.
+        #[inline]
+        pub fn set_visible_chars(&mut self, count: Option<usize>) {
+            let relayout = self.visible.is_some() != count.is_some();
+            self.visible = count;
+
+            if relayout {
+                self.refresh();
+            }
+        }
+
+        fn raw_bounds(&self) -> FloatRect {
+            match (&self.glyphs, &self.data) {
+                (Some(glyphs), _) => glyphs.bounds,
+                (None, Some(data)) => data.borrow().local_bounds(),
+                (None, None) => FloatRect::default(),
+            }
+        }
+
+        // Bounds of the text relative to its anchor, taking the alignment into account.
+        pub fn local_bounds(&self) -> Rect {
+            let bounds = self.raw_bounds();
+
+            Rect::new(
+                bounds.left - self.origin.x,
+                bounds.top - self.origin.y,
+                bounds.width,
+                bounds.height,
+            )
+        }
+
+        pub fn bounds_at<P: Into<Vec2>>(&self, anchor: P) -> Rect {
+            let anchor = anchor.into();
+            let mut bounds = self.local_bounds();
+            bounds.move_by(anchor.x, anchor.y);
+            bounds
+        }
+
+        #[inline]
+        pub fn width(&self) -> f32 {
+            self.raw_bounds().width
+        }
+
+        #[inline]
+        pub fn height(&self) -> f32 {
+            self.raw_bounds().height
+        }
+
+        // Lays the text out again if its font was reloaded since the last layout.
+        #[inline]
+        pub fn sync_font(&mut self) {
+            if self.font_generation != self.font.generation() {
+                self.refresh();
+            }
+        }
+
+        fn refresh(&mut self) {
+            if self.font_generation != self.font.generation() {
+                self.font_generation = self.font.generation();
+                let version = self.font.version();
+                self.data = sfml_text(&version, self.font_size);
+                self.font_version = version;
+            }
+
+            // Glyphs are laid out against the version `page_texture` draws from.
+            let font = self.font_version.clone();
+            let face = match (font.sfml_font(), font.bitmap_font()) {
+                (Some(sfml_font), _) => Face::Vector(sfml_font),
+                (None, Some(bitmap_font)) => Face::Bitmap(bitmap_font),
+                (None, None) => return,
+            };
+
+            let by_glyph = matches!(face, Face::Bitmap(_)) || self.markup || self.visible.is_some();
+
+            if by_glyph {
+                let base = RunStyle {
+                    color: None,
+                    size: self.font_size,
+                    style: self.style,
+                };
+
+                let mut items = if self.markup {
+                    parse_markup(&self.string, base)
+                } else {
+                    self.string.chars().map(|c| Item::Char(c, base)).collect()
+                };
+
+                let settings = LayoutSettings {
+                    letter_spacing: self.letter_spacing,
+                    line_spacing: self.line_spacing,
+                    outline: self.outline,
+                    max_width: self.max_width,
+                    icons: &self.icons,
+                };
+
+                self.glyphs = Some(layout_glyphs(face, &mut items, &settings));
+            } else if let Some(data) = &mut self.data {
+                self.glyphs = None;
+
+                let data = data.get_mut();
+                data.set_character_size(self.font_size);
+                data.set_line_spacing(self.line_spacing);
+                data.set_letter_spacing(self.letter_spacing);
+                data.set_style(self.style);
+                data.set_outline_color(self.outline.0);
+                data.set_outline_thickness(self.outline.1);
+
+                let mut chars: Vec<char> = self.string.chars().collect();
+
+                if let Some(max_width) = self.max_width {
+                    data.set_string(self.string.as_str());
+                    data.set_position((0.0, 0.0));
+                    data.set_origin((0.0, 0.0));
+                    data.set_rotation(0.0);
+
+                    let xs: Vec<f32> = (0..=chars.len())
+                        .map(|i| data.find_character_pos(i).x)
+                        .collect();
+                    wrap(&mut chars, &xs, max_width);
+                }
+
+                let wrapped: String = chars.into_iter().collect();
+                data.set_string(wrapped.as_str());
+            }
+
+            self.refresh_origin();
+        }
+
+        fn refresh_origin(&mut self) {
+            let bounds = self.raw_bounds();
+
+            let x = match self.h_align {
+                HAlign::Left => bounds.left,
+                HAlign::Center => bounds.left + bounds.width / 2.0,
+                HAlign::Right => bounds.left + bounds.width,
+            };
+
+            let y = match self.v_align {
+                VAlign::Top => bounds.top,
+                VAlign::Center => bounds.top + bounds.height / 2.0,
+                VAlign::Bottom => bounds.top + bounds.height,
+            };
+
+            self.origin = Vector2f::new(x, y);
+
+            if let Some(data) = &mut self.data {
+                data.get_mut().set_origin(self.origin);
+            }
+        }
+
+        fn page_texture(&self, page: Page) -> Option<&Texture> {
+            match page {
+                Page::Vector(size) => self.font_version.sfml_font().map(|font| font.texture(size)),
+                Page::Bitmap(index) => self
+                    .font_version
+                    .bitmap_font()
+                    .and_then(|font| font.pages().get(index))
+                    .map(|page| page.texture()),
+                Page::Icon(index) => self.icons.get(index).map(|(_, icon)| icon.texture()),
+                Page::Solid => None,
+            }
         }
 
         #[inline]
         pub fn draw<Args: WriteArgs>(&self, target: &mut Canvas, args: Args) {
-            let mut text_data = SfmlText::new(&self.string, &self.font.sfml_font(), self.font_size);
-            text_data.set_position(args.position());
-            text_data.set_rotation(args.rotation());
-            text_data.set_fill_color(args.color());
-            target.draw(&text_data);
+            match (&self.glyphs, &self.data) {
+                (Some(glyphs), _) => self.draw_glyphs(glyphs, target, args),
+                (None, Some(data)) => {
+                    let mut data = data.borrow_mut();
+                    data.set_position(args.position());
+                    data.set_rotation(args.rotation());
+                    data.set_fill_color(args.color());
+                    target.draw(&*data);
+                }
+                (None, None) => {}
+            }
+        }
+
+        fn draw_glyphs<Args: WriteArgs>(
+            &self,
+            glyphs: &GlyphLayout,
+            target: &mut Canvas,
+            args: Args,
+        ) {
+            let position = Vec2::from(args.position());
+            let origin = Vec2::from(self.origin);
+            let (rotation, color) = (args.rotation(), args.color());
+            let visible = self.visible.unwrap_or(usize::MAX);
+
+            // One batch per texture, with every outline drawn below the glyphs.
+            let mut batches: Vec<(bool, Page, Vec<Vertex>)> = Vec::new();
+
+            for quad in glyphs.quads.iter().filter(|quad| quad.index < visible) {
+                let tint = quad.tint.unwrap_or(color);
+                let vertices = quad.vertices.map(|vertex| {
+                    let local = Vec2::from(vertex.position) - origin;
+                    let world = position + local.rotate(rotation);
+                    Vertex::new(world.into(), tint, vertex.tex_coords)
+                });
+
+                match batches
+                    .iter_mut()
+                    .find(|(outline, page, _)| *outline == quad.outline && *page == quad.page)
+                {
+                    Some((_, _, batch)) => batch.extend(vertices),
+                    None => batches.push((quad.outline, quad.page, vertices.to_vec())),
+                }
+            }
+
+            batches.sort_by_key(|(outline, _, _)| !outline);
+
+            for (_, page, vertices) in &batches {
+                let texture = self.page_texture(*page);
+
+                if texture.is_some() || *page == Page::Solid {
+                    target.draw_vertices(vertices, PrimitiveType::QUADS, texture);
+                }
+            }
+        }
+    }
+
+    fn sfml_text(font: &FontVersion, font_size: u32) -> Option<RefCell<SfmlText<'static>>> {
+        font.sfml_font().map(|sfml_font| {
+            // SAFETY: `Text` keeps the font version in `font_version`, declared after `data`
+            // so it is dropped later, and replaces both together.
+            let sfml_font: &'static SfmlFont = unsafe { &*(sfml_font as *const SfmlFont) };
+            RefCell::new(SfmlText::new("", sfml_font, font_size))
+        })
+    }
+
+    pub struct Typewriter {
+        chars_per_second: f32,
+        elapsed: f32,
+    }
+
+    impl Typewriter {
+        #[inline]
+        pub fn new(chars_per_second: f32) -> Self {
+            Typewriter {
+                chars_per_second,
+                elapsed: 0.0,
+            }
+        }
+
+        #[inline]
+        pub fn chars_per_second(&self) -> f32 {
+            self.chars_per_second
+        }
+
+        #[inline]
+        pub fn set_chars_per_second(&mut self, chars_per_second: f32) {
+            self.chars_per_second = chars_per_second;
+        }
+
+        pub fn update(&mut self, text: &mut Text, frame_time: Duration) {
+            self.elapsed += frame_time.as_secs_f32();
+
+            let shown = (self.elapsed * self.chars_per_second) as usize;
+            text.set_visible_chars(Some(shown.min(text.char_count())));
+        }
+
+        #[inline]
+        pub fn is_finished(&self, text: &Text) -> bool {
+            text.visible_chars()
+                .is_none_or(|visible| visible >= text.char_count())
+        }
+
+        pub fn skip(&mut self, text: &mut Text) {
+            let count = text.char_count();
+            self.elapsed = count as f32 / self.chars_per_second.max(f32::EPSILON);
+            text.set_visible_chars(Some(count));
+        }
+
+        pub fn restart(&mut self, text: &mut Text) {
+            self.elapsed = 0.0;
+            text.set_visible_chars(Some(0));
+        }
+    }
+
+    // Greedy word wrap over the pen position of every char, breaking at the last space
+    // of a line that grows past `max_width`.
+    fn wrap(chars: &mut [char], xs: &[f32], max_width: f32) {
+        let mut line_start = 0;
+        let mut last_space = None;
+
+        for i in 0..=chars.len() {
+            let boundary = chars.get(i).copied();
+
+            if !matches!(boundary, None | Some(' ') | Some('\n')) {
+                continue;
+            }
+
+            if xs[i] - xs[line_start] > max_width {
+                if let Some(space) = last_space {
+                    chars[space] = '\n';
+                    line_start = space + 1;
+                }
+            }
+
+            match boundary {
+                Some('\n') => {
+                    line_start = i + 1;
+                    last_space = None;
+                }
+                Some(_) => last_space = Some(i),
+                None => {}
+            }
+        }
+    }
+
+    struct GlyphInfo {
+        advance: f32,
+        // Relative to the pen position on the baseline.
+        bounds: FloatRect,
+        texture_rect: FloatRect,
+        page: Page,
+    }
+
+    #[derive(Clone, Copy)]
+    enum Face<'f> {
+        Vector(&'f SfmlFont),
+        Bitmap(&'f BitmapFont),
+    }
+
+    impl Face<'_> {
+        fn bitmap_scale(font: &BitmapFont, size: u32) -> f32 {
+            size as f32 / font.size().max(1.0)
+        }
+
+        fn glyph(self, c: char, size: u32, bold: bool, outline: f32) -> Option<GlyphInfo> {
+            match self {
+                Face::Vector(font) => {
+                    let glyph = font.glyph(c as u32, size, bold, outline);
+                    let rect = glyph.texture_rect();
+
+                    Some(GlyphInfo {
+                        advance: glyph.advance(),
+                        bounds: glyph.bounds(),
+                        texture_rect: FloatRect::new(
+                            rect.left as f32,
+                            rect.top as f32,
+                            rect.width as f32,
+                            rect.height as f32,
+                        ),
+                        page: Page::Vector(size),
+                    })
+                }
+                Face::Bitmap(_) if outline != 0.0 => None,
+                Face::Bitmap(font) => {
+                    let glyph: &Glyph = font.glyph(c).or_else(|| font.glyph('?'))?;
+                    let scale = Face::bitmap_scale(font, size);
+
+                    Some(GlyphInfo {
+                        advance: glyph.advance * scale,
+                        bounds: FloatRect::new(
+                            glyph.x_offset * scale,
+                            (glyph.y_offset - font.base()) * scale,
+                            glyph.width as f32 * scale,
+                            glyph.height as f32 * scale,
+                        ),
+                        texture_rect: FloatRect::new(
+                            glyph.x as f32,
+                            glyph.y as f32,
+                            glyph.width as f32,
+                            glyph.height as f32,
+                        ),
+                        page: Page::Bitmap(glyph.page),
+                    })
+                }
+            }
+        }
+
+        fn kerning(self, first: char, second: char, size: u32) -> f32 {
+            match self {
+                Face::Vector(font) => font.kerning(first as u32, second as u32, size),
+                Face::Bitmap(font) => font.kerning(first, second) * Face::bitmap_scale(font, size),
+            }
+        }
+
+        fn line_height(self, size: u32) -> f32 {
+            match self {
+                Face::Vector(font) => font.line_spacing(size),
+                Face::Bitmap(font) => font.line_height() * Face::bitmap_scale(font, size),
+            }
+        }
+
+        // Distance from the top of a line to its baseline.
+        fn ascent(self, size: u32) -> f32 {
+            match self {
+                Face::Vector(_) => size as f32,
+                Face::Bitmap(font) => font.base() * Face::bitmap_scale(font, size),
+            }
+        }
+
+        // Offset below the baseline and thickness.
+        fn underline(self, size: u32) -> (f32, f32) {
+            match self {
+                Face::Vector(font) => (
+                    font.underline_position(size),
+                    font.underline_thickness(size),
+                ),
+                Face::Bitmap(font) => {
+                    let pixel = Face::bitmap_scale(font, size).max(1.0);
+                    (pixel, pixel)
+                }
+            }
+        }
+
+        // Bitmap fonts have no bold glyphs, so they are drawn twice a pixel apart instead.
+        fn bold_offset(self, size: u32) -> f32 {
+            match self {
+                Face::Vector(_) => 0.0,
+                Face::Bitmap(font) => Face::bitmap_scale(font, size).max(1.0),
+            }
+        }
+
+        // Matches how SFML turns its letter spacing factor into pixels.
+        fn letter_spacing(self, size: u32, factor: f32) -> f32 {
+            let whitespace = self
+                .glyph(' ', size, false, 0.0)
+                .map_or(size as f32 / 3.0, |glyph| glyph.advance);
+            whitespace / 3.0 * (factor - 1.0)
+        }
+    }
+
+    struct LayoutSettings<'a> {
+        letter_spacing: f32,
+        line_spacing: f32,
+        outline: (Color, f32),
+        max_width: Option<f32>,
+        icons: &'a [(String, Sprite)],
+    }
+
+    impl LayoutSettings<'_> {
+        fn icon(&self, name: &str, size: u32) -> Option<(usize, Vector2f)> {
+            let index = self.icons.iter().position(|(icon, _)| icon == name)?;
+            let icon_size = self.icons[index].1.size();
+            let height = size as f32;
+
+            Some((
+                index,
+                Vector2f::new(icon_size.x * height / icon_size.y.max(1.0), height),
+            ))
+        }
+
+        fn advance(&self, face: Face, c: char, run: &RunStyle) -> f32 {
+            let bold = run.style.contains(TextStyle::BOLD);
+            let bold_offset = if bold {
+                face.bold_offset(run.size)
+            } else {
+                0.0
+            };
+
+            face.glyph(c, run.size, bold, 0.0)
+                .map_or(0.0, |glyph| glyph.advance)
+                + bold_offset
+                + face.letter_spacing(run.size, self.letter_spacing)
+        }
+
+        // Pen x of every item within its line, plus the end of the text.
+        fn pen_positions(&self, face: Face, items: &[Item]) -> Vec<f32> {
+            let mut xs = Vec::with_capacity(items.len() + 1);
+            let mut x = 0.0;
+            let mut previous: Option<(char, u32)> = None;
+
+            for item in items {
+                xs.push(x);
+
+                match item {
+                    Item::Char('\n', _) => {
+                        x = 0.0;
+                        previous = None;
+                    }
+                    Item::Char(c, run) => {
+                        if let Some((previous, size)) = previous {
+                            if size == run.size {
+                                let kerning = face.kerning(previous, *c, run.size);
+                                *xs.last_mut().unwrap() += kerning;
+                                x += kerning;
+                            }
+                        }
+
+                        x += self.advance(face, *c, run);
+                        previous = Some((*c, run.size));
+                    }
+                    Item::Icon(name, run) => {
+                        x += self.icon(name, run.size).map_or(0.0, |(_, size)| size.x);
+                        previous = None;
+                    }
+                }
+            }
+
+            xs.push(x);
+            xs
+        }
+    }
+
+    fn layout_glyphs(face: Face, items: &mut [Item], settings: &LayoutSettings) -> GlyphLayout {
+        if let Some(max_width) = settings.max_width {
+            let xs = settings.pen_positions(face, items);
+            let mut chars: Vec<char> = items
+                .iter()
+                .map(|item| match item {
+                    Item::Char(c, _) => *c,
+                    Item::Icon(..) => ICON_CHAR,
+                })
+                .collect();
+
+            wrap(&mut chars, &xs, max_width);
+
+            for (item, c) in items.iter_mut().zip(chars) {
+                if let Item::Char(original, _) = item {
+                    *original = c;
+                }
+            }
+        }
+
+        let xs = settings.pen_positions(face, items);
+        let mut layout = GlyphLayout::default();
+        let mut top = 0.0;
+        let mut start = 0;
+
+        while start < items.len() {
+            let end = items[start..]
+                .iter()
+                .position(|item| matches!(item, Item::Char('\n', _)))
+                .map_or(items.len(), |i| start + i + 1);
+            let line = &items[start..end];
+
+            // Lines grow to fit the largest run on them.
+            let sizes = line.iter().map(|item| match item {
+                Item::Char(_, run) | Item::Icon(_, run) => run.size,
+            });
+            let ascent = sizes
+                .clone()
+                .map(|size| face.ascent(size))
+                .fold(0.0, f32::max);
+            let height = sizes.map(|size| face.line_height(size)).fold(0.0, f32::max)
+                * settings.line_spacing;
+            let baseline = top + ascent;
+
+            for (i, item) in line.iter().enumerate() {
+                let (x, next_x) = (xs[start + i], xs[start + i + 1]);
+
+                match item {
+                    Item::Char('\n', _) => continue,
+                    Item::Char(c, run) => {
+                        push_char(&mut layout, face, *c, run, (x, baseline), settings);
+
+                        if run.style.contains(TextStyle::UNDERLINED) && next_x > x {
+                            let (offset, thickness) = face.underline(run.size);
+                            let rect = FloatRect::new(
+                                x,
+                                baseline + offset - thickness / 2.0,
+                                next_x - x,
+                                thickness,
+                            );
+                            let vertices = rect_vertices(rect, FloatRect::default(), 0.0, 0.0);
+                            layout.push(Page::Solid, vertices, run.color, false);
+                        }
+                    }
+                    Item::Icon(name, run) => {
+                        if let Some((index, size)) = settings.icon(name, run.size) {
+                            let texture_size = settings.icons[index].1.size();
+                            let rect = FloatRect::new(x, baseline - size.y, size.x, size.y);
+                            let texture_rect =
+                                FloatRect::new(0.0, 0.0, texture_size.x, texture_size.y);
+                            let vertices = rect_vertices(rect, texture_rect, 0.0, 0.0);
+                            let tint = run.color.or(Some(Color::WHITE));
+                            layout.push(Page::Icon(index), vertices, tint, false);
+                        }
+                    }
+                }
+
+                layout.count += 1;
+            }
+
+            top += height;
+            start = end;
+        }
+
+        let vertices: Vec<Vertex> = layout.quads.iter().flat_map(|quad| quad.vertices).collect();
+        layout.bounds = vertex_array_bounds(&vertices);
+        layout
+    }
+
+    fn push_char(
+        layout: &mut GlyphLayout,
+        face: Face,
+        c: char,
+        run: &RunStyle,
+        (x, baseline): (f32, f32),
+        settings: &LayoutSettings,
+    ) {
+        let bold = run.style.contains(TextStyle::BOLD);
+        let shear = if run.style.contains(TextStyle::ITALIC) {
+            ITALIC_SHEAR
+        } else {
+            0.0
+        };
+
+        let mut push = |glyph: &GlyphInfo, x: f32, tint: Option<Color>, outline: bool| {
+            if glyph.bounds.width > 0.0 && glyph.bounds.height > 0.0 {
+                let rect = FloatRect::new(
+                    x + glyph.bounds.left,
+                    baseline + glyph.bounds.top,
+                    glyph.bounds.width,
+                    glyph.bounds.height,
+                );
+                let vertices = rect_vertices(rect, glyph.texture_rect, shear, baseline);
+                layout.push(glyph.page, vertices, tint, outline);
+            }
+        };
+
+        let (outline_color, outline_thickness) = settings.outline;
+
+        if outline_thickness != 0.0 {
+            if let Some(glyph) = face.glyph(c, run.size, bold, outline_thickness) {
+                push(&glyph, x, Some(outline_color), true);
+            }
+        }
+
+        if let Some(glyph) = face.glyph(c, run.size, bold, 0.0) {
+            push(&glyph, x, run.color, false);
+
+            if bold && face.bold_offset(run.size) > 0.0 {
+                push(&glyph, x + face.bold_offset(run.size), run.color, false);
+            }
+        }
+    }
+
+    // Corners of `rect` mapped onto `texture_rect`, sheared around the baseline for italics.
+    fn rect_vertices(
+        rect: FloatRect,
+        texture_rect: FloatRect,
+        shear: f32,
+        baseline: f32,
+    ) -> [Vertex; 4] {
+        let (left, top) = (rect.left, rect.top);
+        let (right, bottom) = (left + rect.width, top + rect.height);
+        let (u0, v0) = (texture_rect.left, texture_rect.top);
+        let (u1, v1) = (u0 + texture_rect.width, v0 + texture_rect.height);
+
+        [
+            (left, top, u0, v0),
+            (right, top, u1, v0),
+            (right, bottom, u1, v1),
+            (left, bottom, u0, v1),
+        ]
+        .map(|(x, y, u, v)| {
+            Vertex::new(
+                Vector2f::new(x - shear * (y - baseline), y),
+                Color::WHITE,
+                Vector2f::new(u, v),
+            )
+        })
+    }
+
+    impl GlyphLayout {
+        fn push(&mut self, page: Page, vertices: [Vertex; 4], tint: Option<Color>, outline: bool) {
+            self.quads.push(Quad {
+                page,
+                vertices,
+                tint,
+                outline,
+                index: self.count,
+            });
         }
     }
 }
 pub use text::*;
+
+mod primitives {
+    use super::*;
+
+    use sfml::graphics::{
+        CircleShape, ConvexShape, RectangleShape, Shape as SfmlShape, Transformable,
+    };
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct ShapeStyle {
+        pub fill: Color,
+        pub outline: Color,
+        pub thickness: f32,
+    }
+
+    impl Default for ShapeStyle {
+        fn default() -> Self {
+            ShapeStyle {
+                fill: Color::WHITE,
+                outline: Color::TRANSPARENT,
+                thickness: 0.0,
+            }
+        }
+    }
+
+    impl ShapeStyle {
+        #[inline]
+        pub fn filled(color: Color) -> Self {
+            ShapeStyle {
+                fill: color,
+                ..Default::default()
+            }
+        }
+
+        #[inline]
+        pub fn outlined(color: Color, thickness: f32) -> Self {
+            ShapeStyle {
+                fill: Color::TRANSPARENT,
+                outline: color,
+                thickness,
+            }
+        }
+
+        #[inline]
+        pub fn with_outline(mut self, color: Color, thickness: f32) -> Self {
+            self.outline = color;
+            self.thickness = thickness;
+            self
+        }
+
+        fn apply<'s, S: SfmlShape<'s>>(&self, shape: &mut S) {
+            shape.set_fill_color(self.fill);
+            shape.set_outline_color(self.outline);
+            shape.set_outline_thickness(self.thickness);
+        }
+    }
+
+    impl From<Color> for ShapeStyle {
+        fn from(color: Color) -> Self {
+            ShapeStyle::filled(color)
+        }
+    }
+
+    fn circle_points(radius: f32) -> u32 {
+        ((radius.abs().sqrt() * 4.0) as u32).clamp(12, 96)
+    }
+
+    impl Canvas<'_> {
+        pub fn draw_rect<P: Into<Vec2>, S: Into<Vec2>, St: Into<ShapeStyle>>(
+            &mut self,
+            position: P,
+            size: S,
+            style: St,
+        ) {
+            let (position, size) = (position.into(), size.into());
+
+            let mut rect_data = RectangleShape::new();
+            rect_data.set_position(Vector2f::from(position));
+            rect_data.set_size(Vector2f::from(size));
+            style.into().apply(&mut rect_data);
+            self.draw(&rect_data);
+        }
+
+        pub fn draw_circle<P: Into<Vec2>, St: Into<ShapeStyle>>(
+            &mut self,
+            center: P,
+            radius: f32,
+            style: St,
+        ) {
+            let center = center.into();
+
+            let mut circle_data = CircleShape::new(radius, circle_points(radius));
+            circle_data.set_origin((radius, radius));
+            circle_data.set_position(Vector2f::from(center));
+            style.into().apply(&mut circle_data);
+            self.draw(&circle_data);
+        }
+
+        // The fill is only correct for convex polygons, the outline works for any.
+        pub fn draw_polygon<St: Into<ShapeStyle>>(&mut self, points: &[Vec2], style: St) {
+            if points.len() < 3 {
+                return;
+            }
+
+            let mut polygon_data = ConvexShape::new(points.len() as u32);
+            for (i, point) in points.iter().enumerate() {
+                polygon_data.set_point(i as u32, Vector2f::from(*point));
+            }
+            style.into().apply(&mut polygon_data);
+            self.draw(&polygon_data);
+        }
+
+        pub fn draw_line<A: Into<Vec2>, B: Into<Vec2>>(
+            &mut self,
+            from: A,
+            to: B,
+            thickness: f32,
+            color: Color,
+        ) {
+            let (from, to) = (from.into(), to.into());
+            let direction = (to - from).normalize();
+
+            if direction == Vec2::ZERO {
+                return;
+            }
+
+            let offset = direction.perpendicular() * (thickness / 2.0);
+
+            let vertices = [from + offset, to + offset, to - offset, from - offset]
+                .map(|corner| Vertex::with_pos_color(corner.into(), color));
+            self.draw_vertices(&vertices, PrimitiveType::QUADS, None);
+        }
+
+        pub fn draw_arrow<A: Into<Vec2>, B: Into<Vec2>>(
+            &mut self,
+            from: A,
+            to: B,
+            thickness: f32,
+            color: Color,
+        ) {
+            let (from, to) = (from.into(), to.into());
+            let length = from.distance(to);
+
+            if length <= 0.0 {
+                return;
+            }
+
+            let direction = (to - from) / length;
+            let head_length = (thickness * 4.0).max(6.0).min(length);
+            let head_width = head_length * 0.6;
+
+            let base = to - direction * head_length;
+            let side = direction.perpendicular() * head_width;
+
+            self.draw_line(from, base, thickness, color);
+
+            let vertices = [to, base + side, base - side]
+                .map(|corner| Vertex::with_pos_color(corner.into(), color));
+            self.draw_vertices(&vertices, PrimitiveType::TRIANGLES, None);
+        }
+    }
+}
+pub use primitives::*;
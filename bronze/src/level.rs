@@ -0,0 +1,718 @@
+use std::{collections::BTreeMap, fmt, fs};
+
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    scene::{Collision, Entity, Scene, Spawn},
+    vfs::Vfs,
+};
+
+mod value {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(untagged)]
+    pub enum Value {
+        Bool(bool),
+        Int(i64),
+        Float(f64),
+        String(String),
+    }
+
+    impl Value {
+        #[inline]
+        pub fn as_bool(&self) -> Option<bool> {
+            match self {
+                Value::Bool(value) => Some(*value),
+                _ => None,
+            }
+        }
+
+        #[inline]
+        pub fn as_int(&self) -> Option<i64> {
+            match self {
+                Value::Int(value) => Some(*value),
+                _ => None,
+            }
+        }
+
+        #[inline]
+        pub fn as_float(&self) -> Option<f64> {
+            match self {
+                Value::Float(value) => Some(*value),
+                Value::Int(value) => Some(*value as f64),
+                _ => None,
+            }
+        }
+
+        #[inline]
+        pub fn as_str(&self) -> Option<&str> {
+            match self {
+                Value::String(value) => Some(value),
+                _ => None,
+            }
+        }
+
+        #[inline]
+        pub fn kind(&self) -> ValueKind {
+            match self {
+                Value::Bool(_) => ValueKind::Bool,
+                Value::Int(_) => ValueKind::Int,
+                Value::Float(_) => ValueKind::Float,
+                Value::String(_) => ValueKind::String,
+            }
+        }
+    }
+
+    impl From<bool> for Value {
+        #[inline]
+        fn from(value: bool) -> Self {
+            Value::Bool(value)
+        }
+    }
+
+    impl From<i64> for Value {
+        #[inline]
+        fn from(value: i64) -> Self {
+            Value::Int(value)
+        }
+    }
+
+    impl From<f64> for Value {
+        #[inline]
+        fn from(value: f64) -> Self {
+            Value::Float(value)
+        }
+    }
+
+    impl From<&str> for Value {
+        #[inline]
+        fn from(value: &str) -> Self {
+            Value::String(value.to_string())
+        }
+    }
+
+    impl fmt::Display for Value {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match self {
+                Value::Bool(value) => write!(f, "{}", value),
+                Value::Int(value) => write!(f, "{}", value),
+                Value::Float(value) => write!(f, "{}", value),
+                Value::String(value) => write!(f, "{:?}", value),
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum ValueKind {
+        Bool,
+        Int,
+        Float,
+        String,
+    }
+
+    impl ValueKind {
+        // Integers are accepted where a float is expected, `x: 1` reads the same as `x: 1.0`.
+        #[inline]
+        pub fn accepts(&self, value: &Value) -> bool {
+            let kind = value.kind();
+            kind == *self || (*self == ValueKind::Float && kind == ValueKind::Int)
+        }
+    }
+
+    impl fmt::Display for ValueKind {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match self {
+                ValueKind::Bool => write!(f, "bool"),
+                ValueKind::Int => write!(f, "integer"),
+                ValueKind::Float => write!(f, "float"),
+                ValueKind::String => write!(f, "string"),
+            }
+        }
+    }
+
+    pub type Properties = BTreeMap<String, Value>;
+}
+pub use value::*;
+
+mod error {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct LevelError {
+        pub path: String,
+        pub line: Option<usize>,
+        pub column: Option<usize>,
+        pub field: Option<String>,
+        pub message: String,
+    }
+
+    impl LevelError {
+        #[inline]
+        pub(super) fn new(message: impl Into<String>) -> Self {
+            LevelError {
+                path: String::new(),
+                line: None,
+                column: None,
+                field: None,
+                message: message.into(),
+            }
+        }
+
+        #[inline]
+        pub(super) fn at(mut self, line: Option<usize>, column: Option<usize>) -> Self {
+            self.line = line;
+            self.column = column;
+            self
+        }
+
+        #[inline]
+        pub(super) fn field(mut self, field: impl Into<String>) -> Self {
+            self.field = Some(field.into());
+            self
+        }
+    }
+
+    // Formatted as `path:line:column: field: message`, leaving out the parts that are unknown.
+    impl fmt::Display for LevelError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            if !self.path.is_empty() {
+                write!(f, "{}:", self.path)?;
+            }
+            if let Some(line) = self.line {
+                write!(f, "{}:", line)?;
+                if let Some(column) = self.column {
+                    write!(f, "{}:", column)?;
+                }
+            }
+            if !self.path.is_empty() || self.line.is_some() {
+                write!(f, " ")?;
+            }
+            if let Some(field) = &self.field {
+                write!(f, "{}: ", field)?;
+            }
+            write!(f, "{}", self.message)
+        }
+    }
+
+    impl std::error::Error for LevelError {}
+}
+pub use error::*;
+
+mod schema {
+    use super::*;
+
+    #[derive(Debug, Clone)]
+    struct Field {
+        name: String,
+        kind: ValueKind,
+        required: bool,
+        allowed: Vec<Value>,
+    }
+
+    #[derive(Debug, Clone, Default)]
+    pub struct PropertySchema {
+        fields: Vec<Field>,
+    }
+
+    impl PropertySchema {
+        #[inline]
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        #[inline]
+        pub fn with_required(self, name: &str, kind: ValueKind) -> Self {
+            self.with_field(name, kind, true)
+        }
+
+        #[inline]
+        pub fn with_optional(self, name: &str, kind: ValueKind) -> Self {
+            self.with_field(name, kind, false)
+        }
+
+        fn with_field(mut self, name: &str, kind: ValueKind, required: bool) -> Self {
+            self.fields.push(Field {
+                name: name.to_string(),
+                kind,
+                required,
+                allowed: Vec::new(),
+            });
+            self
+        }
+
+        // Limits an already declared property to the given values.
+        pub fn with_allowed<I, V>(mut self, name: &str, values: I) -> Self
+        where
+            I: IntoIterator<Item = V>,
+            V: Into<Value>,
+        {
+            if let Some(field) = self.fields.iter_mut().find(|field| field.name == name) {
+                field.allowed = values.into_iter().map(Into::into).collect();
+            }
+            self
+        }
+
+        pub(super) fn validate(
+            &self,
+            properties: &Properties,
+            prefix: &str,
+            line: Option<usize>,
+            errors: &mut Vec<LevelError>,
+        ) {
+            for Field {
+                name,
+                kind,
+                required,
+                allowed,
+            } in &self.fields
+            {
+                let field = format!("{}.{}", prefix, name);
+                match properties.get(name) {
+                    Some(value) if !kind.accepts(value) => errors.push(
+                        LevelError::new(format!(
+                            "expected {}, found {} `{}`",
+                            kind,
+                            value.kind(),
+                            value
+                        ))
+                        .at(line, None)
+                        .field(field),
+                    ),
+                    Some(value) if !allowed.is_empty() && !allowed.contains(value) => errors.push(
+                        LevelError::new(unknown_value(value, allowed))
+                            .at(line, None)
+                            .field(field),
+                    ),
+                    None if *required => errors.push(
+                        LevelError::new(format!("missing required {} property", kind))
+                            .at(line, None)
+                            .field(field),
+                    ),
+                    _ => {}
+                }
+            }
+
+            for name in properties.keys() {
+                if !self.fields.iter().any(|field| &field.name == name) {
+                    errors.push(
+                        LevelError::new(format!(
+                            "unknown property, expected one of {}",
+                            self.expected()
+                        ))
+                        .at(line, None)
+                        .field(format!("{}.{}", prefix, name)),
+                    );
+                }
+            }
+        }
+
+        fn expected(&self) -> String {
+            if self.fields.is_empty() {
+                return "no properties".to_string();
+            }
+            let names: Vec<_> = self
+                .fields
+                .iter()
+                .map(|field| format!("`{}`", field.name))
+                .collect();
+            names.join(", ")
+        }
+    }
+
+    fn unknown_value(value: &Value, allowed: &[Value]) -> String {
+        let allowed: Vec<_> = allowed.iter().map(Value::to_string).collect();
+        format!(
+            "unknown value {}, expected one of {}",
+            value,
+            allowed.join(", ")
+        )
+    }
+
+    // Describes which spawn kinds a game understands and the properties each of them takes.
+    // Kinds that are not registered are reported as errors.
+    #[derive(Debug, Clone, Default)]
+    pub struct LevelSchema {
+        kinds: BTreeMap<String, PropertySchema>,
+        properties: PropertySchema,
+        background: bool,
+        backgrounds: Vec<Value>,
+    }
+
+    impl LevelSchema {
+        #[inline]
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        #[inline]
+        pub fn with_kind(mut self, kind: &str, properties: PropertySchema) -> Self {
+            self.kinds.insert(kind.to_string(), properties);
+            self
+        }
+
+        #[inline]
+        pub fn with_properties(mut self, properties: PropertySchema) -> Self {
+            self.properties = properties;
+            self
+        }
+
+        #[inline]
+        pub fn with_background(mut self) -> Self {
+            self.background = true;
+            self
+        }
+
+        // Requires a background and limits it to the given names.
+        pub fn with_backgrounds<I, S>(mut self, names: I) -> Self
+        where
+            I: IntoIterator<Item = S>,
+            S: AsRef<str>,
+        {
+            self.background = true;
+            self.backgrounds = names
+                .into_iter()
+                .map(|name| Value::from(name.as_ref()))
+                .collect();
+            self
+        }
+
+        pub fn validate(&self, level: &Level) -> Vec<LevelError> {
+            let mut errors = Vec::new();
+
+            match &level.background {
+                None if self.background => {
+                    errors.push(LevelError::new("missing background").field("background"))
+                }
+                Some(background) if !self.backgrounds.is_empty() => {
+                    let value = Value::from(background.as_str());
+                    if !self.backgrounds.contains(&value) {
+                        errors.push(
+                            LevelError::new(unknown_value(&value, &self.backgrounds))
+                                .at(level.background_line(), None)
+                                .field("background"),
+                        );
+                    }
+                }
+                _ => {}
+            }
+
+            self.properties
+                .validate(&level.properties, "properties", None, &mut errors);
+
+            for (i, spawn) in level.spawns.iter().enumerate() {
+                let prefix = format!("spawns[{}]", i);
+                match self.kinds.get(&spawn.kind) {
+                    Some(schema) => schema.validate(
+                        &spawn.properties,
+                        &format!("{}.properties", prefix),
+                        spawn.line(),
+                        &mut errors,
+                    ),
+                    None => {
+                        let kinds: Vec<_> = self.kinds.keys().map(|k| format!("`{}`", k)).collect();
+                        errors.push(
+                            LevelError::new(format!(
+                                "unknown kind `{}`, expected one of {}",
+                                spawn.kind,
+                                kinds.join(", ")
+                            ))
+                            .at(spawn.line(), None)
+                            .field(format!("{}.kind", prefix)),
+                        );
+                    }
+                }
+            }
+
+            errors
+        }
+    }
+}
+pub use schema::*;
+
+mod data {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    pub struct SpawnPoint {
+        pub kind: String,
+        pub x: f32,
+        pub y: f32,
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        pub properties: Properties,
+        #[serde(skip)]
+        line: Option<usize>,
+    }
+
+    impl SpawnPoint {
+        #[inline]
+        pub fn new(kind: &str, x: f32, y: f32) -> Self {
+            SpawnPoint {
+                kind: kind.to_string(),
+                x,
+                y,
+                properties: Properties::new(),
+                line: None,
+            }
+        }
+
+        #[inline]
+        pub fn with_property(mut self, name: &str, value: impl Into<Value>) -> Self {
+            self.properties.insert(name.to_string(), value.into());
+            self
+        }
+
+        #[inline]
+        pub fn property(&self, name: &str) -> Option<&Value> {
+            self.properties.get(name)
+        }
+
+        // Line of the file this spawn was read from, `None` for spawns built in code.
+        #[inline]
+        pub fn line(&self) -> Option<usize> {
+            self.line
+        }
+    }
+
+    #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+    #[serde(rename = "Level", deny_unknown_fields)]
+    pub struct Level {
+        #[serde(default)]
+        pub background: Option<String>,
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        pub properties: Properties,
+        #[serde(default)]
+        pub spawns: Vec<SpawnPoint>,
+        #[serde(skip)]
+        background_line: Option<usize>,
+    }
+
+    impl Level {
+        #[inline]
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        #[inline]
+        pub fn load(path: &str) -> Result<Self, Vec<LevelError>> {
+            Self::load_from(&Vfs::new(), path)
+        }
+
+        // Reads the level through `vfs`, e.g. from the archive holding the rest of the assets.
+        pub fn load_from(vfs: &Vfs, path: &str) -> Result<Self, Vec<LevelError>> {
+            let source = vfs.read_to_string(path).map_err(|error| {
+                vec![LevelError {
+                    path: path.to_string(),
+                    ..LevelError::new(error.cause())
+                }]
+            })?;
+
+            Self::parse(&source).map_err(|errors| with_path(errors, path))
+        }
+
+        #[inline]
+        pub fn load_validated(path: &str, schema: &LevelSchema) -> Result<Self, Vec<LevelError>> {
+            Self::load_validated_from(&Vfs::new(), path, schema)
+        }
+
+        // Loads the level and checks it against `schema`, reporting every problem at once
+        // rather than stopping at the first one.
+        pub fn load_validated_from(
+            vfs: &Vfs,
+            path: &str,
+            schema: &LevelSchema,
+        ) -> Result<Self, Vec<LevelError>> {
+            let level = Self::load_from(vfs, path)?;
+
+            let errors = schema.validate(&level);
+            if errors.is_empty() {
+                Ok(level)
+            } else {
+                Err(with_path(errors, path))
+            }
+        }
+
+        pub fn parse(source: &str) -> Result<Self, Vec<LevelError>> {
+            let spanned = |error: ron::error::SpannedError| {
+                LevelError::new(error.code.to_string())
+                    .at(Some(error.position.line), Some(error.position.col))
+            };
+
+            let mut deserializer =
+                ron::Deserializer::from_str(source).map_err(|error| vec![spanned(error)])?;
+
+            let mut level: Level = match serde_path_to_error::deserialize(&mut deserializer) {
+                Ok(level) => level,
+                Err(error) => {
+                    let field = error.path().to_string();
+                    let error = spanned(deserializer.span_error(error.into_inner()));
+                    return Err(vec![if field == "." {
+                        error
+                    } else {
+                        error.field(field)
+                    }]);
+                }
+            };
+            deserializer
+                .end()
+                .map_err(|error| vec![spanned(deserializer.span_error(error))])?;
+
+            let (_, lines) = key_lines(source, "spawns");
+            for (spawn, line) in level.spawns.iter_mut().zip(lines) {
+                spawn.line = Some(line);
+            }
+            level.background_line = key_lines(source, "background").0;
+
+            Ok(level)
+        }
+
+        #[inline]
+        pub fn property(&self, name: &str) -> Option<&Value> {
+            self.properties.get(name)
+        }
+
+        // Line of the `background` field, `None` for levels built in code.
+        #[inline]
+        pub fn background_line(&self) -> Option<usize> {
+            self.background_line
+        }
+
+        // Spawns are written one per line so that errors point at a single entry.
+        pub fn to_ron(&self) -> String {
+            let config = PrettyConfig::new().struct_names(true).depth_limit(2);
+            ron::ser::to_string_pretty(self, config).unwrap_or_default()
+        }
+
+        pub fn save(&self, path: &str) -> bool {
+            fs::write(path, self.to_ron() + "\n").is_ok()
+        }
+
+        pub fn spawn<S, D, Ctx, F>(&self, scene: &mut Scene<S, D, Ctx>, mut spawn: F)
+        where
+            S: Entity<Ctx> + Collision<D, Ctx>,
+            D: Entity<Ctx> + Collision<D, Ctx> + Collision<S, Ctx>,
+            F: FnMut(&SpawnPoint) -> Option<Spawn<S, D>>,
+        {
+            for point in &self.spawns {
+                if let Some(entity) = spawn(point) {
+                    scene.spawn(entity);
+                }
+            }
+        }
+    }
+
+    fn with_path(errors: Vec<LevelError>, path: &str) -> Vec<LevelError> {
+        errors
+            .into_iter()
+            .map(|error| LevelError {
+                path: path.to_string(),
+                ..error
+            })
+            .collect()
+    }
+
+    // Finds the line of the top level field `key` and, when it holds a list, the line each
+    // element starts on. Serde does not hand out positions, so this walks the RON source just
+    // far enough to track nesting.
+    fn key_lines(source: &str, key: &str) -> (Option<usize>, Vec<usize>) {
+        let mut key_line = None;
+        let mut lines = Vec::new();
+        let mut chars = source.chars().peekable();
+        let mut line = 1;
+        let mut depth = 0;
+        let mut word = String::new();
+        let mut last_word = String::new();
+        let mut after_key = false;
+        let mut list_depth = None;
+        let mut expect_element = false;
+
+        while let Some(c) = chars.next() {
+            if c.is_whitespace() {
+                if c == '\n' {
+                    line += 1;
+                }
+                if !word.is_empty() {
+                    last_word = std::mem::take(&mut word);
+                }
+                continue;
+            }
+
+            if c == '/' && matches!(chars.peek(), Some('/') | Some('*')) {
+                let mut star = false;
+                let block = chars.next() == Some('*');
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        line += 1;
+                        if !block {
+                            break;
+                        }
+                    }
+                    if block && star && c == '/' {
+                        break;
+                    }
+                    star = c == '*';
+                }
+                continue;
+            }
+
+            if list_depth == Some(depth) && expect_element && c != ',' && c != ']' {
+                lines.push(line);
+                expect_element = false;
+            }
+
+            if c.is_alphanumeric() || c == '_' {
+                word.push(c);
+                after_key = false;
+                continue;
+            }
+            if !word.is_empty() {
+                last_word = std::mem::take(&mut word);
+            }
+
+            match c {
+                '"' => {
+                    let mut escaped = false;
+                    for c in chars.by_ref() {
+                        if c == '\n' {
+                            line += 1;
+                        }
+                        if c == '"' && !escaped {
+                            break;
+                        }
+                        escaped = c == '\\' && !escaped;
+                    }
+                }
+                ':' => {
+                    after_key = depth == 1 && last_word == key;
+                    if after_key {
+                        key_line.get_or_insert(line);
+                    }
+                    last_word.clear();
+                    continue;
+                }
+                '[' if after_key => {
+                    depth += 1;
+                    list_depth = Some(depth);
+                    expect_element = true;
+                }
+                '(' | '[' | '{' => depth += 1,
+                ')' | ']' | '}' => {
+                    if list_depth == Some(depth) {
+                        break;
+                    }
+                    depth -= 1;
+                }
+                ',' if list_depth == Some(depth) => expect_element = true,
+                _ => {}
+            }
+
+            after_key = false;
+            last_word.clear();
+        }
+
+        (key_line, lines)
+    }
+}
+pub use data::*;
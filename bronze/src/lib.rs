@@ -1,11 +1,22 @@
+pub mod capture;
 pub mod engine;
 pub mod game;
 pub mod graphics;
 pub mod input;
+pub mod level;
+pub mod math;
+pub mod particles;
+pub mod physics;
 pub mod resources;
 pub mod scene;
 pub mod shape;
+pub mod state;
+pub mod tiled;
+pub mod tilemap;
 pub mod timer;
+pub mod transition;
+pub mod tween;
+pub mod vfs;
 pub mod window;
 
 pub mod system {
@@ -0,0 +1,485 @@
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use sfml::{
+    graphics::{Color, Transform as SfmlTransform},
+    system::Vector2f,
+};
+
+use crate::shape::Point;
+
+mod vec2 {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, Default, PartialEq)]
+    pub struct Vec2 {
+        pub x: f32,
+        pub y: f32,
+    }
+
+    impl Vec2 {
+        pub const ZERO: Vec2 = Vec2 { x: 0.0, y: 0.0 };
+        pub const ONE: Vec2 = Vec2 { x: 1.0, y: 1.0 };
+        pub const UP: Vec2 = Vec2 { x: 0.0, y: -1.0 };
+        pub const DOWN: Vec2 = Vec2 { x: 0.0, y: 1.0 };
+        pub const LEFT: Vec2 = Vec2 { x: -1.0, y: 0.0 };
+        pub const RIGHT: Vec2 = Vec2 { x: 1.0, y: 0.0 };
+
+        #[inline]
+        pub const fn new(x: f32, y: f32) -> Self {
+            Vec2 { x, y }
+        }
+
+        #[inline]
+        pub fn from_angle(degrees: f32) -> Self {
+            let (sin, cos) = degrees.to_radians().sin_cos();
+            Vec2::new(cos, sin)
+        }
+
+        #[inline]
+        pub fn dot(self, other: Vec2) -> f32 {
+            self.x * other.x + self.y * other.y
+        }
+
+        #[inline]
+        pub fn cross(self, other: Vec2) -> f32 {
+            self.x * other.y - self.y * other.x
+        }
+
+        #[inline]
+        pub fn length_squared(self) -> f32 {
+            self.dot(self)
+        }
+
+        #[inline]
+        pub fn length(self) -> f32 {
+            self.length_squared().sqrt()
+        }
+
+        #[inline]
+        pub fn distance(self, other: Vec2) -> f32 {
+            (other - self).length()
+        }
+
+        pub fn normalize(self) -> Vec2 {
+            let length = self.length();
+
+            if length > 0.0 {
+                self / length
+            } else {
+                Vec2::ZERO
+            }
+        }
+
+        pub fn with_length(self, length: f32) -> Vec2 {
+            self.normalize() * length
+        }
+
+        pub fn clamp_length(self, max: f32) -> Vec2 {
+            if self.length_squared() > max * max {
+                self.with_length(max)
+            } else {
+                self
+            }
+        }
+
+        #[inline]
+        pub fn lerp(self, other: Vec2, t: f32) -> Vec2 {
+            self + (other - self) * t
+        }
+
+        pub fn rotate(self, degrees: f32) -> Vec2 {
+            let (sin, cos) = degrees.to_radians().sin_cos();
+            Vec2::new(self.x * cos - self.y * sin, self.x * sin + self.y * cos)
+        }
+
+        #[inline]
+        pub fn perpendicular(self) -> Vec2 {
+            Vec2::new(-self.y, self.x)
+        }
+
+        pub fn angle(self) -> f32 {
+            self.y.atan2(self.x).to_degrees().rem_euclid(360.0)
+        }
+
+        pub fn angle_to(self, other: Vec2) -> f32 {
+            self.cross(other).atan2(self.dot(other)).to_degrees()
+        }
+
+        pub fn project(self, onto: Vec2) -> Vec2 {
+            let length_squared = onto.length_squared();
+
+            if length_squared > 0.0 {
+                onto * (self.dot(onto) / length_squared)
+            } else {
+                Vec2::ZERO
+            }
+        }
+
+        pub fn reflect(self, normal: Vec2) -> Vec2 {
+            self - normal * (2.0 * self.dot(normal))
+        }
+    }
+
+    impl Add for Vec2 {
+        type Output = Vec2;
+
+        #[inline]
+        fn add(self, other: Vec2) -> Vec2 {
+            Vec2::new(self.x + other.x, self.y + other.y)
+        }
+    }
+
+    impl Sub for Vec2 {
+        type Output = Vec2;
+
+        #[inline]
+        fn sub(self, other: Vec2) -> Vec2 {
+            Vec2::new(self.x - other.x, self.y - other.y)
+        }
+    }
+
+    impl Mul<f32> for Vec2 {
+        type Output = Vec2;
+
+        #[inline]
+        fn mul(self, scalar: f32) -> Vec2 {
+            Vec2::new(self.x * scalar, self.y * scalar)
+        }
+    }
+
+    impl Mul<Vec2> for f32 {
+        type Output = Vec2;
+
+        #[inline]
+        fn mul(self, vector: Vec2) -> Vec2 {
+            vector * self
+        }
+    }
+
+    impl Div<f32> for Vec2 {
+        type Output = Vec2;
+
+        #[inline]
+        fn div(self, scalar: f32) -> Vec2 {
+            Vec2::new(self.x / scalar, self.y / scalar)
+        }
+    }
+
+    impl Neg for Vec2 {
+        type Output = Vec2;
+
+        #[inline]
+        fn neg(self) -> Vec2 {
+            Vec2::new(-self.x, -self.y)
+        }
+    }
+
+    impl AddAssign for Vec2 {
+        #[inline]
+        fn add_assign(&mut self, other: Vec2) {
+            *self = *self + other;
+        }
+    }
+
+    impl SubAssign for Vec2 {
+        #[inline]
+        fn sub_assign(&mut self, other: Vec2) {
+            *self = *self - other;
+        }
+    }
+
+    impl MulAssign<f32> for Vec2 {
+        #[inline]
+        fn mul_assign(&mut self, scalar: f32) {
+            *self = *self * scalar;
+        }
+    }
+
+    impl DivAssign<f32> for Vec2 {
+        #[inline]
+        fn div_assign(&mut self, scalar: f32) {
+            *self = *self / scalar;
+        }
+    }
+
+    impl From<(f32, f32)> for Vec2 {
+        fn from((x, y): (f32, f32)) -> Self {
+            Vec2::new(x, y)
+        }
+    }
+
+    impl From<Vec2> for (f32, f32) {
+        fn from(Vec2 { x, y }: Vec2) -> Self {
+            (x, y)
+        }
+    }
+
+    impl From<Point> for Vec2 {
+        fn from(Point { x, y }: Point) -> Self {
+            Vec2::new(x, y)
+        }
+    }
+
+    impl From<&Point> for Vec2 {
+        fn from(point: &Point) -> Self {
+            Vec2::new(point.x, point.y)
+        }
+    }
+
+    impl From<Vec2> for Point {
+        fn from(Vec2 { x, y }: Vec2) -> Self {
+            Point::new(x, y)
+        }
+    }
+
+    impl From<Vector2f> for Vec2 {
+        fn from(vector: Vector2f) -> Self {
+            Vec2::new(vector.x, vector.y)
+        }
+    }
+
+    impl From<Vec2> for Vector2f {
+        fn from(Vec2 { x, y }: Vec2) -> Self {
+            Vector2f::new(x, y)
+        }
+    }
+}
+pub use vec2::*;
+
+mod transform {
+    use super::*;
+
+    // | m00 m01 m02 |
+    // | m10 m11 m12 |
+    // |  0   0   1  |
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct Transform2D {
+        m00: f32,
+        m01: f32,
+        m02: f32,
+        m10: f32,
+        m11: f32,
+        m12: f32,
+    }
+
+    impl Default for Transform2D {
+        fn default() -> Self {
+            Transform2D::IDENTITY
+        }
+    }
+
+    impl Transform2D {
+        pub const IDENTITY: Transform2D = Transform2D {
+            m00: 1.0,
+            m01: 0.0,
+            m02: 0.0,
+            m10: 0.0,
+            m11: 1.0,
+            m12: 0.0,
+        };
+
+        pub fn translation(x: f32, y: f32) -> Self {
+            Transform2D {
+                m02: x,
+                m12: y,
+                ..Transform2D::IDENTITY
+            }
+        }
+
+        pub fn rotation(degrees: f32) -> Self {
+            let (sin, cos) = degrees.to_radians().sin_cos();
+
+            Transform2D {
+                m00: cos,
+                m01: -sin,
+                m10: sin,
+                m11: cos,
+                ..Transform2D::IDENTITY
+            }
+        }
+
+        pub fn scaling(x: f32, y: f32) -> Self {
+            Transform2D {
+                m00: x,
+                m11: y,
+                ..Transform2D::IDENTITY
+            }
+        }
+
+        pub fn rotation_around(degrees: f32, center: Vec2) -> Self {
+            Transform2D::translation(center.x, center.y)
+                .compose(&Transform2D::rotation(degrees))
+                .compose(&Transform2D::translation(-center.x, -center.y))
+        }
+
+        pub fn from_parts(position: Vec2, rotation: f32, scale: Vec2) -> Self {
+            Transform2D::translation(position.x, position.y)
+                .compose(&Transform2D::rotation(rotation))
+                .compose(&Transform2D::scaling(scale.x, scale.y))
+        }
+
+        pub fn translate(self, x: f32, y: f32) -> Self {
+            Transform2D::translation(x, y).compose(&self)
+        }
+
+        pub fn rotate(self, degrees: f32) -> Self {
+            Transform2D::rotation(degrees).compose(&self)
+        }
+
+        pub fn scale(self, x: f32, y: f32) -> Self {
+            Transform2D::scaling(x, y).compose(&self)
+        }
+
+        // The result applies `other` first and then `self`.
+        pub fn compose(&self, other: &Transform2D) -> Self {
+            Transform2D {
+                m00: self.m00 * other.m00 + self.m01 * other.m10,
+                m01: self.m00 * other.m01 + self.m01 * other.m11,
+                m02: self.m00 * other.m02 + self.m01 * other.m12 + self.m02,
+                m10: self.m10 * other.m00 + self.m11 * other.m10,
+                m11: self.m10 * other.m01 + self.m11 * other.m11,
+                m12: self.m10 * other.m02 + self.m11 * other.m12 + self.m12,
+            }
+        }
+
+        pub fn determinant(&self) -> f32 {
+            self.m00 * self.m11 - self.m01 * self.m10
+        }
+
+        pub fn invert(&self) -> Option<Self> {
+            let det = self.determinant();
+
+            if det.abs() <= f32::EPSILON {
+                return None;
+            }
+
+            let m00 = self.m11 / det;
+            let m01 = -self.m01 / det;
+            let m10 = -self.m10 / det;
+            let m11 = self.m00 / det;
+
+            Some(Transform2D {
+                m00,
+                m01,
+                m02: -(m00 * self.m02 + m01 * self.m12),
+                m10,
+                m11,
+                m12: -(m10 * self.m02 + m11 * self.m12),
+            })
+        }
+
+        pub fn apply<V: Into<Vec2> + From<Vec2>>(&self, point: V) -> V {
+            let Vec2 { x, y } = point.into();
+
+            Vec2::new(
+                self.m00 * x + self.m01 * y + self.m02,
+                self.m10 * x + self.m11 * y + self.m12,
+            )
+            .into()
+        }
+
+        pub fn apply_vector(&self, vector: Vec2) -> Vec2 {
+            Vec2::new(
+                self.m00 * vector.x + self.m01 * vector.y,
+                self.m10 * vector.x + self.m11 * vector.y,
+            )
+        }
+
+        pub fn position(&self) -> Vec2 {
+            Vec2::new(self.m02, self.m12)
+        }
+
+        pub fn rotation_degrees(&self) -> f32 {
+            self.m10.atan2(self.m00).to_degrees().rem_euclid(360.0)
+        }
+
+        pub fn scale_factors(&self) -> Vec2 {
+            let x = Vec2::new(self.m00, self.m10).length();
+
+            if x > 0.0 {
+                Vec2::new(x, self.determinant() / x)
+            } else {
+                Vec2::new(0.0, Vec2::new(self.m01, self.m11).length())
+            }
+        }
+    }
+
+    impl Mul for Transform2D {
+        type Output = Transform2D;
+
+        fn mul(self, other: Transform2D) -> Transform2D {
+            self.compose(&other)
+        }
+    }
+
+    impl Mul<Vec2> for Transform2D {
+        type Output = Vec2;
+
+        fn mul(self, point: Vec2) -> Vec2 {
+            self.apply(point)
+        }
+    }
+
+    impl From<Transform2D> for SfmlTransform {
+        fn from(t: Transform2D) -> Self {
+            SfmlTransform::new(t.m00, t.m01, t.m02, t.m10, t.m11, t.m12, 0.0, 0.0, 1.0)
+        }
+    }
+}
+pub use transform::*;
+
+mod lerp {
+    use super::*;
+
+    pub trait Lerp: Clone {
+        fn lerp(&self, other: &Self, t: f32) -> Self;
+    }
+
+    impl Lerp for f32 {
+        #[inline]
+        fn lerp(&self, other: &f32, t: f32) -> f32 {
+            self + (other - self) * t
+        }
+    }
+
+    impl Lerp for Vec2 {
+        #[inline]
+        fn lerp(&self, other: &Vec2, t: f32) -> Vec2 {
+            Vec2::lerp(*self, *other, t)
+        }
+    }
+
+    impl Lerp for Point {
+        #[inline]
+        fn lerp(&self, other: &Point, t: f32) -> Point {
+            Point::new(self.x.lerp(&other.x, t), self.y.lerp(&other.y, t))
+        }
+    }
+
+    impl Lerp for Color {
+        fn lerp(&self, other: &Color, t: f32) -> Color {
+            let channel =
+                |a: u8, b: u8| (a as f32).lerp(&(b as f32), t).round().clamp(0.0, 255.0) as u8;
+
+            Color::rgba(
+                channel(self.r, other.r),
+                channel(self.g, other.g),
+                channel(self.b, other.b),
+                channel(self.a, other.a),
+            )
+        }
+    }
+
+    impl Lerp for Transform2D {
+        fn lerp(&self, other: &Transform2D, t: f32) -> Transform2D {
+            let position = self.position().lerp(other.position(), t);
+            let scale = self.scale_factors().lerp(other.scale_factors(), t);
+
+            let from = self.rotation_degrees();
+            let delta = (other.rotation_degrees() - from + 540.0).rem_euclid(360.0) - 180.0;
+
+            Transform2D::from_parts(position, from + delta * t, scale)
+        }
+    }
+}
+pub use lerp::*;
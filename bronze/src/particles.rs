@@ -0,0 +1,364 @@
+use std::time::Duration;
+
+use sfml::{
+    graphics::{PrimitiveType, Vertex},
+    system::Vector2f,
+};
+
+use crate::{
+    graphics::{Color, Sprite},
+    math::{Lerp, Vec2},
+    window::Canvas,
+};
+
+mod curve {
+    use super::*;
+
+    #[derive(Debug, Clone)]
+    pub struct Curve<T: Lerp> {
+        keys: Vec<(f32, T)>,
+    }
+
+    impl<T: Lerp> Curve<T> {
+        pub fn new(mut keys: Vec<(f32, T)>) -> Self {
+            keys.sort_by(|a, b| a.0.total_cmp(&b.0));
+            Curve { keys }
+        }
+
+        pub fn constant(value: T) -> Self {
+            Curve {
+                keys: vec![(0.0, value)],
+            }
+        }
+
+        pub fn linear(from: T, to: T) -> Self {
+            Curve {
+                keys: vec![(0.0, from), (1.0, to)],
+            }
+        }
+
+        pub fn with_key(mut self, t: f32, value: T) -> Self {
+            let index = self.keys.partition_point(|(key, _)| *key <= t);
+            self.keys.insert(index, (t, value));
+            self
+        }
+
+        pub fn sample(&self, t: f32) -> Option<T> {
+            let index = self.keys.partition_point(|(key, _)| *key <= t);
+
+            match (
+                index.checked_sub(1).map(|i| &self.keys[i]),
+                self.keys.get(index),
+            ) {
+                (Some((t0, a)), Some((t1, b))) => Some(a.lerp(b, (t - t0) / (t1 - t0))),
+                (Some((_, a)), None) => Some(a.clone()),
+                (None, Some((_, b))) => Some(b.clone()),
+                (None, None) => None,
+            }
+        }
+    }
+}
+pub use curve::*;
+
+mod rng {
+    use std::{
+        sync::atomic::{AtomicU32, Ordering},
+        time::{SystemTime, UNIX_EPOCH},
+    };
+
+    static SEED: AtomicU32 = AtomicU32::new(0x9e37_79b9);
+
+    pub struct Rng {
+        state: u32,
+    }
+
+    impl Rng {
+        pub fn new() -> Self {
+            let time = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|time| time.subsec_nanos())
+                .unwrap_or(0);
+            let seed = SEED.fetch_add(0x6d2b_79f5, Ordering::Relaxed) ^ time;
+
+            Rng { state: seed | 1 }
+        }
+
+        pub fn next_f32(&mut self) -> f32 {
+            self.state ^= self.state << 13;
+            self.state ^= self.state >> 17;
+            self.state ^= self.state << 5;
+            (self.state >> 8) as f32 / (1 << 24) as f32
+        }
+
+        pub fn range(&mut self, (min, max): (f32, f32)) -> f32 {
+            min + (max - min) * self.next_f32()
+        }
+    }
+}
+use rng::Rng;
+
+mod emitter {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy)]
+    pub enum Affector {
+        Gravity(Vec2),
+        Drag(f32),
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum ParticleSpace {
+        World,
+        Local,
+    }
+
+    #[derive(Debug, Clone)]
+    pub struct EmitterConfig {
+        pub rate: f32,
+        pub max_particles: usize,
+        pub lifetime: (f32, f32),
+        pub speed: (f32, f32),
+        pub direction: f32,
+        pub spread: f32,
+        pub space: ParticleSpace,
+        pub color: Curve<Color>,
+        pub alpha: Curve<f32>,
+        pub scale: Curve<f32>,
+        pub affectors: Vec<Affector>,
+    }
+
+    impl Default for EmitterConfig {
+        fn default() -> Self {
+            EmitterConfig {
+                rate: 0.0,
+                max_particles: 512,
+                lifetime: (1.0, 1.0),
+                speed: (0.0, 0.0),
+                direction: 0.0,
+                spread: 360.0,
+                space: ParticleSpace::World,
+                color: Curve::constant(Color::WHITE),
+                alpha: Curve::constant(1.0),
+                scale: Curve::constant(1.0),
+                affectors: Vec::new(),
+            }
+        }
+    }
+
+    struct Particle {
+        position: Vec2,
+        velocity: Vec2,
+        age: f32,
+        lifetime: f32,
+    }
+
+    impl Particle {
+        fn progress(&self) -> f32 {
+            (self.age / self.lifetime).min(1.0)
+        }
+    }
+
+    pub struct Emitter {
+        config: EmitterConfig,
+        sprite: Option<Sprite>,
+        position: Vec2,
+        particles: Vec<Particle>,
+        emitting: bool,
+        accumulator: f32,
+        rng: Rng,
+    }
+
+    impl Emitter {
+        pub fn new(config: EmitterConfig) -> Self {
+            Emitter {
+                emitting: config.rate > 0.0,
+                config,
+                sprite: None,
+                position: Vec2::ZERO,
+                particles: Vec::new(),
+                accumulator: 0.0,
+                rng: Rng::new(),
+            }
+        }
+
+        pub fn with_sprite(config: EmitterConfig, sprite: &Sprite) -> Self {
+            Emitter {
+                sprite: Some(sprite.clone()),
+                ..Emitter::new(config)
+            }
+        }
+
+        #[inline]
+        pub fn config(&self) -> &EmitterConfig {
+            &self.config
+        }
+
+        #[inline]
+        pub fn config_mut(&mut self) -> &mut EmitterConfig {
+            &mut self.config
+        }
+
+        #[inline]
+        pub fn position(&self) -> Vec2 {
+            self.position
+        }
+
+        #[inline]
+        pub fn set_position(&mut self, x: f32, y: f32) {
+            self.position = Vec2::new(x, y);
+        }
+
+        #[inline]
+        pub fn start(&mut self) {
+            self.emitting = true;
+        }
+
+        #[inline]
+        pub fn stop(&mut self) {
+            self.emitting = false;
+            self.accumulator = 0.0;
+        }
+
+        #[inline]
+        pub fn is_emitting(&self) -> bool {
+            self.emitting
+        }
+
+        #[inline]
+        pub fn len(&self) -> usize {
+            self.particles.len()
+        }
+
+        #[inline]
+        pub fn is_empty(&self) -> bool {
+            self.particles.is_empty()
+        }
+
+        #[inline]
+        pub fn is_finished(&self) -> bool {
+            !self.emitting && self.particles.is_empty()
+        }
+
+        pub fn clear(&mut self) {
+            self.particles.clear();
+        }
+
+        pub fn burst(&mut self, count: usize) {
+            for _ in 0..count {
+                self.spawn();
+            }
+        }
+
+        fn spawn(&mut self) {
+            if self.particles.len() >= self.config.max_particles {
+                return;
+            }
+
+            let half_spread = self.config.spread / 2.0;
+            let angle = self.config.direction + self.rng.range((-half_spread, half_spread));
+            let speed = self.rng.range(self.config.speed);
+            let lifetime = self.rng.range(self.config.lifetime).max(f32::EPSILON);
+
+            let position = match self.config.space {
+                ParticleSpace::World => self.position,
+                ParticleSpace::Local => Vec2::ZERO,
+            };
+
+            self.particles.push(Particle {
+                position,
+                velocity: Vec2::from_angle(angle) * speed,
+                age: 0.0,
+                lifetime,
+            });
+        }
+
+        pub fn update(&mut self, frame_time: Duration) {
+            let dt = frame_time.as_secs_f32();
+
+            for particle in self.particles.iter_mut() {
+                particle.age += dt;
+
+                for affector in self.config.affectors.iter() {
+                    match affector {
+                        Affector::Gravity(gravity) => particle.velocity += *gravity * dt,
+                        Affector::Drag(drag) => particle.velocity *= (1.0 - drag * dt).max(0.0),
+                    }
+                }
+
+                particle.position += particle.velocity * dt;
+            }
+
+            self.particles
+                .retain(|particle| particle.age < particle.lifetime);
+
+            if self.emitting && self.config.rate > 0.0 {
+                self.accumulator += dt * self.config.rate;
+
+                while self.accumulator >= 1.0 {
+                    self.accumulator -= 1.0;
+                    self.spawn();
+                }
+            }
+        }
+
+        fn color_at(&self, t: f32) -> Color {
+            let mut color = self.config.color.sample(t).unwrap_or(Color::WHITE);
+            let alpha = self.config.alpha.sample(t).unwrap_or(1.0).clamp(0.0, 1.0);
+            color.a = (color.a as f32 * alpha) as u8;
+            color
+        }
+
+        pub fn draw(&self, target: &mut Canvas) {
+            if self.particles.is_empty() {
+                return;
+            }
+
+            let offset = match self.config.space {
+                ParticleSpace::World => Vec2::ZERO,
+                ParticleSpace::Local => self.position,
+            };
+
+            match &self.sprite {
+                Some(sprite) => {
+                    let size = sprite.size();
+                    let mut vertices = Vec::with_capacity(self.particles.len() * 4);
+
+                    for particle in self.particles.iter() {
+                        let t = particle.progress();
+                        let color = self.color_at(t);
+                        let scale = self.config.scale.sample(t).unwrap_or(1.0);
+
+                        let center = particle.position + offset;
+                        let half = Vec2::new(size.x, size.y) * (scale / 2.0);
+
+                        let corners = [
+                            (Vec2::new(-half.x, -half.y), Vector2f::new(0.0, 0.0)),
+                            (Vec2::new(half.x, -half.y), Vector2f::new(size.x, 0.0)),
+                            (Vec2::new(half.x, half.y), Vector2f::new(size.x, size.y)),
+                            (Vec2::new(-half.x, half.y), Vector2f::new(0.0, size.y)),
+                        ];
+
+                        for (corner, tex_coords) in corners {
+                            vertices.push(Vertex::new((center + corner).into(), color, tex_coords));
+                        }
+                    }
+
+                    target.draw_vertices(&vertices, PrimitiveType::QUADS, Some(sprite.texture()));
+                }
+                None => {
+                    let vertices: Vec<Vertex> = self
+                        .particles
+                        .iter()
+                        .map(|particle| {
+                            let color = self.color_at(particle.progress());
+                            Vertex::with_pos_color((particle.position + offset).into(), color)
+                        })
+                        .collect();
+
+                    target.draw_vertices(&vertices, PrimitiveType::POINTS, None);
+                }
+            }
+        }
+    }
+}
+pub use emitter::*;
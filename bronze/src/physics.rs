@@ -0,0 +1,299 @@
+use crate::{
+    math::Vec2,
+    shape::{BBox, Movable, Point, Rect, Shape, ShapeRef},
+};
+
+pub const DEFAULT_GRAVITY: Vec2 = Vec2::new(0.0, 980.0);
+
+mod body {
+    use super::*;
+
+    pub struct Body {
+        pub shape: Shape,
+        pub velocity: Vec2,
+        pub acceleration: Vec2,
+        pub mass: f32,
+        pub restitution: f32,
+        pub friction: f32,
+        pub drag: f32,
+        pub gravity_scale: f32,
+        force: Vec2,
+    }
+
+    impl Body {
+        pub fn new(shape: Shape, mass: f32) -> Self {
+            Body {
+                shape,
+                velocity: Vec2::ZERO,
+                acceleration: Vec2::ZERO,
+                mass,
+                restitution: 0.0,
+                friction: 0.2,
+                drag: 0.0,
+                gravity_scale: 1.0,
+                force: Vec2::ZERO,
+            }
+        }
+
+        pub fn fixed(shape: Shape) -> Self {
+            Body::new(shape, 0.0)
+        }
+
+        pub fn with_velocity(mut self, x: f32, y: f32) -> Self {
+            self.velocity = Vec2::new(x, y);
+            self
+        }
+
+        pub fn with_restitution(mut self, restitution: f32) -> Self {
+            self.restitution = restitution;
+            self
+        }
+
+        pub fn with_friction(mut self, friction: f32) -> Self {
+            self.friction = friction;
+            self
+        }
+
+        pub fn with_drag(mut self, drag: f32) -> Self {
+            self.drag = drag;
+            self
+        }
+
+        pub fn with_gravity_scale(mut self, gravity_scale: f32) -> Self {
+            self.gravity_scale = gravity_scale;
+            self
+        }
+
+        #[inline]
+        pub fn is_fixed(&self) -> bool {
+            self.mass <= 0.0
+        }
+
+        #[inline]
+        pub fn inverse_mass(&self) -> f32 {
+            if self.is_fixed() {
+                0.0
+            } else {
+                1.0 / self.mass
+            }
+        }
+
+        #[inline]
+        pub fn position(&self) -> Vec2 {
+            Vec2::new(self.shape.left(), self.shape.top())
+        }
+
+        #[inline]
+        pub fn set_position(&mut self, x: f32, y: f32) {
+            self.shape.set_position(x, y);
+        }
+
+        #[inline]
+        pub fn center(&self) -> Vec2 {
+            Vec2::from(self.shape.center())
+        }
+
+        #[inline]
+        pub fn translate(&mut self, offset: Vec2) {
+            self.shape.move_by(offset.x, offset.y);
+        }
+
+        pub fn apply_force(&mut self, force: Vec2) {
+            self.force += force;
+        }
+
+        pub fn apply_impulse(&mut self, impulse: Vec2) {
+            self.velocity += impulse * self.inverse_mass();
+        }
+
+        pub fn integrate(&mut self, gravity: Vec2, dt: f32) {
+            let inverse_mass = self.inverse_mass();
+
+            if inverse_mass > 0.0 {
+                let gravity = gravity * self.gravity_scale;
+                self.velocity += (self.acceleration + gravity + self.force * inverse_mass) * dt;
+            }
+
+            self.velocity *= (1.0 - self.drag * dt).max(0.0);
+            self.force = Vec2::ZERO;
+
+            self.translate(self.velocity * dt);
+        }
+
+        pub fn as_ref(&self) -> ShapeRef {
+            self.shape.as_ref()
+        }
+    }
+}
+pub use body::*;
+
+mod manifold {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct Manifold {
+        pub normal: Vec2,
+        pub penetration: f32,
+    }
+
+    impl Manifold {
+        pub fn new(normal: Vec2, penetration: f32) -> Self {
+            Manifold {
+                normal,
+                penetration,
+            }
+        }
+
+        pub fn flipped(self) -> Self {
+            Manifold::new(-self.normal, self.penetration)
+        }
+
+        // The normal points from `a` towards `b`.
+        pub fn between(a: &ShapeRef, b: &ShapeRef) -> Option<Self> {
+            match (a, b) {
+                (ShapeRef::None, _) | (_, ShapeRef::None) => None,
+                (ShapeRef::Circle(a), ShapeRef::Circle(b)) => {
+                    circle_circle(a.center(), a.radius, b.center(), b.radius)
+                }
+                (ShapeRef::Rect(a), ShapeRef::Rect(b)) => rect_rect(a, b),
+                (ShapeRef::Circle(a), ShapeRef::Rect(b)) => {
+                    rect_circle(b, a.center(), a.radius).map(Manifold::flipped)
+                }
+                (ShapeRef::Rect(a), ShapeRef::Circle(b)) => rect_circle(a, b.center(), b.radius),
+                (ShapeRef::Point(a), ShapeRef::Circle(b)) => {
+                    circle_circle((*a).clone(), 0.0, b.center(), b.radius)
+                }
+                (ShapeRef::Circle(a), ShapeRef::Point(b)) => {
+                    circle_circle(a.center(), a.radius, (*b).clone(), 0.0)
+                }
+                _ => rect_rect(&bounds(a), &bounds(b)),
+            }
+        }
+    }
+
+    fn bounds(shape: &ShapeRef) -> Rect {
+        Rect::new(shape.left(), shape.top(), shape.width(), shape.height())
+    }
+
+    fn circle_circle(a: Point, ra: f32, b: Point, rb: f32) -> Option<Manifold> {
+        let delta = Vec2::from(b) - Vec2::from(a);
+        let distance = delta.length();
+        let radius = ra + rb;
+
+        if distance > radius {
+            return None;
+        }
+
+        let normal = if distance > 0.0 {
+            delta / distance
+        } else {
+            Vec2::RIGHT
+        };
+
+        Some(Manifold::new(normal, radius - distance))
+    }
+
+    fn rect_rect(a: &Rect, b: &Rect) -> Option<Manifold> {
+        let overlap_x = a.right().min(b.right()) - a.left().max(b.left());
+        let overlap_y = a.bottom().min(b.bottom()) - a.top().max(b.top());
+
+        if overlap_x < 0.0 || overlap_y < 0.0 {
+            return None;
+        }
+
+        if overlap_x < overlap_y {
+            let direction = if b.center_x() < a.center_x() {
+                -1.0
+            } else {
+                1.0
+            };
+            Some(Manifold::new(Vec2::new(direction, 0.0), overlap_x))
+        } else {
+            let direction = if b.center_y() < a.center_y() {
+                -1.0
+            } else {
+                1.0
+            };
+            Some(Manifold::new(Vec2::new(0.0, direction), overlap_y))
+        }
+    }
+
+    fn rect_circle(rect: &Rect, center: Point, radius: f32) -> Option<Manifold> {
+        let closest = Point::new(
+            center.x.clamp(rect.left(), rect.right()),
+            center.y.clamp(rect.top(), rect.bottom()),
+        );
+
+        let inside = closest.x == center.x && closest.y == center.y;
+
+        if inside {
+            let circle = Rect::new(
+                center.x - radius,
+                center.y - radius,
+                radius * 2.0,
+                radius * 2.0,
+            );
+            return rect_rect(rect, &circle);
+        }
+
+        let delta = Vec2::from(center) - Vec2::from(closest);
+        let distance = delta.length();
+
+        if distance > radius {
+            return None;
+        }
+
+        Some(Manifold::new(delta / distance, radius - distance))
+    }
+}
+pub use manifold::*;
+
+mod resolve {
+    use super::*;
+
+    const CORRECTION_PERCENT: f32 = 0.8;
+    const CORRECTION_SLOP: f32 = 0.01;
+
+    pub fn resolve(a: &mut Body, b: &mut Body, manifold: &Manifold) {
+        let (inv_a, inv_b) = (a.inverse_mass(), b.inverse_mass());
+        let inverse_sum = inv_a + inv_b;
+
+        if inverse_sum <= 0.0 {
+            return;
+        }
+
+        let correction =
+            (manifold.penetration - CORRECTION_SLOP).max(0.0) / inverse_sum * CORRECTION_PERCENT;
+        a.translate(manifold.normal * (-correction * inv_a));
+        b.translate(manifold.normal * (correction * inv_b));
+
+        let relative = b.velocity - a.velocity;
+        let normal_speed = relative.dot(manifold.normal);
+
+        if normal_speed > 0.0 {
+            return;
+        }
+
+        let restitution = a.restitution.min(b.restitution);
+        let j = -(1.0 + restitution) * normal_speed / inverse_sum;
+        let impulse = manifold.normal * j;
+
+        a.velocity -= impulse * inv_a;
+        b.velocity += impulse * inv_b;
+
+        let relative = b.velocity - a.velocity;
+        let tangent = (relative - manifold.normal * relative.dot(manifold.normal)).normalize();
+
+        if tangent == Vec2::ZERO {
+            return;
+        }
+
+        let mu = (a.friction * b.friction).sqrt();
+        let jt = (-relative.dot(tangent) / inverse_sum).clamp(-j * mu, j * mu);
+        let friction = tangent * jt;
+
+        a.velocity -= friction * inv_a;
+        b.velocity += friction * inv_b;
+    }
+}
+pub use resolve::*;
@@ -1,221 +1,2376 @@
-use std::rc::Rc;
+use std::{
+    cell::{Cell, RefCell},
+    fmt,
+    path::Path,
+    rc::Rc,
+};
 
 use sfml::SfBox;
 
+use crate::vfs::Vfs;
+
+mod error {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub enum ResourceErrorKind {
+        NotFound,
+        Decode,
+        Texture,
+        Invalid(String),
+    }
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct ResourceError {
+        pub path: String,
+        pub kind: ResourceErrorKind,
+    }
+
+    impl ResourceError {
+        #[inline]
+        pub fn new(path: &str, kind: ResourceErrorKind) -> Self {
+            ResourceError {
+                path: path.to_string(),
+                kind,
+            }
+        }
+
+        #[inline]
+        pub fn not_found(path: &str) -> Self {
+            Self::new(path, ResourceErrorKind::NotFound)
+        }
+
+        #[inline]
+        pub fn decode(path: &str) -> Self {
+            Self::new(path, ResourceErrorKind::Decode)
+        }
+
+        #[inline]
+        pub fn texture(path: &str) -> Self {
+            Self::new(path, ResourceErrorKind::Texture)
+        }
+
+        #[inline]
+        pub fn invalid(path: &str, message: impl Into<String>) -> Self {
+            Self::new(path, ResourceErrorKind::Invalid(message.into()))
+        }
+
+        // SFML only reports failure, so a missing file is told apart from a bad one up front.
+        pub(super) fn check_exists(path: &str) -> Result<(), Self> {
+            if Path::new(path).is_file() {
+                Ok(())
+            } else {
+                Err(Self::not_found(path))
+            }
+        }
+    }
+
+    impl ResourceError {
+        // The error without its path.
+        pub fn cause(&self) -> &str {
+            match &self.kind {
+                ResourceErrorKind::NotFound => "file not found",
+                ResourceErrorKind::Decode => "could not decode file",
+                ResourceErrorKind::Texture => "could not create texture",
+                ResourceErrorKind::Invalid(message) => message,
+            }
+        }
+    }
+
+    impl fmt::Display for ResourceError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "{}: {}", self.path, self.cause())
+        }
+    }
+
+    impl std::error::Error for ResourceError {}
+}
+pub use error::*;
+
+mod versions {
+    use super::*;
+
+    // Contents of a resource that can be reloaded through a shared reference. A reload pushes
+    // a new version and keeps the previous one alive, since references handed out before it
+    // may still point into it. `release` drops it on the pool's next `poll_reload`.
+    pub(super) struct Versions<T> {
+        versions: RefCell<Vec<Box<T>>>,
+        generation: Cell<usize>,
+    }
+
+    impl<T> Versions<T> {
+        #[inline]
+        pub fn new(value: T) -> Self {
+            Versions {
+                versions: RefCell::new(vec![Box::new(value)]),
+                generation: Cell::new(0),
+            }
+        }
+
+        #[inline]
+        pub fn current(&self) -> &T {
+            let versions = self.versions.borrow();
+            let current: *const T = &**versions.last().unwrap();
+            // SAFETY: versions are boxed, so a version stays at the same address until it is
+            // dropped. While `self` is shared, versions are only dropped by `release`, which
+            // only `ResourcePool::poll_reload` reaches, with the pool borrowed mutably, and which
+            // keeps the current version. A reference handed out here therefore stays valid
+            // until the second `poll_reload` after the reload that replaced it. Nothing in the
+            // crate keeps one beyond a single draw or layout, and `Text` holds on to its own
+            // font version, so callers must not keep them across `poll_reload` either.
+            unsafe { &*current }
+        }
+
+        // Nothing can point into the old versions while `self` is borrowed mutably, so they
+        // are dropped here.
+        pub fn current_mut(&mut self) -> &mut T {
+            let versions = self.versions.get_mut();
+            let old = versions.len() - 1;
+            versions.drain(..old);
+            versions.last_mut().unwrap()
+        }
+
+        #[inline]
+        pub fn into_inner(self) -> T {
+            *self.versions.into_inner().pop().unwrap()
+        }
+
+        // Keeps every version alive, `release` is the only place they are dropped while
+        // shared.
+        #[inline]
+        pub fn replace(&self, value: T) {
+            self.versions.borrow_mut().push(Box::new(value));
+            self.generation.set(self.generation.get() + 1);
+        }
+
+        // Drops every version but the current one.
+        pub fn release(&self) {
+            let mut versions = self.versions.borrow_mut();
+            let old = versions.len() - 1;
+            versions.drain(..old);
+        }
+
+        // Adds `f` up over every version still alive, the current one included.
+        pub fn sum(&self, f: impl Fn(&T) -> usize) -> usize {
+            self.versions
+                .borrow()
+                .iter()
+                .map(|version| f(version))
+                .sum()
+        }
+
+        // Increases with every reload, so holders of derived data can tell it is stale.
+        #[inline]
+        pub fn generation(&self) -> usize {
+            self.generation.get()
+        }
+    }
+}
+use versions::Versions;
+
 mod image {
     use sfml::{
-        graphics::{Image as SfmlImage, IntRect, Texture},
+        graphics::{Color, Image as SfmlImage, IntRect, Texture},
         system::Vector2,
     };
 
     use super::*;
 
-    pub struct Image {
+    struct ImageData {
         image: SfmlImage,
         texture: SfBox<Texture>,
     }
 
+    pub struct Image {
+        data: Versions<ImageData>,
+    }
+
     impl Image {
         pub fn new(path: &str) -> Option<Self> {
-            SfmlImage::from_file(path).and_then(|image| {
-                let Vector2 { x, y } = image.size();
-                let mut texture = Texture::new()?;
-                texture
-                    .load_from_image(&image, IntRect::new(0, 0, x as i32, y as i32))
-                    .ok()?;
-
-                Some(Image { image, texture })
+            Self::load(path).ok()
+        }
+
+        pub fn load(path: &str) -> Result<Self, ResourceError> {
+            ResourceError::check_exists(path)?;
+            let image = SfmlImage::from_file(path).ok_or_else(|| ResourceError::decode(path))?;
+            Image::from_sfml_image(image).ok_or_else(|| ResourceError::texture(path))
+        }
+
+        // A magenta and black checkerboard that stands in for images that failed to load.
+        pub fn placeholder() -> Self {
+            const SIZE: u32 = 32;
+            const SQUARE: u32 = 8;
+
+            let mut pixels = Vec::with_capacity((SIZE * SIZE * 4) as usize);
+            for y in 0..SIZE {
+                for x in 0..SIZE {
+                    if (x / SQUARE + y / SQUARE) & 1 == 0 {
+                        pixels.extend_from_slice(&[255, 0, 255, 255]);
+                    } else {
+                        pixels.extend_from_slice(&[0, 0, 0, 255]);
+                    }
+                }
+            }
+
+            Image::from_rgba(SIZE, SIZE, &pixels).expect("failed to create the placeholder image")
+        }
+
+        // Decodes a PNG, JPEG, BMP, TGA or GIF file that is already in memory.
+        pub fn from_memory(bytes: &[u8]) -> Option<Self> {
+            SfmlImage::from_memory(bytes).and_then(Image::from_sfml_image)
+        }
+
+        // `pixels` holds `width * height` RGBA pixels, row by row.
+        pub fn from_rgba(width: u32, height: u32, pixels: &[u8]) -> Option<Self> {
+            let len = (width as usize)
+                .checked_mul(height as usize)
+                .and_then(|count| count.checked_mul(4))?;
+            if pixels.len() != len {
+                return None;
+            }
+            // The length was checked against the size above.
+            unsafe { SfmlImage::create_from_pixels(width, height, pixels) }
+                .and_then(Image::from_sfml_image)
+        }
+
+        pub fn from_color(width: u32, height: u32, color: Color) -> Option<Self> {
+            SfmlImage::from_color(width, height, color).and_then(Image::from_sfml_image)
+        }
+
+        pub(crate) fn from_sfml_image(image: SfmlImage) -> Option<Self> {
+            let Vector2 { x, y } = image.size();
+            let mut texture = Texture::new()?;
+            texture
+                .load_from_image(&image, IntRect::new(0, 0, x as i32, y as i32))
+                .ok()?;
+
+            Some(Image {
+                data: Versions::new(ImageData { image, texture }),
             })
         }
 
+        pub(crate) fn from_texture(texture: &Texture) -> Option<Self> {
+            texture.copy_to_image().and_then(Image::from_sfml_image)
+        }
+
+        // Swaps in the contents of `image`, every `Rc` and `Sprite` holding this image draws
+        // the new contents from then on. Only `ResourcePool::poll_reload` reloads, see
+        // `Versions::current`.
+        pub(super) fn reload_from(&self, image: Image) {
+            self.data.replace(image.data.into_inner());
+        }
+
+        #[inline]
+        pub(super) fn release_previous(&self) {
+            self.data.release();
+        }
+
+        #[inline]
+        pub fn generation(&self) -> usize {
+            self.data.generation()
+        }
+
+        pub fn save(&self, path: &str) -> bool {
+            self.data.current().image.save_to_file(path)
+        }
+
         pub fn size(&self) -> Vector2<u32> {
-            self.image.size()
+            self.data.current().image.size()
         }
 
         pub fn pixels(&self) -> &[u8] {
-            self.image.pixel_data()
+            self.data.current().image.pixel_data()
         }
 
         pub fn texture(&self) -> &Texture {
-            &self.texture
+            &self.data.current().texture
+        }
+
+        // The pixels kept in memory, counting the previous version while a reload keeps it.
+        pub fn cpu_bytes(&self) -> usize {
+            self.data.sum(|data| data.image.pixel_data().len())
+        }
+
+        pub fn gpu_bytes(&self) -> usize {
+            self.data.sum(|data| {
+                let size = data.texture.size();
+                size.x as usize * size.y as usize * 4
+            })
+        }
+
+        #[inline]
+        fn contains(&self, x: u32, y: u32) -> bool {
+            let size = self.size();
+            x < size.x && y < size.y
+        }
+
+        #[inline]
+        fn contains_region(&self, x: u32, y: u32, width: u32, height: u32) -> bool {
+            let size = self.size();
+            x.checked_add(width).is_some_and(|right| right <= size.x)
+                && y.checked_add(height).is_some_and(|bottom| bottom <= size.y)
+        }
+
+        pub fn pixel(&self, x: u32, y: u32) -> Option<Color> {
+            // The position was checked against the size.
+            self.contains(x, y)
+                .then(|| unsafe { self.data.current().image.pixel_at(x, y) })
+        }
+
+        // The editing methods below only change the pixels kept in memory, call
+        // `update_texture` once done to show the changes on screen.
+        pub fn set_pixel(&mut self, x: u32, y: u32, color: Color) -> bool {
+            if !self.contains(x, y) {
+                return false;
+            }
+            // The position was checked against the size above.
+            unsafe { self.data.current_mut().image.set_pixel(x, y, color) };
+            true
+        }
+
+        pub fn fill(&mut self, color: Color) {
+            let Vector2 { x, y } = self.size();
+            if let Some(filled) = SfmlImage::from_color(x, y, color) {
+                self.data.current_mut().image = filled;
+            }
+        }
+
+        // Draws `source` with its top left corner at `x, y`, blending by the source alpha.
+        // Pixels that fall outside this image are skipped.
+        pub fn blit(&mut self, source: &Image, x: u32, y: u32) {
+            let Vector2 {
+                x: width,
+                y: height,
+            } = source.size();
+            self.blit_region(source, (0, 0, width, height), x, y);
+        }
+
+        // Like `blit`, but only copies the `(x, y, width, height)` region of `source`.
+        pub fn blit_region(
+            &mut self,
+            source: &Image,
+            region: (u32, u32, u32, u32),
+            x: u32,
+            y: u32,
+        ) {
+            let (left, top, width, height) = region;
+            if width == 0 || height == 0 {
+                return;
+            }
+            let rect = IntRect::new(left as i32, top as i32, width as i32, height as i32);
+            let source = &source.data.current().image;
+            self.data
+                .current_mut()
+                .image
+                .copy_image(source, x, y, &rect, true);
+        }
+
+        pub fn flip_horizontally(&mut self) {
+            self.data.current_mut().image.flip_horizontally();
+        }
+
+        pub fn flip_vertically(&mut self) {
+            self.data.current_mut().image.flip_vertically();
+        }
+
+        // A new image with the pixels of the `width * height` region at `x, y`.
+        pub fn copy_region(&self, x: u32, y: u32, width: u32, height: u32) -> Option<Image> {
+            if width == 0 || height == 0 || !self.contains_region(x, y, width, height) {
+                return None;
+            }
+
+            let stride = self.size().x as usize * 4;
+            let pixels: Vec<u8> = self
+                .pixels()
+                .chunks_exact(stride)
+                .skip(y as usize)
+                .take(height as usize)
+                .flat_map(|row| &row[x as usize * 4..(x + width) as usize * 4])
+                .copied()
+                .collect();
+
+            Image::from_rgba(width, height, &pixels)
+        }
+
+        // Uploads the edited pixels to the texture that sprites draw.
+        pub fn update_texture(&mut self) {
+            let ImageData { image, texture } = self.data.current_mut();
+            // Editing never changes the size of the image, so it still matches the texture.
+            unsafe { texture.update_from_image(image, 0, 0) };
         }
     }
 }
 pub use image::*;
 
 mod audio {
+    use super::*;
+
     pub struct Audio;
 
     impl Audio {
-        pub fn new(_path: &str) -> Option<Self> {
-            unimplemented!("Audio is not implemented yet");
+        #[inline]
+        pub fn new(path: &str) -> Option<Self> {
+            Audio::load(path).ok()
         }
-    }
-}
-pub use audio::*;
-
-mod font {
-    use sfml::graphics::Font as SfmlFont;
-
-    use super::*;
 
-    pub struct Font {
-        sfml_font: SfBox<SfmlFont>,
-    }
+        // There is no audio backend yet, so every audio entry fails to load instead of
+        // bringing the game down.
+        pub fn load(path: &str) -> Result<Self, ResourceError> {
+            ResourceError::check_exists(path)?;
+            Err(ResourceError::invalid(path, "audio is not supported"))
+        }
 
-    impl Font {
-        pub fn new(path: &str) -> Option<Self> {
-            SfmlFont::from_file(path).map(|sfml_font| Font { sfml_font })
+        #[inline]
+        pub fn cpu_bytes(&self) -> usize {
+            0
         }
 
-        pub fn sfml_font(&self) -> &SfmlFont {
-            &self.sfml_font
+        #[inline]
+        pub fn gpu_bytes(&self) -> usize {
+            0
         }
     }
 }
-pub use font::*;
-
-mod resource_pool {
-    use std::{collections::HashMap, hash::Hash, rc::Rc};
+pub use audio::*;
 
-    use once_cell::unsync::OnceCell;
-    use strum::IntoEnumIterator;
+mod bitmap_font {
+    use std::{collections::HashMap, fs::read_to_string};
 
     use super::*;
 
-    pub trait Key: IntoEnumIterator + Eq + Hash {}
-    impl<T> Key for T where T: IntoEnumIterator + Eq + Hash {}
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct Glyph {
+        pub x: u32,
+        pub y: u32,
+        pub width: u32,
+        pub height: u32,
+        pub x_offset: f32,
+        pub y_offset: f32,
+        pub advance: f32,
+        pub page: usize,
+    }
 
-    pub struct ResourcePool<I: Key, A: Key, F: Key> {
-        images: HashMap<I, OnceCell<Rc<Image>>>,
-        audios: HashMap<A, OnceCell<Rc<Audio>>>,
-        fonts: HashMap<F, OnceCell<Rc<Font>>>,
-        load_image: fn(&I) -> Image,
-        load_audio: fn(&A) -> Audio,
-        load_font: fn(&F) -> Font,
+    pub struct BitmapFont {
+        pages: Vec<Image>,
+        glyphs: HashMap<char, Glyph>,
+        kerning: HashMap<(char, char), f32>,
+        size: f32,
+        line_height: f32,
+        base: f32,
     }
 
-    impl<I: Key, A: Key, F: Key> ResourcePool<I, A, F> {
-        pub fn new(
-            load_image: fn(&I) -> Image,
-            load_audio: fn(&A) -> Audio,
-            load_font: fn(&F) -> Font,
-        ) -> Self {
-            let mut images = HashMap::new();
-            let mut audios = HashMap::new();
-            let mut fonts = HashMap::new();
+    impl BitmapFont {
+        // Loads an AngelCode BMFont descriptor in the text format, with its pages
+        // resolved relative to the descriptor.
+        pub fn from_fnt(path: &str) -> Option<Self> {
+            let content = read_to_string(path).ok()?;
+            let directory = Path::new(path).parent().unwrap_or(Path::new(""));
 
-            for id in I::iter() {
-                images.insert(id, OnceCell::new());
+            let mut pages = Vec::new();
+            let mut glyphs = HashMap::new();
+            let mut kerning = HashMap::new();
+            let mut size = 0.0;
+            let mut line_height = 0.0;
+            let mut base = 0.0;
+
+            for line in content.lines() {
+                let (tag, attributes) = match parse_line(line) {
+                    Some(parsed) => parsed,
+                    None => continue,
+                };
+                let get = |key: &str| attributes.get(key).copied();
+                let number = |key: &str| get(key).and_then(|value| value.parse::<f32>().ok());
+
+                match tag {
+                    "info" => size = number("size")?.abs(),
+                    "common" => {
+                        line_height = number("lineHeight")?;
+                        base = number("base")?;
+                    }
+                    "page" => {
+                        let id = number("id")? as usize;
+                        let file = directory.join(get("file")?);
+                        let image = Image::new(file.to_str()?)?;
+
+                        if id != pages.len() {
+                            return None;
+                        }
+                        pages.push(image);
+                    }
+                    "char" => {
+                        let id = char::from_u32(number("id")? as u32)?;
+                        glyphs.insert(
+                            id,
+                            Glyph {
+                                x: number("x")? as u32,
+                                y: number("y")? as u32,
+                                width: number("width")? as u32,
+                                height: number("height")? as u32,
+                                x_offset: number("xoffset")?,
+                                y_offset: number("yoffset")?,
+                                advance: number("xadvance")?,
+                                page: number("page").unwrap_or(0.0) as usize,
+                            },
+                        );
+                    }
+                    "kerning" => {
+                        let first = char::from_u32(number("first")? as u32)?;
+                        let second = char::from_u32(number("second")? as u32)?;
+                        kerning.insert((first, second), number("amount")?);
+                    }
+                    _ => {}
+                }
             }
 
-            for id in A::iter() {
-                audios.insert(id, OnceCell::new());
+            if pages.is_empty() || glyphs.values().any(|glyph| glyph.page >= pages.len()) {
+                return None;
             }
 
-            for id in F::iter() {
-                fonts.insert(id, OnceCell::new());
+            Some(BitmapFont {
+                pages,
+                glyphs,
+                kerning,
+                size: if size > 0.0 { size } else { line_height },
+                line_height,
+                base,
+            })
+        }
+
+        // Builds a monospaced font from an atlas of equally sized cells, laid out left to
+        // right and top to bottom in the order of `chars`.
+        pub fn grid(image: Image, cell_width: u32, cell_height: u32, chars: &str) -> Option<Self> {
+            let columns = image.size().x / cell_width.max(1);
+
+            if columns == 0 {
+                return None;
             }
 
-            ResourcePool {
-                images,
-                audios,
-                fonts,
-                load_image,
-                load_audio,
-                load_font,
+            let glyphs = chars
+                .chars()
+                .enumerate()
+                .map(|(i, c)| {
+                    let glyph = Glyph {
+                        x: i as u32 % columns * cell_width,
+                        y: i as u32 / columns * cell_height,
+                        width: cell_width,
+                        height: cell_height,
+                        x_offset: 0.0,
+                        y_offset: 0.0,
+                        advance: cell_width as f32,
+                        page: 0,
+                    };
+                    (c, glyph)
+                })
+                .collect();
+
+            Some(BitmapFont {
+                pages: vec![image],
+                glyphs,
+                kerning: HashMap::new(),
+                size: cell_height as f32,
+                line_height: cell_height as f32,
+                base: cell_height as f32,
+            })
+        }
+
+        // A 5x7 font covering printable ASCII, drawn into 6x8 cells so glyphs are spaced apart.
+        pub fn builtin() -> Self {
+            const COLUMNS: u32 = 16;
+            let rows = (BUILTIN_GLYPHS.len() as u32).div_ceil(COLUMNS);
+            let (width, height) = (COLUMNS * 6, rows * 8);
+
+            let mut pixels = vec![0; (width * height * 4) as usize];
+            for (i, glyph) in BUILTIN_GLYPHS.iter().enumerate() {
+                let (cell_x, cell_y) = (i as u32 % COLUMNS * 6, i as u32 / COLUMNS * 8);
+                for (x, column) in glyph.iter().enumerate() {
+                    for y in 0..7 {
+                        if column >> y & 1 == 1 {
+                            let index = ((cell_y + y) * width + cell_x + x as u32) as usize * 4;
+                            pixels[index..index + 4].copy_from_slice(&[255; 4]);
+                        }
+                    }
+                }
             }
+
+            let chars: String = (' '..='~').collect();
+
+            Image::from_rgba(width, height, &pixels)
+                .and_then(|image| BitmapFont::grid(image, 6, 8, &chars))
+                .expect("failed to create the builtin font")
         }
 
-        pub fn get_image(&self, id: I) -> Rc<Image> {
-            let cell = self.images.get(&id).unwrap();
-            Rc::clone(cell.get_or_init(|| Rc::new((self.load_image)(&id))))
+        #[inline]
+        pub fn size(&self) -> f32 {
+            self.size
         }
 
-        pub fn get_audio(&self, id: A) -> Rc<Audio> {
-            let cell = self.audios.get(&id).unwrap();
-            Rc::clone(cell.get_or_init(|| Rc::new((self.load_audio)(&id))))
+        #[inline]
+        pub fn line_height(&self) -> f32 {
+            self.line_height
         }
 
-        pub fn get_font(&self, id: F) -> Rc<Font> {
-            let cell = self.fonts.get(&id).unwrap();
-            Rc::clone(cell.get_or_init(|| Rc::new((self.load_font)(&id))))
+        #[inline]
+        pub fn set_line_height(&mut self, line_height: f32) {
+            self.line_height = line_height;
         }
 
-        pub fn try_clear(&mut self) {
-            for (_, cell) in &mut self.images {
-                let cell: &mut OnceCell<Rc<Image>> = cell;
+        #[inline]
+        pub fn base(&self) -> f32 {
+            self.base
+        }
 
-                if let Some(rc) = cell.get() {
-                    if Rc::strong_count(rc) == 1 {
-                        cell.take();
-                    }
-                }
-            }
+        #[inline]
+        pub fn pages(&self) -> &[Image] {
+            &self.pages
+        }
 
-            for (_, cell) in &mut self.audios {
-                let cell: &mut OnceCell<Rc<Audio>> = cell;
+        #[inline]
+        pub fn glyph(&self, c: char) -> Option<&Glyph> {
+            self.glyphs.get(&c)
+        }
 
-                if let Some(rc) = cell.get() {
-                    if Rc::strong_count(rc) == 1 {
-                        cell.take();
-                    }
-                }
-            }
+        #[inline]
+        pub fn kerning(&self, first: char, second: char) -> f32 {
+            self.kerning.get(&(first, second)).copied().unwrap_or(0.0)
+        }
 
-            for (_, cell) in &mut self.fonts {
-                let cell: &mut OnceCell<Rc<Font>> = cell;
+        pub fn set_kerning(&mut self, first: char, second: char, amount: f32) {
+            self.kerning.insert((first, second), amount);
+        }
+    }
 
-                if let Some(rc) = cell.get() {
-                    if Rc::strong_count(rc) == 1 {
-                        cell.take();
-                    }
-                }
-            }
+    // Columns of each glyph from ' ' to '~', least significant bit at the top.
+    const BUILTIN_GLYPHS: [[u8; 5]; 95] = [
+        [0x00, 0x00, 0x00, 0x00, 0x00],
+        [0x00, 0x00, 0x5F, 0x00, 0x00],
+        [0x00, 0x07, 0x00, 0x07, 0x00],
+        [0x14, 0x7F, 0x14, 0x7F, 0x14],
+        [0x24, 0x2A, 0x7F, 0x2A, 0x12],
+        [0x23, 0x13, 0x08, 0x64, 0x62],
+        [0x36, 0x49, 0x55, 0x22, 0x50],
+        [0x00, 0x05, 0x03, 0x00, 0x00],
+        [0x00, 0x1C, 0x22, 0x41, 0x00],
+        [0x00, 0x41, 0x22, 0x1C, 0x00],
+        [0x14, 0x08, 0x3E, 0x08, 0x14],
+        [0x08, 0x08, 0x3E, 0x08, 0x08],
+        [0x00, 0x50, 0x30, 0x00, 0x00],
+        [0x08, 0x08, 0x08, 0x08, 0x08],
+        [0x00, 0x60, 0x60, 0x00, 0x00],
+        [0x20, 0x10, 0x08, 0x04, 0x02],
+        [0x3E, 0x51, 0x49, 0x45, 0x3E],
+        [0x00, 0x42, 0x7F, 0x40, 0x00],
+        [0x42, 0x61, 0x51, 0x49, 0x46],
+        [0x21, 0x41, 0x45, 0x4B, 0x31],
+        [0x18, 0x14, 0x12, 0x7F, 0x10],
+        [0x27, 0x45, 0x45, 0x45, 0x39],
+        [0x3C, 0x4A, 0x49, 0x49, 0x30],
+        [0x01, 0x71, 0x09, 0x05, 0x03],
+        [0x36, 0x49, 0x49, 0x49, 0x36],
+        [0x06, 0x49, 0x49, 0x29, 0x1E],
+        [0x00, 0x36, 0x36, 0x00, 0x00],
+        [0x00, 0x56, 0x36, 0x00, 0x00],
+        [0x08, 0x14, 0x22, 0x41, 0x00],
+        [0x14, 0x14, 0x14, 0x14, 0x14],
+        [0x00, 0x41, 0x22, 0x14, 0x08],
+        [0x02, 0x01, 0x51, 0x09, 0x06],
+        [0x32, 0x49, 0x79, 0x41, 0x3E],
+        [0x7E, 0x11, 0x11, 0x11, 0x7E],
+        [0x7F, 0x49, 0x49, 0x49, 0x36],
+        [0x3E, 0x41, 0x41, 0x41, 0x22],
+        [0x7F, 0x41, 0x41, 0x22, 0x1C],
+        [0x7F, 0x49, 0x49, 0x49, 0x41],
+        [0x7F, 0x09, 0x09, 0x09, 0x01],
+        [0x3E, 0x41, 0x49, 0x49, 0x7A],
+        [0x7F, 0x08, 0x08, 0x08, 0x7F],
+        [0x00, 0x41, 0x7F, 0x41, 0x00],
+        [0x20, 0x40, 0x41, 0x3F, 0x01],
+        [0x7F, 0x08, 0x14, 0x22, 0x41],
+        [0x7F, 0x40, 0x40, 0x40, 0x40],
+        [0x7F, 0x02, 0x0C, 0x02, 0x7F],
+        [0x7F, 0x04, 0x08, 0x10, 0x7F],
+        [0x3E, 0x41, 0x41, 0x41, 0x3E],
+        [0x7F, 0x09, 0x09, 0x09, 0x06],
+        [0x3E, 0x41, 0x51, 0x21, 0x5E],
+        [0x7F, 0x09, 0x19, 0x29, 0x46],
+        [0x46, 0x49, 0x49, 0x49, 0x31],
+        [0x01, 0x01, 0x7F, 0x01, 0x01],
+        [0x3F, 0x40, 0x40, 0x40, 0x3F],
+        [0x1F, 0x20, 0x40, 0x20, 0x1F],
+        [0x3F, 0x40, 0x38, 0x40, 0x3F],
+        [0x63, 0x14, 0x08, 0x14, 0x63],
+        [0x07, 0x08, 0x70, 0x08, 0x07],
+        [0x61, 0x51, 0x49, 0x45, 0x43],
+        [0x00, 0x7F, 0x41, 0x41, 0x00],
+        [0x02, 0x04, 0x08, 0x10, 0x20],
+        [0x00, 0x41, 0x41, 0x7F, 0x00],
+        [0x04, 0x02, 0x01, 0x02, 0x04],
+        [0x40, 0x40, 0x40, 0x40, 0x40],
+        [0x00, 0x01, 0x02, 0x04, 0x00],
+        [0x20, 0x54, 0x54, 0x54, 0x78],
+        [0x7F, 0x48, 0x44, 0x44, 0x38],
+        [0x38, 0x44, 0x44, 0x44, 0x20],
+        [0x38, 0x44, 0x44, 0x48, 0x7F],
+        [0x38, 0x54, 0x54, 0x54, 0x18],
+        [0x08, 0x7E, 0x09, 0x01, 0x02],
+        [0x0C, 0x52, 0x52, 0x52, 0x3E],
+        [0x7F, 0x08, 0x04, 0x04, 0x78],
+        [0x00, 0x44, 0x7D, 0x40, 0x00],
+        [0x20, 0x40, 0x44, 0x3D, 0x00],
+        [0x7F, 0x10, 0x28, 0x44, 0x00],
+        [0x00, 0x41, 0x7F, 0x40, 0x00],
+        [0x7C, 0x04, 0x18, 0x04, 0x78],
+        [0x7C, 0x08, 0x04, 0x04, 0x78],
+        [0x38, 0x44, 0x44, 0x44, 0x38],
+        [0x7C, 0x14, 0x14, 0x14, 0x08],
+        [0x08, 0x14, 0x14, 0x18, 0x7C],
+        [0x7C, 0x08, 0x04, 0x04, 0x08],
+        [0x48, 0x54, 0x54, 0x54, 0x20],
+        [0x04, 0x3F, 0x44, 0x40, 0x20],
+        [0x3C, 0x40, 0x40, 0x20, 0x7C],
+        [0x1C, 0x20, 0x40, 0x20, 0x1C],
+        [0x3C, 0x40, 0x30, 0x40, 0x3C],
+        [0x44, 0x28, 0x10, 0x28, 0x44],
+        [0x0C, 0x50, 0x50, 0x50, 0x3C],
+        [0x44, 0x64, 0x54, 0x4C, 0x44],
+        [0x00, 0x08, 0x36, 0x41, 0x00],
+        [0x00, 0x00, 0x7F, 0x00, 0x00],
+        [0x00, 0x41, 0x36, 0x08, 0x00],
+        [0x08, 0x04, 0x08, 0x10, 0x08],
+    ];
+
+    fn parse_line(line: &str) -> Option<(&str, HashMap<&str, &str>)> {
+        let line = line.trim();
+        let (tag, mut rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+
+        if tag.is_empty() {
+            return None;
         }
 
-        pub fn full_clear(&mut self) {
-            for (_, cell) in &mut self.images {
-                let cell: &mut OnceCell<Rc<Image>> = cell;
-                cell.take();
-            }
+        let mut attributes = HashMap::new();
 
-            for (_, cell) in &mut self.audios {
-                let cell: &mut OnceCell<Rc<Audio>> = cell;
-                cell.take();
-            }
+        loop {
+            rest = rest.trim_start();
 
-            for (_, cell) in &mut self.fonts {
-                let cell: &mut OnceCell<Rc<Font>> = cell;
-                cell.take();
-            }
+            let (key, after_key) = match rest.split_once('=') {
+                Some(pair) => pair,
+                None => break,
+            };
+
+            let (value, after_value) = match after_key.strip_prefix('"') {
+                Some(quoted) => {
+                    let end = quoted.find('"').unwrap_or(quoted.len());
+                    (&quoted[..end], quoted.get(end + 1..).unwrap_or(""))
+                }
+                None => {
+                    let end = after_key
+                        .find(char::is_whitespace)
+                        .unwrap_or(after_key.len());
+                    (&after_key[..end], &after_key[end..])
+                }
+            };
+
+            attributes.insert(key.trim(), value);
+            rest = after_value;
         }
+
+        Some((tag, attributes))
     }
 }
-pub use resource_pool::*;
+pub use bitmap_font::*;
 
-mod cursor {
-    use sfml::window::Cursor as SfmlCursor;
+mod font {
+    use sfml::graphics::Font as SfmlFont;
 
     use super::*;
 
-    pub struct Cursor {
-        _image: Rc<Image>,
-        cursor: SfBox<SfmlCursor>,
+    enum FontKind {
+        Vector(SfBox<SfmlFont>),
+        // SFML reads glyphs from the buffer lazily, so it is kept alive, and dropped after,
+        // the font created from it.
+        Memory {
+            font: SfBox<SfmlFont>,
+            _bytes: Vec<u8>,
+        },
+        Bitmap(BitmapFont),
     }
 
-    impl Cursor {
-        pub fn from_image(image: &Rc<Image>) -> Option<Self> {
-            unsafe { SfmlCursor::from_pixels(image.pixels(), image.size(), image.size() / 2) }.map(
-                |cursor| Cursor {
-                    _image: Rc::clone(image),
-                    cursor,
-                },
-            )
+    // Keeps one version of a font alive for texts that point into it, across reloads.
+    #[derive(Clone)]
+    pub(crate) struct FontVersion(Rc<FontKind>);
+
+    impl FontVersion {
+        pub(crate) fn sfml_font(&self) -> Option<&SfmlFont> {
+            match &*self.0 {
+                FontKind::Vector(sfml_font)
+                | FontKind::Memory {
+                    font: sfml_font, ..
+                } => Some(sfml_font),
+                FontKind::Bitmap(_) => None,
+            }
+        }
+
+        pub(crate) fn bitmap_font(&self) -> Option<&BitmapFont> {
+            match &*self.0 {
+                FontKind::Vector(_) | FontKind::Memory { .. } => None,
+                FontKind::Bitmap(bitmap_font) => Some(bitmap_font),
+            }
+        }
+    }
+
+    pub struct Font {
+        kind: Versions<Rc<FontKind>>,
+    }
+
+    impl Font {
+        pub fn new(path: &str) -> Option<Self> {
+            Self::load(path).ok()
+        }
+
+        pub fn load(path: &str) -> Result<Self, ResourceError> {
+            ResourceError::check_exists(path)?;
+            SfmlFont::from_file(path)
+                .map(|sfml_font| Font {
+                    kind: Versions::new(Rc::new(FontKind::Vector(sfml_font))),
+                })
+                .ok_or_else(|| ResourceError::decode(path))
+        }
+
+        // Reads a TrueType or OpenType font that is already in memory.
+        pub fn from_memory(bytes: Vec<u8>) -> Option<Self> {
+            // The buffer is moved into the font kind below, and a `Vec` keeps its heap
+            // allocation in place when moved.
+            let sfml_font = unsafe { SfmlFont::from_memory(&bytes) }?;
+            Some(Font {
+                kind: Versions::new(Rc::new(FontKind::Memory {
+                    font: sfml_font,
+                    _bytes: bytes,
+                })),
+            })
+        }
+
+        // The built in 5x7 bitmap font, always available as a fallback.
+        pub fn placeholder() -> Self {
+            Font::from_bitmap(BitmapFont::builtin())
+        }
+
+        pub fn from_fnt(path: &str) -> Option<Self> {
+            BitmapFont::from_fnt(path).map(Font::from_bitmap)
+        }
+
+        pub fn from_grid(
+            path: &str,
+            cell_width: u32,
+            cell_height: u32,
+            chars: &str,
+        ) -> Option<Self> {
+            let image = Image::new(path)?;
+            BitmapFont::grid(image, cell_width, cell_height, chars).map(Font::from_bitmap)
+        }
+
+        pub fn from_bitmap(bitmap_font: BitmapFont) -> Self {
+            Font {
+                kind: Versions::new(Rc::new(FontKind::Bitmap(bitmap_font))),
+            }
+        }
+
+        // Swaps in the contents of `font`. Texts pick up the new font the next time they are
+        // changed or `Text::sync_font` is called. Only `ResourcePool::poll_reload` reloads.
+        pub(super) fn reload_from(&self, font: Font) {
+            self.kind.replace(font.kind.into_inner());
+        }
+
+        #[inline]
+        pub(super) fn release_previous(&self) {
+            self.kind.release();
+        }
+
+        #[inline]
+        pub(crate) fn version(&self) -> FontVersion {
+            FontVersion(Rc::clone(self.kind.current()))
+        }
+
+        #[inline]
+        pub fn generation(&self) -> usize {
+            self.kind.generation()
+        }
+
+        pub fn sfml_font(&self) -> Option<&SfmlFont> {
+            match &**self.kind.current() {
+                FontKind::Vector(sfml_font)
+                | FontKind::Memory {
+                    font: sfml_font, ..
+                } => Some(sfml_font),
+                FontKind::Bitmap(_) => None,
+            }
+        }
+
+        // Fonts loaded from a file are streamed from disk by SFML, and the glyph pages of vector
+        // fonts grow as text is drawn, so only the known buffers and bitmap pages are counted.
+        // Versions only texts still hold on to are left out.
+        pub fn cpu_bytes(&self) -> usize {
+            self.kind.sum(|kind| match &**kind {
+                FontKind::Vector(_) => 0,
+                FontKind::Memory { _bytes: bytes, .. } => bytes.len(),
+                FontKind::Bitmap(bitmap_font) => {
+                    bitmap_font.pages().iter().map(Image::cpu_bytes).sum()
+                }
+            })
+        }
+
+        pub fn gpu_bytes(&self) -> usize {
+            self.kind.sum(|kind| match &**kind {
+                FontKind::Vector(_) | FontKind::Memory { .. } => 0,
+                FontKind::Bitmap(bitmap_font) => {
+                    bitmap_font.pages().iter().map(Image::gpu_bytes).sum()
+                }
+            })
+        }
+
+        pub fn bitmap_font(&self) -> Option<&BitmapFont> {
+            match &**self.kind.current() {
+                FontKind::Vector(_) | FontKind::Memory { .. } => None,
+                FontKind::Bitmap(bitmap_font) => Some(bitmap_font),
+            }
+        }
+    }
+}
+pub use font::*;
+
+mod manifest {
+    use std::{
+        collections::BTreeMap,
+        env, fs,
+        path::{Path, PathBuf},
+    };
+
+    use serde::Deserialize;
+
+    use super::*;
+
+    // Looks for `path` next to the executable and then in each of its parent directories, so
+    // assets are found the same way whether the game runs from `target/debug` or an install
+    // directory, regardless of the working directory. Absolute paths are returned as they are.
+    pub fn asset_path(path: &str) -> Option<PathBuf> {
+        let path = Path::new(path);
+        if path.is_absolute() {
+            return Some(path.to_path_buf());
+        }
+
+        let exe = env::current_exe().ok()?;
+        exe.ancestors()
+            .skip(1)
+            .map(|directory| directory.join(path))
+            .find(|candidate| candidate.exists())
+    }
+
+    #[derive(Debug, Clone, Default, Deserialize)]
+    #[serde(rename = "Manifest", deny_unknown_fields)]
+    pub struct Manifest {
+        #[serde(skip)]
+        vfs: Vfs,
+        #[serde(default)]
+        root: PathBuf,
+        #[serde(default)]
+        pub(super) images: BTreeMap<String, String>,
+        #[serde(default)]
+        pub(super) audios: BTreeMap<String, String>,
+        #[serde(default)]
+        pub(super) fonts: BTreeMap<String, String>,
+        #[serde(default)]
+        groups: BTreeMap<String, Vec<String>>,
+        #[serde(default)]
+        atlases: BTreeMap<String, Vec<String>>,
+    }
+
+    impl Manifest {
+        // Reads a RON manifest located through `asset_path`. Entries are relative to `root`,
+        // which is itself relative to the manifest file.
+        pub fn load(path: &str) -> Result<Self, ResourceError> {
+            let file = asset_path(path).ok_or_else(|| ResourceError::not_found(path))?;
+            let source = fs::read_to_string(&file).map_err(|_| ResourceError::decode(path))?;
+
+            Manifest::parse(&source, path, &file, Vfs::new())
+        }
+
+        // Reads a manifest, and later every entry it names, through `vfs`, e.g. from an archive
+        // or from files embedded in the executable.
+        pub fn load_from(vfs: Vfs, path: &str) -> Result<Self, ResourceError> {
+            let source = vfs.read_to_string(path)?;
+            Manifest::parse(&source, path, Path::new(path), vfs)
+        }
+
+        fn parse(source: &str, path: &str, file: &Path, vfs: Vfs) -> Result<Self, ResourceError> {
+            let mut manifest: Manifest = ron::from_str(source).map_err(|error| {
+                ResourceError::invalid(
+                    path,
+                    format!(
+                        "{}:{}: {}",
+                        error.position.line, error.position.col, error.code
+                    ),
+                )
+            })?;
+
+            let directory = file.parent().unwrap_or(Path::new(""));
+            manifest.root = directory.join(&manifest.root);
+            manifest.vfs = vfs;
+
+            Ok(manifest)
+        }
+
+        #[inline]
+        pub fn vfs(&self) -> &Vfs {
+            &self.vfs
+        }
+
+        #[inline]
+        pub fn root(&self) -> &Path {
+            &self.root
+        }
+
+        #[inline]
+        pub fn image_path(&self, name: &str) -> Option<PathBuf> {
+            self.images.get(name).map(|path| self.root.join(path))
+        }
+
+        #[inline]
+        pub fn audio_path(&self, name: &str) -> Option<PathBuf> {
+            self.audios.get(name).map(|path| self.root.join(path))
+        }
+
+        #[inline]
+        pub fn font_path(&self, name: &str) -> Option<PathBuf> {
+            self.fonts.get(name).map(|path| self.root.join(path))
+        }
+
+        #[inline]
+        pub fn group(&self, name: &str) -> Option<&[String]> {
+            self.groups.get(name).map(Vec::as_slice)
+        }
+
+        #[inline]
+        pub fn atlas(&self, name: &str) -> Option<&[String]> {
+            self.atlases.get(name).map(Vec::as_slice)
+        }
+
+        pub fn atlas_of(&self, image: &str) -> Option<&str> {
+            self.atlases
+                .iter()
+                .find(|(_, images)| images.iter().any(|name| name == image))
+                .map(|(atlas, _)| atlas.as_str())
+        }
+
+        fn entries(&self) -> [(&'static str, &BTreeMap<String, String>); 3] {
+            [
+                ("image", &self.images),
+                ("audio", &self.audios),
+                ("font", &self.fonts),
+            ]
+        }
+
+        // Reports every entry whose file is missing, along with groups and atlases that name
+        // entries the manifest does not have.
+        pub fn validate(&self) -> Vec<ResourceError> {
+            let mut errors = Vec::new();
+
+            for (_, entries) in self.entries() {
+                for path in entries.values() {
+                    let file = self.root.join(path);
+                    if !self.vfs.exists(&file) {
+                        errors.push(ResourceError::not_found(&file.to_string_lossy()));
+                    }
+                }
+            }
+
+            for (group, names) in &self.groups {
+                for name in names {
+                    if self
+                        .entries()
+                        .iter()
+                        .all(|(_, entries)| !entries.contains_key(name))
+                    {
+                        errors.push(ResourceError::invalid(
+                            name,
+                            format!("unknown entry in group `{}`", group),
+                        ));
+                    }
+                }
+            }
+
+            let mut atlas_of = BTreeMap::new();
+            for (atlas, names) in &self.atlases {
+                for name in names {
+                    if !self.images.contains_key(name) {
+                        errors.push(ResourceError::invalid(
+                            name,
+                            format!("unknown image in atlas `{}`", atlas),
+                        ));
+                    } else if let Some(other) = atlas_of.insert(name, atlas) {
+                        errors.push(ResourceError::invalid(
+                            name,
+                            format!("image is in both atlas `{}` and `{}`", other, atlas),
+                        ));
+                    }
+                }
+            }
+
+            errors
+        }
+    }
+}
+pub use manifest::*;
+
+mod hot_reload {
+    use std::{
+        fs,
+        path::PathBuf,
+        time::{Duration, Instant, SystemTime},
+    };
+
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub enum Reload<I, F> {
+        Image(I),
+        Font(F),
+        File(PathBuf),
+        Failed(ResourceError),
+    }
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub(super) enum Target {
+        Image(String),
+        Font(String),
+        File,
+    }
+
+    // Polls modification times instead of relying on OS file notifications.
+    pub(super) struct Watcher {
+        interval: Duration,
+        last_poll: Instant,
+        files: Vec<(PathBuf, Option<SystemTime>, Target)>,
+    }
+
+    impl Watcher {
+        #[inline]
+        pub fn new(interval: Duration) -> Self {
+            Watcher {
+                interval,
+                last_poll: Instant::now(),
+                files: Vec::new(),
+            }
+        }
+
+        pub fn watch(&mut self, path: PathBuf, target: Target) {
+            if !self.files.iter().any(|(file, _, _)| *file == path) {
+                let modified = modified(&path);
+                self.files.push((path, modified, target));
+            }
+        }
+
+        // Returns the files that changed since the last poll, looking at most once per interval.
+        pub fn poll(&mut self) -> Vec<(PathBuf, Target)> {
+            if self.last_poll.elapsed() < self.interval {
+                return Vec::new();
+            }
+            self.last_poll = Instant::now();
+
+            let mut changed = Vec::new();
+            for (path, last_modified, target) in &mut self.files {
+                let modified = modified(path);
+                if modified.is_some() && modified != *last_modified {
+                    *last_modified = modified;
+                    changed.push((path.clone(), target.clone()));
+                }
+            }
+
+            changed
+        }
+    }
+
+    fn modified(path: &Path) -> Option<SystemTime> {
+        fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    }
+}
+pub use hot_reload::*;
+
+mod resource_pool {
+    use std::{
+        collections::{BTreeMap, HashMap},
+        fmt::{Debug, Write},
+        hash::Hash,
+        path::PathBuf,
+        rc::Rc,
+        time::{Duration, Instant},
+    };
+
+    use once_cell::unsync::OnceCell;
+    use strum::IntoEnumIterator;
+
+    use super::*;
+
+    pub trait Key: IntoEnumIterator + Eq + Hash {}
+    impl<T> Key for T where T: IntoEnumIterator + Eq + Hash {}
+
+    pub type Loader<K, T> = Box<dyn Fn(&K) -> Result<T, ResourceError>>;
+
+    pub(super) trait Usage {
+        fn cpu_bytes(&self) -> usize;
+        fn gpu_bytes(&self) -> usize;
+    }
+
+    macro_rules! impl_usage {
+        ($($resource:ty),*) => {
+            $(impl Usage for $resource {
+                #[inline]
+                fn cpu_bytes(&self) -> usize {
+                    <$resource>::cpu_bytes(self)
+                }
+
+                #[inline]
+                fn gpu_bytes(&self) -> usize {
+                    <$resource>::gpu_bytes(self)
+                }
+            })*
+        };
+    }
+
+    impl_usage!(Image, Audio, Font);
+
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+    pub struct ResourceStats {
+        pub cpu_bytes: usize,
+        pub gpu_bytes: usize,
+        pub load_time: Duration,
+        // The pool frame, see `ResourcePool::end_frame`, of the last `get` or `try_get`.
+        pub last_access: u64,
+        // Strong references held outside the pool.
+        pub users: usize,
+        pub pinned: bool,
+    }
+
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+    pub struct PoolStats {
+        pub loaded: usize,
+        pub pinned: usize,
+        pub cpu_bytes: usize,
+        pub gpu_bytes: usize,
+        pub frame: u64,
+        pub budget: Option<usize>,
+    }
+
+    impl PoolStats {
+        #[inline]
+        pub fn total_bytes(&self) -> usize {
+            self.cpu_bytes + self.gpu_bytes
+        }
+    }
+
+    struct Entry<T> {
+        cell: OnceCell<Rc<T>>,
+        last_access: Cell<u64>,
+        load_time: Cell<Duration>,
+        pinned: bool,
+    }
+
+    impl<T: Usage> Entry<T> {
+        fn new() -> Self {
+            Entry {
+                cell: OnceCell::new(),
+                last_access: Cell::new(0),
+                load_time: Cell::new(Duration::ZERO),
+                pinned: false,
+            }
+        }
+
+        #[inline]
+        fn get(&self) -> Option<&Rc<T>> {
+            self.cell.get()
+        }
+
+        fn set(&self, resource: T, load_time: Duration, frame: u64) {
+            if self.cell.set(Rc::new(resource)).is_ok() {
+                self.load_time.set(load_time);
+                self.last_access.set(frame);
+            }
+        }
+
+        #[inline]
+        fn take(&mut self) -> Option<Rc<T>> {
+            self.cell.take()
+        }
+
+        #[inline]
+        fn is_unused(&self) -> bool {
+            self.get().is_some_and(|rc| Rc::strong_count(rc) == 1)
+        }
+
+        fn stats(&self) -> Option<ResourceStats> {
+            let resource = self.get()?;
+            Some(ResourceStats {
+                cpu_bytes: resource.cpu_bytes(),
+                gpu_bytes: resource.gpu_bytes(),
+                load_time: self.load_time.get(),
+                last_access: self.last_access.get(),
+                users: Rc::strong_count(resource) - 1,
+                pinned: self.pinned,
+            })
+        }
+    }
+
+    pub struct ResourcePool<I: Key, A: Key, F: Key> {
+        images: HashMap<I, Entry<Image>>,
+        audios: HashMap<A, Entry<Audio>>,
+        fonts: HashMap<F, Entry<Font>>,
+        load_image: Loader<I, Image>,
+        load_audio: Loader<A, Audio>,
+        load_font: Loader<F, Font>,
+        image_fallback: Option<Rc<Image>>,
+        font_fallback: Option<Rc<Font>>,
+        manifest: Option<Manifest>,
+        watcher: Option<Watcher>,
+        frame: u64,
+        budget: Option<usize>,
+    }
+
+    impl<I: Key, A: Key, F: Key> ResourcePool<I, A, F> {
+        pub fn new(
+            load_image: impl Fn(&I) -> Result<Image, ResourceError> + 'static,
+            load_audio: impl Fn(&A) -> Result<Audio, ResourceError> + 'static,
+            load_font: impl Fn(&F) -> Result<Font, ResourceError> + 'static,
+        ) -> Self {
+            let mut images = HashMap::new();
+            let mut audios = HashMap::new();
+            let mut fonts = HashMap::new();
+
+            for id in I::iter() {
+                images.insert(id, Entry::new());
+            }
+
+            for id in A::iter() {
+                audios.insert(id, Entry::new());
+            }
+
+            for id in F::iter() {
+                fonts.insert(id, Entry::new());
+            }
+
+            ResourcePool {
+                images,
+                audios,
+                fonts,
+                load_image: Box::new(load_image),
+                load_audio: Box::new(load_audio),
+                load_font: Box::new(load_font),
+                image_fallback: None,
+                font_fallback: None,
+                manifest: None,
+                watcher: None,
+                frame: 0,
+                budget: None,
+            }
+        }
+
+        // Memory, in bytes on the CPU and GPU together, that `end_frame` evicts down to.
+        pub fn with_budget(mut self, bytes: usize) -> Self {
+            self.budget = Some(bytes);
+            self
+        }
+
+        #[inline]
+        pub fn set_budget(&mut self, bytes: Option<usize>) {
+            self.budget = bytes;
+        }
+
+        #[inline]
+        pub fn budget(&self) -> Option<usize> {
+            self.budget
+        }
+
+        #[inline]
+        pub fn frame(&self) -> u64 {
+            self.frame
+        }
+
+        // Images that fail to load are replaced by `image` in `get_image` instead of panicking.
+        pub fn with_image_fallback(mut self, image: Image) -> Self {
+            self.image_fallback = Some(Rc::new(image));
+            self
+        }
+
+        pub fn with_font_fallback(mut self, font: Font) -> Self {
+            self.font_fallback = Some(Rc::new(font));
+            self
+        }
+
+        // Falls back to the checkerboard image and the builtin font.
+        pub fn with_placeholders(self) -> Self {
+            self.with_image_fallback(Image::placeholder())
+                .with_font_fallback(Font::placeholder())
+        }
+
+        // Failed loads are not cached, the next call tries the loader again.
+        pub fn try_get_image(&self, id: I) -> Result<Rc<Image>, ResourceError> {
+            try_get(&self.images[&id], self.frame, || (self.load_image)(&id))
+        }
+
+        pub fn try_get_audio(&self, id: A) -> Result<Rc<Audio>, ResourceError> {
+            try_get(&self.audios[&id], self.frame, || (self.load_audio)(&id))
+        }
+
+        pub fn try_get_font(&self, id: F) -> Result<Rc<Font>, ResourceError> {
+            try_get(&self.fonts[&id], self.frame, || (self.load_font)(&id))
+        }
+
+        pub fn get_image(&self, id: I) -> Rc<Image> {
+            or_fallback(self.try_get_image(id), &self.image_fallback)
+        }
+
+        pub fn get_audio(&self, id: A) -> Rc<Audio> {
+            or_fallback(self.try_get_audio(id), &None)
+        }
+
+        pub fn get_font(&self, id: F) -> Rc<Font> {
+            or_fallback(self.try_get_font(id), &self.font_fallback)
+        }
+
+        #[inline]
+        pub fn manifest(&self) -> Option<&Manifest> {
+            self.manifest.as_ref()
+        }
+
+        // Drops every entry that is only held by the pool, except pinned ones.
+        pub fn try_clear(&mut self) {
+            clear_unused(&mut self.images);
+            clear_unused(&mut self.audios);
+            clear_unused(&mut self.fonts);
+        }
+
+        pub fn full_clear(&mut self) {
+            for entry in self.images.values_mut() {
+                entry.take();
+            }
+
+            for entry in self.audios.values_mut() {
+                entry.take();
+            }
+
+            for entry in self.fonts.values_mut() {
+                entry.take();
+            }
+        }
+
+        // Pinned entries are never evicted or cleared by `try_clear`, only by `full_clear`.
+        pub fn pin_image(&mut self, id: I, pinned: bool) {
+            self.images.get_mut(&id).unwrap().pinned = pinned;
+        }
+
+        pub fn pin_audio(&mut self, id: A, pinned: bool) {
+            self.audios.get_mut(&id).unwrap().pinned = pinned;
+        }
+
+        pub fn pin_font(&mut self, id: F, pinned: bool) {
+            self.fonts.get_mut(&id).unwrap().pinned = pinned;
+        }
+
+        // `None` while the entry is not loaded.
+        pub fn image_stats(&self, id: I) -> Option<ResourceStats> {
+            self.images[&id].stats()
+        }
+
+        pub fn audio_stats(&self, id: A) -> Option<ResourceStats> {
+            self.audios[&id].stats()
+        }
+
+        pub fn font_stats(&self, id: F) -> Option<ResourceStats> {
+            self.fonts[&id].stats()
+        }
+
+        pub fn stats(&self) -> PoolStats {
+            let all = (self.images.values().filter_map(Entry::stats))
+                .chain(self.audios.values().filter_map(Entry::stats))
+                .chain(self.fonts.values().filter_map(Entry::stats));
+
+            all.fold(
+                PoolStats {
+                    frame: self.frame,
+                    budget: self.budget,
+                    ..Default::default()
+                },
+                |mut pool, stats| {
+                    pool.loaded += 1;
+                    pool.pinned += stats.pinned as usize;
+                    pool.cpu_bytes += stats.cpu_bytes;
+                    pool.gpu_bytes += stats.gpu_bytes;
+                    pool
+                },
+            )
+        }
+
+        // Advances the frame counter used for the last access of entries, and evicts down to
+        // the budget if there is one. Call it once per frame.
+        pub fn end_frame(&mut self) {
+            self.frame += 1;
+
+            if let Some(budget) = self.budget {
+                self.evict(budget);
+            }
+        }
+
+        // Drops the least recently used entries until the pool fits in `budget` bytes,
+        // returning how many were dropped. Only entries held by nothing but the pool, and not
+        // pinned, can be dropped, since the others would stay in memory anyway.
+        pub fn evict(&mut self, budget: usize) -> usize {
+            let mut total = self.stats().total_bytes();
+            if total <= budget {
+                return 0;
+            }
+
+            let mut candidates = Vec::new();
+            candidates.extend(lru_candidates(&self.images, Slot::Image));
+            candidates.extend(lru_candidates(&self.audios, Slot::Audio));
+            candidates.extend(lru_candidates(&self.fonts, Slot::Font));
+            candidates.sort_by_key(|(last_access, ..)| *last_access);
+
+            let mut chosen = Vec::new();
+            for (_, bytes, slot, position) in candidates {
+                if total <= budget {
+                    break;
+                }
+                total -= bytes;
+                chosen.push((slot, position));
+            }
+
+            take_chosen(&mut self.images, Slot::Image, &chosen);
+            take_chosen(&mut self.audios, Slot::Audio, &chosen);
+            take_chosen(&mut self.fonts, Slot::Font, &chosen);
+
+            chosen.len()
+        }
+    }
+
+    // Manifest entries are named after the `Debug` output of the keys, which for the plain
+    // enums used as keys is the variant name.
+    impl<I, A, F> ResourcePool<I, A, F>
+    where
+        I: Key + Debug + 'static,
+        A: Key + Debug + 'static,
+        F: Key + Debug + 'static,
+    {
+        // Builds a pool that loads every key from the path the manifest gives it. The manifest
+        // is validated first, and every missing file, unmapped key or unknown entry is
+        // reported together.
+        pub fn from_manifest(manifest: Manifest) -> Result<Self, Vec<ResourceError>> {
+            let mut errors = manifest.validate();
+
+            check_keys::<I>(&manifest.images, &mut errors);
+            check_keys::<A>(&manifest.audios, &mut errors);
+            check_keys::<F>(&manifest.fonts, &mut errors);
+
+            if !errors.is_empty() {
+                return Err(errors);
+            }
+
+            let paths = |entries: &BTreeMap<String, String>| -> HashMap<String, PathBuf> {
+                entries
+                    .iter()
+                    .map(|(name, path)| (name.clone(), manifest.root().join(path)))
+                    .collect()
+            };
+            let images = paths(&manifest.images);
+            let audios = paths(&manifest.audios);
+            let fonts = paths(&manifest.fonts);
+
+            let (image_vfs, font_vfs) = (manifest.vfs().clone(), manifest.vfs().clone());
+
+            let mut pool = Self::new(
+                move |id: &I| {
+                    let path = &images[&key_name(id)];
+                    let name = path.to_string_lossy();
+                    let bytes = image_vfs.read(path)?;
+                    Image::from_memory(&bytes).ok_or_else(|| ResourceError::decode(&name))
+                },
+                move |id: &A| Audio::load(&audios[&key_name(id)].to_string_lossy()),
+                move |id: &F| {
+                    let path = &fonts[&key_name(id)];
+                    let name = path.to_string_lossy();
+                    let bytes = font_vfs.read(path)?;
+                    Font::from_memory(bytes).ok_or_else(|| ResourceError::decode(&name))
+                },
+            );
+            pool.manifest = Some(manifest);
+
+            Ok(pool)
+        }
+
+        // Loads every entry of a manifest group ahead of time, returning the ones that failed.
+        pub fn preload(&self, group: &str) -> Result<(), Vec<ResourceError>> {
+            let manifest = self.manifest.as_ref();
+            let names = manifest
+                .and_then(|manifest| manifest.group(group))
+                .ok_or_else(|| vec![ResourceError::invalid(group, "unknown group")])?;
+
+            let mut errors = Vec::new();
+            for name in names {
+                let loaded = if let Some(id) = find_key::<I>(name) {
+                    self.try_get_image(id).map(drop)
+                } else if let Some(id) = find_key::<A>(name) {
+                    self.try_get_audio(id).map(drop)
+                } else if let Some(id) = find_key::<F>(name) {
+                    self.try_get_font(id).map(drop)
+                } else {
+                    Err(ResourceError::invalid(name, "unknown key"))
+                };
+
+                if let Err(error) = loaded {
+                    errors.push(error);
+                }
+            }
+
+            if errors.is_empty() {
+                Ok(())
+            } else {
+                Err(errors)
+            }
+        }
+
+        pub fn atlas(&self, atlas: &str) -> Vec<I> {
+            let names = self
+                .manifest
+                .as_ref()
+                .and_then(|manifest| manifest.atlas(atlas));
+            names
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|name| find_key::<I>(name))
+                .collect()
+        }
+
+        pub fn atlas_of(&self, id: &I) -> Option<&str> {
+            self.manifest.as_ref()?.atlas_of(&key_name(id))
+        }
+
+        // Starts loading every entry of a manifest group that isn't loaded yet in the
+        // background, see `AsyncLoad`.
+        pub fn load_async(&self, group: &str) -> Result<AsyncLoad<I, A, F>, ResourceError> {
+            let manifest = self
+                .manifest
+                .as_ref()
+                .ok_or_else(|| ResourceError::invalid(group, "pool has no manifest"))?;
+            let names = manifest
+                .group(group)
+                .ok_or_else(|| ResourceError::invalid(group, "unknown group"))?;
+
+            let mut jobs = Vec::new();
+            for name in names {
+                let job = if let Some(id) = find_key::<I>(name) {
+                    let path = manifest.image_path(name);
+                    path.filter(|_| self.images[&id].get().is_none())
+                        .map(|path| Job::new(Kind::Image, name, path, manifest.vfs()))
+                } else if let Some(id) = find_key::<A>(name) {
+                    let path = manifest.audio_path(name);
+                    path.filter(|_| self.audios[&id].get().is_none())
+                        .map(|path| Job::new(Kind::Audio, name, path, manifest.vfs()))
+                } else if let Some(id) = find_key::<F>(name) {
+                    let path = manifest.font_path(name);
+                    path.filter(|_| self.fonts[&id].get().is_none())
+                        .map(|path| Job::new(Kind::Font, name, path, manifest.vfs()))
+                } else {
+                    None
+                };
+
+                jobs.extend(job);
+            }
+
+            Ok(AsyncLoad::start(jobs, manifest.vfs()))
+        }
+
+        pub(super) fn store_image(&self, name: &str, image: Image, load_time: Duration) {
+            if let Some(id) = find_key::<I>(name) {
+                self.images[&id].set(image, load_time, self.frame);
+            }
+        }
+
+        pub(super) fn store_font(&self, name: &str, font: Font, load_time: Duration) {
+            if let Some(id) = find_key::<F>(name) {
+                self.fonts[&id].set(font, load_time, self.frame);
+            }
+        }
+
+        // A table of every loaded entry, largest first, for debugging memory use.
+        pub fn debug_dump(&self) -> String {
+            let mut rows = Vec::new();
+            rows.extend(dump_rows("image", &self.images));
+            rows.extend(dump_rows("audio", &self.audios));
+            rows.extend(dump_rows("font", &self.fonts));
+            rows.sort_by_key(|(_, _, stats)| std::cmp::Reverse(stats.cpu_bytes + stats.gpu_bytes));
+
+            let pool = self.stats();
+            let mut dump = format!(
+                "{} loaded, {} pinned, {} KiB CPU, {} KiB GPU, frame {}",
+                pool.loaded,
+                pool.pinned,
+                pool.cpu_bytes / 1024,
+                pool.gpu_bytes / 1024,
+                pool.frame
+            );
+            if let Some(budget) = pool.budget {
+                let _ = write!(dump, ", budget {} KiB", budget / 1024);
+            }
+            dump.push('\n');
+
+            let _ = writeln!(
+                dump,
+                "{:<6} {:<24} {:>10} {:>10} {:>5} {:>8} {:>9} {:>6}",
+                "kind", "name", "cpu KiB", "gpu KiB", "users", "access", "load ms", "pinned"
+            );
+            for (kind, name, stats) in rows {
+                let _ = writeln!(
+                    dump,
+                    "{:<6} {:<24} {:>10} {:>10} {:>5} {:>8} {:>9.1} {:>6}",
+                    kind,
+                    name,
+                    stats.cpu_bytes / 1024,
+                    stats.gpu_bytes / 1024,
+                    stats.users,
+                    stats.last_access,
+                    stats.load_time.as_secs_f64() * 1000.0,
+                    if stats.pinned { "yes" } else { "" }
+                );
+            }
+
+            dump
+        }
+
+        // Development aid: watches the files behind the manifest's images and fonts, checking
+        // them every `interval` in `poll_reload`. Only pools built from a manifest know their
+        // paths, other files can still be added with `watch_file`.
+        pub fn with_hot_reload(mut self, interval: Duration) -> Self {
+            let mut watcher = Watcher::new(interval);
+
+            if let Some(manifest) = &self.manifest {
+                for name in manifest.images.keys() {
+                    if let Some(path) = manifest.image_path(name) {
+                        watcher.watch(path, Target::Image(name.clone()));
+                    }
+                }
+                for name in manifest.fonts.keys() {
+                    if let Some(path) = manifest.font_path(name) {
+                        watcher.watch(path, Target::Font(name.clone()));
+                    }
+                }
+            }
+
+            self.watcher = Some(watcher);
+            self
+        }
+
+        // Watches any other file, such as a level, reported as `Reload::File` when it changes.
+        // Does nothing unless hot reloading was enabled.
+        pub fn watch_file(&mut self, path: &str) {
+            if let Some(watcher) = &mut self.watcher {
+                watcher.watch(PathBuf::from(path), Target::File);
+            }
+        }
+
+        #[inline]
+        pub fn is_hot_reloading(&self) -> bool {
+            self.watcher.is_some()
+        }
+
+        // Reloads the loaded images and fonts whose files changed, in place, so existing `Rc`s
+        // and sprites see the new contents. Entries that were never loaded are skipped, they
+        // pick up the new file when first requested. A failed reload keeps the old contents.
+        pub fn poll_reload(&mut self) -> Vec<Reload<I, F>> {
+            let changed = match &mut self.watcher {
+                Some(watcher) => watcher.poll(),
+                None => return Vec::new(),
+            };
+
+            // Frees the contents replaced by the previous poll, this is the only place shared
+            // resources drop a version, see `Versions::current`.
+            for image in self.images.values().filter_map(Entry::get) {
+                image.release_previous();
+            }
+            for font in self.fonts.values().filter_map(Entry::get) {
+                font.release_previous();
+            }
+
+            let mut events = Vec::new();
+            for (path, target) in changed {
+                let event = match target {
+                    Target::Image(name) => find_key::<I>(&name).and_then(|id| {
+                        let image = self.images[&id].get()?;
+                        Some(match (self.load_image)(&id) {
+                            Ok(new) => {
+                                image.reload_from(new);
+                                Reload::Image(id)
+                            }
+                            Err(error) => Reload::Failed(error),
+                        })
+                    }),
+                    Target::Font(name) => find_key::<F>(&name).and_then(|id| {
+                        let font = self.fonts[&id].get()?;
+                        Some(match (self.load_font)(&id) {
+                            Ok(new) => {
+                                font.reload_from(new);
+                                Reload::Font(id)
+                            }
+                            Err(error) => Reload::Failed(error),
+                        })
+                    }),
+                    Target::File => Some(Reload::File(path)),
+                };
+
+                events.extend(event);
+            }
+
+            events
+        }
+    }
+
+    fn try_get<T: Usage>(
+        entry: &Entry<T>,
+        frame: u64,
+        load: impl FnOnce() -> Result<T, ResourceError>,
+    ) -> Result<Rc<T>, ResourceError> {
+        entry.last_access.set(frame);
+        entry
+            .cell
+            .get_or_try_init(|| {
+                let start = Instant::now();
+                let resource = load()?;
+                entry.load_time.set(start.elapsed());
+                Ok(Rc::new(resource))
+            })
+            .map(Rc::clone)
+    }
+
+    fn clear_unused<K, T: Usage>(entries: &mut HashMap<K, Entry<T>>) {
+        for entry in entries.values_mut() {
+            if !entry.pinned && entry.is_unused() {
+                entry.take();
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Slot {
+        Image,
+        Audio,
+        Font,
+    }
+
+    // Keys can't be cloned, so entries are told apart by their position in the map, which
+    // doesn't change as long as no key is added or removed.
+    fn lru_candidates<K, T: Usage>(
+        entries: &HashMap<K, Entry<T>>,
+        slot: Slot,
+    ) -> impl Iterator<Item = (u64, usize, Slot, usize)> + '_ {
+        entries
+            .values()
+            .enumerate()
+            .filter(|(_, entry)| !entry.pinned && entry.is_unused())
+            .filter_map(move |(position, entry)| {
+                let stats = entry.stats()?;
+                let bytes = stats.cpu_bytes + stats.gpu_bytes;
+                Some((stats.last_access, bytes, slot, position))
+            })
+    }
+
+    fn take_chosen<K, T: Usage>(
+        entries: &mut HashMap<K, Entry<T>>,
+        slot: Slot,
+        chosen: &[(Slot, usize)],
+    ) {
+        for (position, entry) in entries.values_mut().enumerate() {
+            if chosen.contains(&(slot, position)) {
+                entry.take();
+            }
+        }
+    }
+
+    fn key_name<K: Debug>(key: &K) -> String {
+        format!("{:?}", key)
+    }
+
+    fn find_key<K: Key + Debug>(name: &str) -> Option<K> {
+        K::iter().find(|key| key_name(key) == name)
+    }
+
+    fn dump_rows<K: Debug, T: Usage>(
+        kind: &'static str,
+        entries: &HashMap<K, Entry<T>>,
+    ) -> Vec<(&'static str, String, ResourceStats)> {
+        entries
+            .iter()
+            .filter_map(|(key, entry)| Some((kind, key_name(key), entry.stats()?)))
+            .collect()
+    }
+
+    fn check_keys<K: Key + Debug>(
+        entries: &BTreeMap<String, String>,
+        errors: &mut Vec<ResourceError>,
+    ) {
+        for key in K::iter() {
+            let name = key_name(&key);
+            if !entries.contains_key(&name) {
+                errors.push(ResourceError::invalid(&name, "no entry in manifest"));
+            }
+        }
+
+        for name in entries.keys() {
+            if find_key::<K>(name).is_none() {
+                errors.push(ResourceError::invalid(name, "entry does not match any key"));
+            }
+        }
+    }
+
+    fn or_fallback<T>(resource: Result<Rc<T>, ResourceError>, fallback: &Option<Rc<T>>) -> Rc<T> {
+        match (resource, fallback) {
+            (Ok(resource), _) => resource,
+            (Err(_), Some(fallback)) => Rc::clone(fallback),
+            (Err(error), None) => panic!("Failed to load resource {}", error),
+        }
+    }
+}
+pub use resource_pool::*;
+
+mod loading {
+    use std::{
+        fmt::Debug,
+        marker::PhantomData,
+        panic::{self, AssertUnwindSafe},
+        path::PathBuf,
+        sync::{
+            mpsc::{self, Receiver, TryRecvError},
+            Arc, Mutex,
+        },
+        thread,
+        time::{Duration, Instant},
+    };
+
+    use sfml::graphics::Image as SfmlImage;
+
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+    pub struct LoadProgress {
+        pub loaded: usize,
+        pub total: usize,
+        pub bytes_loaded: u64,
+        pub bytes_total: u64,
+    }
+
+    impl LoadProgress {
+        // Weighted by file size, falling back to the entry count when sizes are unknown.
+        pub fn fraction(&self) -> f32 {
+            if self.bytes_total > 0 {
+                self.bytes_loaded as f32 / self.bytes_total as f32
+            } else if self.total > 0 {
+                self.loaded as f32 / self.total as f32
+            } else {
+                1.0
+            }
+        }
+
+        #[inline]
+        pub fn is_complete(&self) -> bool {
+            self.loaded >= self.total
+        }
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub(super) enum Kind {
+        Image,
+        Audio,
+        Font,
+    }
+
+    pub(super) struct Job {
+        pub kind: Kind,
+        pub name: String,
+        pub path: PathBuf,
+        pub size: u64,
+    }
+
+    impl Job {
+        pub fn new(kind: Kind, name: &str, path: PathBuf, vfs: &Vfs) -> Self {
+            let size = vfs.size(&path).unwrap_or(0);
+            Job {
+                kind,
+                name: name.to_string(),
+                path,
+                size,
+            }
+        }
+    }
+
+    enum Decoded {
+        Image {
+            width: u32,
+            height: u32,
+            pixels: Vec<u8>,
+        },
+        Font(Vec<u8>),
+    }
+
+    struct Done {
+        job: Job,
+        result: Result<Decoded, ResourceError>,
+        decode_time: Duration,
+    }
+
+    // Files are read and images decoded on worker threads, while textures and fonts are
+    // created on the thread calling `poll`, which must be the one that owns the window.
+    pub struct AsyncLoad<I, A, F> {
+        receiver: Receiver<Done>,
+        // Paths and sizes of the jobs that have not reported back yet.
+        pending: Vec<(PathBuf, u64)>,
+        progress: LoadProgress,
+        errors: Vec<ResourceError>,
+        keys: PhantomData<(I, A, F)>,
+    }
+
+    impl<I, A, F> AsyncLoad<I, A, F>
+    where
+        I: Key + Debug + 'static,
+        A: Key + Debug + 'static,
+        F: Key + Debug + 'static,
+    {
+        pub(super) fn start(jobs: Vec<Job>, vfs: &Vfs) -> Self {
+            let progress = LoadProgress {
+                loaded: 0,
+                total: jobs.len(),
+                bytes_loaded: 0,
+                bytes_total: jobs.iter().map(|job| job.size).sum(),
+            };
+
+            let pending = jobs
+                .iter()
+                .map(|job| (job.path.clone(), job.size))
+                .collect();
+
+            let workers = thread::available_parallelism()
+                .map(|count| count.get())
+                .unwrap_or(2)
+                .clamp(1, 4)
+                .min(jobs.len());
+
+            let queue = Arc::new(Mutex::new(jobs));
+            let (sender, receiver) = mpsc::channel();
+
+            for _ in 0..workers {
+                let queue = Arc::clone(&queue);
+                let sender = sender.clone();
+                let vfs = vfs.clone();
+
+                thread::spawn(move || loop {
+                    let job = match queue.lock() {
+                        Ok(mut queue) => queue.pop(),
+                        Err(_) => None,
+                    };
+                    let Some(job) = job else { break };
+
+                    // A panicking decoder fails its entry rather than leaving it pending.
+                    let start = Instant::now();
+                    let result = panic::catch_unwind(AssertUnwindSafe(|| decode(&job, &vfs)))
+                        .unwrap_or_else(|_| {
+                            let path = job.path.to_string_lossy();
+                            Err(ResourceError::invalid(&path, "loader thread panicked"))
+                        });
+                    let decode_time = start.elapsed();
+
+                    // The receiving end is gone once the load is dropped, so stop early.
+                    let done = Done {
+                        job,
+                        result,
+                        decode_time,
+                    };
+                    if sender.send(done).is_err() {
+                        break;
+                    }
+                });
+            }
+
+            AsyncLoad {
+                receiver,
+                pending,
+                progress,
+                errors: Vec::new(),
+                keys: PhantomData,
+            }
+        }
+
+        // Moves every decoded entry into `pool`, creating its texture or font, and returns the
+        // progress so far. Call it once per frame from the main thread.
+        pub fn poll(&mut self, pool: &ResourcePool<I, A, F>) -> LoadProgress {
+            loop {
+                let Done {
+                    job,
+                    result,
+                    decode_time,
+                } = match self.receiver.try_recv() {
+                    Ok(done) => done,
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => {
+                        // Every worker is gone, so whatever is still pending never loads.
+                        for (path, size) in self.pending.drain(..) {
+                            self.errors.push(ResourceError::invalid(
+                                &path.to_string_lossy(),
+                                "loader thread stopped before loading it",
+                            ));
+                            self.progress.loaded += 1;
+                            self.progress.bytes_loaded += size;
+                        }
+                        break;
+                    }
+                };
+
+                if let Some(index) = self.pending.iter().position(|(path, _)| *path == job.path) {
+                    self.pending.swap_remove(index);
+                }
+
+                let path = job.path.to_string_lossy();
+                let start = Instant::now();
+
+                let stored = result.and_then(|decoded| match decoded {
+                    Decoded::Image {
+                        width,
+                        height,
+                        pixels,
+                    } => Image::from_rgba(width, height, &pixels)
+                        .ok_or_else(|| ResourceError::texture(&path))
+                        .map(|image| {
+                            pool.store_image(&job.name, image, decode_time + start.elapsed())
+                        }),
+                    Decoded::Font(bytes) => Font::from_memory(bytes)
+                        .ok_or_else(|| ResourceError::decode(&path))
+                        .map(|font| {
+                            pool.store_font(&job.name, font, decode_time + start.elapsed())
+                        }),
+                });
+
+                if let Err(error) = stored {
+                    self.errors.push(error);
+                }
+
+                self.progress.loaded += 1;
+                self.progress.bytes_loaded += job.size;
+            }
+
+            self.progress
+        }
+
+        #[inline]
+        pub fn progress(&self) -> LoadProgress {
+            self.progress
+        }
+
+        #[inline]
+        pub fn is_complete(&self) -> bool {
+            self.progress.is_complete()
+        }
+
+        #[inline]
+        pub fn errors(&self) -> &[ResourceError] {
+            &self.errors
+        }
+    }
+
+    fn decode(job: &Job, vfs: &Vfs) -> Result<Decoded, ResourceError> {
+        let path = job.path.to_string_lossy();
+
+        match job.kind {
+            Kind::Image => {
+                let bytes = vfs.read(&job.path)?;
+                let image =
+                    SfmlImage::from_memory(&bytes).ok_or_else(|| ResourceError::decode(&path))?;
+                let size = image.size();
+                Ok(Decoded::Image {
+                    width: size.x,
+                    height: size.y,
+                    pixels: image.pixel_data().to_vec(),
+                })
+            }
+            Kind::Font => Ok(Decoded::Font(vfs.read(&job.path)?)),
+            Kind::Audio => Err(ResourceError::invalid(&path, "audio is not supported")),
+        }
+    }
+}
+pub use loading::*;
+
+mod cursor {
+    use std::time::Duration;
+
+    use sfml::{system::Vector2u, window::Cursor as SfmlCursor};
+
+    use super::*;
+
+    pub use sfml::window::CursorType as SystemCursor;
+
+    enum CursorSource {
+        Image { image: Rc<Image>, hotspot: Vector2u },
+        System(SystemCursor),
+    }
+
+    // An image or system cursor. Image cursors the platform can't create still work, the
+    // window draws them itself, see `Window::set_software_cursor`.
+    pub struct Cursor {
+        source: CursorSource,
+        cursor: Option<SfBox<SfmlCursor>>,
+    }
+
+    impl Cursor {
+        // The hotspot starts at the center of the image, see `with_hotspot`.
+        pub fn from_image(image: &Rc<Image>) -> Self {
+            Cursor::image_cursor(Rc::clone(image), image.size() / 2)
+        }
+
+        pub fn from_memory(bytes: &[u8]) -> Option<Self> {
+            Image::from_memory(bytes).map(|image| Cursor::from_image(&Rc::new(image)))
+        }
+
+        pub fn from_rgba(width: u32, height: u32, pixels: &[u8]) -> Option<Self> {
+            Image::from_rgba(width, height, pixels).map(|image| Cursor::from_image(&Rc::new(image)))
+        }
+
+        // Returns `None` when the platform has no such cursor.
+        pub fn system(kind: SystemCursor) -> Option<Self> {
+            SfmlCursor::from_system(kind).map(|cursor| Cursor {
+                source: CursorSource::System(kind),
+                cursor: Some(cursor),
+            })
+        }
+
+        fn image_cursor(image: Rc<Image>, hotspot: Vector2u) -> Self {
+            let size = image.size();
+            let hotspot = Vector2u::new(
+                hotspot.x.min(size.x.saturating_sub(1)),
+                hotspot.y.min(size.y.saturating_sub(1)),
+            );
+            // The pixels hold exactly `size` RGBA pixels and the hotspot was clamped to them.
+            let cursor = unsafe { SfmlCursor::from_pixels(image.pixels(), size, hotspot) };
+
+            Cursor {
+                source: CursorSource::Image { image, hotspot },
+                cursor,
+            }
+        }
+
+        // The pixel of the image that points at the mouse position, clamped to the image.
+        // System cursors keep their own hotspot.
+        pub fn with_hotspot(self, x: u32, y: u32) -> Self {
+            match self.source {
+                CursorSource::Image { image, .. } => {
+                    Cursor::image_cursor(image, Vector2u::new(x, y))
+                }
+                CursorSource::System(_) => self,
+            }
+        }
+
+        #[inline]
+        pub fn hotspot(&self) -> Vector2u {
+            match &self.source {
+                CursorSource::Image { hotspot, .. } => *hotspot,
+                CursorSource::System(_) => Vector2u::new(0, 0),
+            }
+        }
+
+        #[inline]
+        pub fn image(&self) -> Option<&Rc<Image>> {
+            match &self.source {
+                CursorSource::Image { image, .. } => Some(image),
+                CursorSource::System(_) => None,
+            }
+        }
+
+        #[inline]
+        pub fn system_cursor(&self) -> Option<SystemCursor> {
+            match &self.source {
+                CursorSource::Image { .. } => None,
+                CursorSource::System(kind) => Some(*kind),
+            }
+        }
+
+        // `None` when the platform couldn't create the cursor.
+        #[inline]
+        pub fn cursor(&self) -> Option<&SfmlCursor> {
+            self.cursor.as_deref()
+        }
+    }
+
+    // Cursors shown for the given duration each, looping.
+    pub struct AnimatedCursor {
+        frames: Vec<(Cursor, Duration)>,
+        elapsed: Duration,
+        current: usize,
+    }
+
+    impl AnimatedCursor {
+        pub fn new(frames: Vec<(Cursor, Duration)>) -> Option<Self> {
+            if frames.is_empty() {
+                return None;
+            }
+
+            Some(AnimatedCursor {
+                frames,
+                elapsed: Duration::ZERO,
+                current: 0,
+            })
+        }
+
+        // Every frame of `images` with the same hotspot, shown for `frame_time` each.
+        pub fn from_images(
+            images: &[Rc<Image>],
+            hotspot: Vector2u,
+            frame_time: Duration,
+        ) -> Option<Self> {
+            let frames = images
+                .iter()
+                .map(|image| {
+                    let cursor = Cursor::from_image(image).with_hotspot(hotspot.x, hotspot.y);
+                    (cursor, frame_time)
+                })
+                .collect();
+
+            AnimatedCursor::new(frames)
+        }
+
+        // Returns whether the current frame changed.
+        pub fn update(&mut self, frame_time: Duration) -> bool {
+            self.elapsed += frame_time;
+
+            let total: Duration = self.frames.iter().map(|(_, duration)| *duration).sum();
+            if total.is_zero() {
+                return false;
+            }
+
+            let mut time = self.elapsed.as_nanos() % total.as_nanos();
+            let mut frame = 0;
+            for (i, (_, duration)) in self.frames.iter().enumerate() {
+                if time < duration.as_nanos() {
+                    frame = i;
+                    break;
+                }
+                time -= duration.as_nanos();
+            }
+
+            let changed = frame != self.current;
+            self.current = frame;
+            changed
+        }
+
+        #[inline]
+        pub fn current(&self) -> &Cursor {
+            &self.frames[self.current].0
         }
 
-        pub fn cursor(&self) -> &SfmlCursor {
-            &self.cursor
+        #[inline]
+        pub fn restart(&mut self) {
+            self.elapsed = Duration::ZERO;
+            self.current = 0;
         }
     }
 }
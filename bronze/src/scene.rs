@@ -1,15 +1,36 @@
 use std::{marker::PhantomData, time::Duration};
 
 use crate::{
+    graphics::Color,
     input::InputManager,
-    shape::{BBox, DrawBBox, ShapeRef},
+    math::Vec2,
+    physics::{resolve, Body, Manifold, DEFAULT_GRAVITY},
+    shape::{BBox, DrawBBox, ShapeRef, DEBUG_COLOR},
     window::Canvas,
 };
 
 pub trait Entity<Ctx> {
     #[inline]
     fn bbox(&self) -> ShapeRef {
-        ShapeRef::None
+        match self.body() {
+            Some(body) => body.as_ref(),
+            None => ShapeRef::None,
+        }
+    }
+
+    #[inline]
+    fn body(&self) -> Option<&Body> {
+        None
+    }
+
+    #[inline]
+    fn body_mut(&mut self) -> Option<&mut Body> {
+        None
+    }
+
+    #[inline]
+    fn debug_color(&self) -> Color {
+        DEBUG_COLOR
     }
 
     #[inline]
@@ -46,6 +67,11 @@ pub trait Collision<T: Entity<Ctx>, Ctx> {
     fn on_collision(&mut self, other: &T, ctx: &mut Ctx);
 }
 
+pub enum Spawn<S, D> {
+    Static(S),
+    Dynamic(D),
+}
+
 pub struct Scene<S, D, Ctx>
 where
     S: Entity<Ctx> + Collision<D, Ctx>,
@@ -53,6 +79,7 @@ where
 {
     static_entities: Vec<S>,
     dynamic_entities: Vec<D>,
+    gravity: Vec2,
     phantom: PhantomData<Ctx>,
 }
 
@@ -66,20 +93,45 @@ where
         Scene {
             static_entities: Vec::new(),
             dynamic_entities: Vec::new(),
+            gravity: DEFAULT_GRAVITY,
             phantom: PhantomData,
         }
     }
 
+    #[inline]
+    pub fn gravity(&self) -> Vec2 {
+        self.gravity
+    }
+
+    #[inline]
+    pub fn set_gravity(&mut self, x: f32, y: f32) {
+        self.gravity = Vec2::new(x, y);
+    }
+
     #[inline]
     pub fn add_static<E: Into<S>>(&mut self, entity: E) {
         self.static_entities.push(entity.into());
     }
 
+    #[inline]
+    pub fn extend_static<E: Into<S>, I: IntoIterator<Item = E>>(&mut self, entities: I) {
+        self.static_entities
+            .extend(entities.into_iter().map(Into::into));
+    }
+
     #[inline]
     pub fn add_dynamic<E: Into<D>>(&mut self, entity: E) {
         self.dynamic_entities.push(entity.into());
     }
 
+    #[inline]
+    pub fn spawn(&mut self, spawn: Spawn<S, D>) {
+        match spawn {
+            Spawn::Static(entity) => self.static_entities.push(entity),
+            Spawn::Dynamic(entity) => self.dynamic_entities.push(entity),
+        }
+    }
+
     pub fn input(&mut self, input: &crate::input::InputManager) {
         for entity in self.static_entities.iter_mut() {
             entity.input(input);
@@ -108,6 +160,24 @@ where
         for entity in self.dynamic_entities.iter_mut() {
             entity.update(ctx, frame_time);
         }
+
+        self.integrate(frame_time);
+    }
+
+    fn integrate(&mut self, frame_time: Duration) {
+        let dt = frame_time.as_secs_f32();
+
+        for entity in self.static_entities.iter_mut() {
+            if let Some(body) = entity.body_mut() {
+                body.integrate(self.gravity, dt);
+            }
+        }
+
+        for entity in self.dynamic_entities.iter_mut() {
+            if let Some(body) = entity.body_mut() {
+                body.integrate(self.gravity, dt);
+            }
+        }
     }
 
     pub fn post_update(&mut self, ctx: &Ctx) {
@@ -152,6 +222,8 @@ where
             let a = &mut a_right[0];
             let b = &mut b_right[0];
 
+            resolve_bodies(a, b);
+
             a.on_collision(b, ctx);
             b.on_collision(a, ctx);
         }
@@ -160,6 +232,8 @@ where
             let a = &mut self.dynamic_entities[i];
             let b = &mut self.static_entities[j];
 
+            resolve_bodies(a, b);
+
             a.on_collision(b, ctx);
             b.on_collision(a, ctx);
         }
@@ -177,11 +251,11 @@ where
 
     pub fn draw_bboxes(&self, canvas: &mut Canvas) {
         for entity in self.static_entities.iter() {
-            entity.bbox().draw(canvas);
+            entity.bbox().draw_colored(canvas, entity.debug_color());
         }
 
         for entity in self.dynamic_entities.iter() {
-            entity.bbox().draw(canvas);
+            entity.bbox().draw_colored(canvas, entity.debug_color());
         }
     }
 
@@ -201,3 +275,18 @@ where
         Some(self.dynamic_entities.swap_remove(index))
     }
 }
+
+fn resolve_bodies<A, B, Ctx>(a: &mut A, b: &mut B)
+where
+    A: Entity<Ctx>,
+    B: Entity<Ctx>,
+{
+    let manifold = match Manifold::between(&a.bbox(), &b.bbox()) {
+        Some(manifold) => manifold,
+        None => return,
+    };
+
+    if let (Some(a), Some(b)) = (a.body_mut(), b.body_mut()) {
+        resolve(a, b, &manifold);
+    }
+}
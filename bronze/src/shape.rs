@@ -1,5 +1,7 @@
 use sfml::system::Vector2f;
 
+use crate::math::{Transform2D, Vec2};
+
 mod prelude {
     use super::*;
 
@@ -51,10 +53,16 @@ mod prelude {
 
         fn intersects_circle(&self, other: &Circle) -> bool;
 
+        fn intersects_bitmask(&self, other: &BitMask) -> bool;
+
         fn intersects_mixed(&self, other: &Mixed) -> bool {
             other.shapes.iter().any(|shape| shape.intersects(self))
         }
 
+        fn intersects_compound(&self, other: &Compound) -> bool {
+            other.shapes().iter().any(|shape| shape.intersects(self))
+        }
+
         fn intersects<B: BBox>(&self, other: &B) -> bool;
     }
 
@@ -89,6 +97,10 @@ mod prelude {
         fn move_by(&mut self, x: f32, y: f32);
     }
 
+    pub trait ApplyTransform {
+        fn transform(&mut self, transform: &Transform2D);
+    }
+
     pub trait ShapeCollision {
         fn collides(&self, other: &ShapeRef) -> bool;
     }
@@ -98,7 +110,9 @@ mod prelude {
         Point(&'b Point),
         Rect(&'b Rect),
         Circle(&'b Circle),
+        BitMask(&'b BitMask),
         Mixed(&'b Mixed<'b>),
+        Compound(&'b Compound),
     }
 
     impl BBox for ShapeRef<'_> {
@@ -108,7 +122,9 @@ mod prelude {
                 ShapeRef::Point(point) => point.left(),
                 ShapeRef::Rect(rect) => rect.left(),
                 ShapeRef::Circle(circle) => circle.left(),
+                ShapeRef::BitMask(bitmask) => bitmask.left(),
                 ShapeRef::Mixed(mixed) => mixed.left(),
+                ShapeRef::Compound(compound) => compound.left(),
             }
         }
 
@@ -118,7 +134,9 @@ mod prelude {
                 ShapeRef::Point(point) => point.top(),
                 ShapeRef::Rect(rect) => rect.top(),
                 ShapeRef::Circle(circle) => circle.top(),
+                ShapeRef::BitMask(bitmask) => bitmask.top(),
                 ShapeRef::Mixed(mixed) => mixed.top(),
+                ShapeRef::Compound(compound) => compound.top(),
             }
         }
 
@@ -128,7 +146,9 @@ mod prelude {
                 ShapeRef::Point(point) => point.width(),
                 ShapeRef::Rect(rect) => rect.width(),
                 ShapeRef::Circle(circle) => circle.width(),
+                ShapeRef::BitMask(bitmask) => bitmask.width(),
                 ShapeRef::Mixed(mixed) => mixed.width(),
+                ShapeRef::Compound(compound) => compound.width(),
             }
         }
 
@@ -138,7 +158,9 @@ mod prelude {
                 ShapeRef::Point(point) => point.height(),
                 ShapeRef::Rect(rect) => rect.height(),
                 ShapeRef::Circle(circle) => circle.height(),
+                ShapeRef::BitMask(bitmask) => bitmask.height(),
                 ShapeRef::Mixed(mixed) => mixed.height(),
+                ShapeRef::Compound(compound) => compound.height(),
             }
         }
 
@@ -148,7 +170,9 @@ mod prelude {
                 ShapeRef::Point(point) => point.intersects_point(other),
                 ShapeRef::Rect(rect) => rect.intersects_point(other),
                 ShapeRef::Circle(circle) => circle.intersects_point(other),
+                ShapeRef::BitMask(bitmask) => bitmask.intersects_point(other),
                 ShapeRef::Mixed(mixed) => mixed.intersects_point(other),
+                ShapeRef::Compound(compound) => compound.intersects_point(other),
             }
         }
 
@@ -158,7 +182,9 @@ mod prelude {
                 ShapeRef::Point(point) => point.intersects_rect(other),
                 ShapeRef::Rect(rect) => rect.intersects_rect(other),
                 ShapeRef::Circle(circle) => circle.intersects_rect(other),
+                ShapeRef::BitMask(bitmask) => bitmask.intersects_rect(other),
                 ShapeRef::Mixed(mixed) => mixed.intersects_rect(other),
+                ShapeRef::Compound(compound) => compound.intersects_rect(other),
             }
         }
 
@@ -168,7 +194,21 @@ mod prelude {
                 ShapeRef::Point(point) => point.intersects_circle(other),
                 ShapeRef::Rect(rect) => rect.intersects_circle(other),
                 ShapeRef::Circle(circle) => circle.intersects_circle(other),
+                ShapeRef::BitMask(bitmask) => bitmask.intersects_circle(other),
                 ShapeRef::Mixed(mixed) => mixed.intersects_circle(other),
+                ShapeRef::Compound(compound) => compound.intersects_circle(other),
+            }
+        }
+
+        fn intersects_bitmask(&self, other: &BitMask) -> bool {
+            match self {
+                ShapeRef::None => false,
+                ShapeRef::Point(point) => point.intersects_bitmask(other),
+                ShapeRef::Rect(rect) => rect.intersects_bitmask(other),
+                ShapeRef::Circle(circle) => circle.intersects_bitmask(other),
+                ShapeRef::BitMask(bitmask) => bitmask.intersects_bitmask(other),
+                ShapeRef::Mixed(mixed) => mixed.intersects_bitmask(other),
+                ShapeRef::Compound(compound) => compound.intersects_bitmask(other),
             }
         }
 
@@ -178,7 +218,9 @@ mod prelude {
                 ShapeRef::Point(point) => point.intersects(other),
                 ShapeRef::Rect(rect) => rect.intersects(other),
                 ShapeRef::Circle(circle) => circle.intersects(other),
+                ShapeRef::BitMask(bitmask) => bitmask.intersects(other),
                 ShapeRef::Mixed(mixed) => mixed.intersects(other),
+                ShapeRef::Compound(compound) => compound.intersects(other),
             }
         }
     }
@@ -200,8 +242,8 @@ mod point {
         }
 
         pub fn distance(&self, other: &Point) -> f32 {
-            let dx = (self.x - other.x).abs();
-            let dy = (self.y - other.y).abs();
+            let dx = self.x - other.x;
+            let dy = self.y - other.y;
             (dx * dx + dy * dy).sqrt()
         }
 
@@ -242,6 +284,13 @@ mod point {
         }
     }
 
+    impl ApplyTransform for Point {
+        fn transform(&mut self, transform: &Transform2D) {
+            let Vec2 { x, y } = transform.apply(Vec2::new(self.x, self.y));
+            self.set_position(x, y);
+        }
+    }
+
     impl BBox for Point {
         fn left(&self) -> f32 {
             self.x
@@ -274,6 +323,10 @@ mod point {
             self.distance(&other.center()) <= other.radius
         }
 
+        fn intersects_bitmask(&self, other: &BitMask) -> bool {
+            other.intersects_point(self)
+        }
+
         fn intersects<B: BBox>(&self, other: &B) -> bool {
             other.intersects_point(self)
         }
@@ -321,6 +374,30 @@ mod rect {
         }
     }
 
+    impl ApplyTransform for Rect {
+        fn transform(&mut self, transform: &Transform2D) {
+            let corners = [
+                transform.apply(Vec2::new(self.left(), self.top())),
+                transform.apply(Vec2::new(self.right(), self.top())),
+                transform.apply(Vec2::new(self.left(), self.bottom())),
+                transform.apply(Vec2::new(self.right(), self.bottom())),
+            ];
+
+            let left = corners.iter().map(|c| c.x).fold(f32::INFINITY, f32::min);
+            let top = corners.iter().map(|c| c.y).fold(f32::INFINITY, f32::min);
+            let right = corners
+                .iter()
+                .map(|c| c.x)
+                .fold(f32::NEG_INFINITY, f32::max);
+            let bottom = corners
+                .iter()
+                .map(|c| c.y)
+                .fold(f32::NEG_INFINITY, f32::max);
+
+            *self = Rect::new(left, top, right - left, bottom - top);
+        }
+    }
+
     impl BBox for Rect {
         fn left(&self) -> f32 {
             self.x
@@ -369,6 +446,10 @@ mod rect {
             Point::new(px, py).intersects_circle(other)
         }
 
+        fn intersects_bitmask(&self, other: &BitMask) -> bool {
+            other.intersects_rect(self)
+        }
+
         fn intersects<B: BBox>(&self, other: &B) -> bool {
             other.intersects_rect(self)
         }
@@ -418,6 +499,14 @@ mod circle {
         }
     }
 
+    impl ApplyTransform for Circle {
+        fn transform(&mut self, transform: &Transform2D) {
+            let Vec2 { x, y } = transform.apply(Vec2::from(self.center()));
+            self.radius *= transform.determinant().abs().sqrt();
+            self.set_center(x, y);
+        }
+    }
+
     impl BBox for Circle {
         fn left(&self) -> f32 {
             self.x
@@ -444,12 +533,16 @@ mod circle {
         }
 
         fn intersects_circle(&self, other: &Circle) -> bool {
-            let dx = (self.center_x() - other.center_x()).abs();
-            let dy = (self.center_y() - other.center_y()).abs();
+            let dx = self.center_x() - other.center_x();
+            let dy = self.center_y() - other.center_y();
             let distance = (dx * dx + dy * dy).sqrt();
             distance <= self.radius + other.radius
         }
 
+        fn intersects_bitmask(&self, other: &BitMask) -> bool {
+            other.intersects_circle(self)
+        }
+
         fn intersects<B: BBox>(&self, other: &B) -> bool {
             other.intersects_circle(self)
         }
@@ -457,6 +550,263 @@ mod circle {
 }
 pub use circle::*;
 
+mod bitmask {
+    use super::*;
+
+    use crate::resources::Image;
+
+    #[derive(Debug, Clone)]
+    pub struct BitMask {
+        pub x: f32,
+        pub y: f32,
+        width: u32,
+        height: u32,
+        stride: usize,
+        bits: Vec<u64>,
+    }
+
+    impl BitMask {
+        pub fn new(x: f32, y: f32, width: u32, height: u32) -> Self {
+            let stride = (width as usize).div_ceil(64);
+
+            BitMask {
+                x,
+                y,
+                width,
+                height,
+                stride,
+                bits: vec![0; stride * height as usize],
+            }
+        }
+
+        pub fn from_alpha(width: u32, height: u32, pixels: &[u8], threshold: u8) -> Self {
+            let mut mask = BitMask::new(0.0, 0.0, width, height);
+
+            for (i, pixel) in pixels
+                .chunks_exact(4)
+                .take(width as usize * height as usize)
+                .enumerate()
+            {
+                if pixel[3] > threshold {
+                    mask.set(i as u32 % width, i as u32 / width, true);
+                }
+            }
+
+            mask
+        }
+
+        pub fn from_image(image: &Image, threshold: u8) -> Self {
+            let size = image.size();
+            BitMask::from_alpha(size.x, size.y, image.pixels(), threshold)
+        }
+
+        pub fn get(&self, x: u32, y: u32) -> bool {
+            if x >= self.width || y >= self.height {
+                return false;
+            }
+
+            let word = self.bits[y as usize * self.stride + x as usize / 64];
+            (word >> (x % 64)) & 1 == 1
+        }
+
+        pub fn set(&mut self, x: u32, y: u32, value: bool) {
+            if x >= self.width || y >= self.height {
+                return;
+            }
+
+            let word = &mut self.bits[y as usize * self.stride + x as usize / 64];
+            let bit = 1 << (x % 64);
+
+            if value {
+                *word |= bit;
+            } else {
+                *word &= !bit;
+            }
+        }
+
+        pub fn count(&self) -> u32 {
+            self.bits.iter().map(|word| word.count_ones()).sum()
+        }
+
+        pub fn bounds(&self) -> Rect {
+            Rect::new(self.x, self.y, self.width(), self.height())
+        }
+
+        pub fn as_ref(&self) -> ShapeRef {
+            ShapeRef::BitMask(self)
+        }
+
+        /// The 64 bits of `row` starting at column `start`, with columns outside the mask cleared.
+        fn row_bits(&self, row: i64, start: i64) -> u64 {
+            if row < 0 || row >= self.height as i64 || start <= -64 || start >= self.width as i64 {
+                return 0;
+            }
+
+            let words = &self.bits[row as usize * self.stride..][..self.stride];
+            let word = |index: i64| {
+                if index >= 0 && (index as usize) < self.stride {
+                    words[index as usize]
+                } else {
+                    0
+                }
+            };
+
+            let index = start.div_euclid(64);
+            let shift = start.rem_euclid(64) as u32;
+
+            if shift == 0 {
+                word(index)
+            } else {
+                (word(index) >> shift) | (word(index + 1) << (64 - shift))
+            }
+        }
+
+        fn any_in_row(&self, row: u32, from: u32, to: u32) -> bool {
+            let mut col = from;
+
+            while col < to {
+                let count = (to - col).min(64);
+                let mask = if count == 64 {
+                    u64::MAX
+                } else {
+                    (1 << count) - 1
+                };
+
+                if self.row_bits(row as i64, col as i64) & mask != 0 {
+                    return true;
+                }
+
+                col += count;
+            }
+
+            false
+        }
+    }
+
+    /// Converts the world range `from..to` into the range of pixel indices it covers.
+    fn span(offset: f32, size: u32, from: f32, to: f32) -> Option<(u32, u32)> {
+        let start = (from - offset).floor().max(0.0);
+        let end = (to - offset).ceil().max(start + 1.0).min(size as f32);
+
+        (start < end).then_some((start as u32, end as u32))
+    }
+
+    impl Movable for BitMask {
+        #[inline]
+        fn set_position(&mut self, x: f32, y: f32) {
+            self.x = x;
+            self.y = y;
+        }
+
+        #[inline]
+        fn move_by(&mut self, x: f32, y: f32) {
+            self.x += x;
+            self.y += y;
+        }
+    }
+
+    impl ApplyTransform for BitMask {
+        fn transform(&mut self, transform: &Transform2D) {
+            let Vec2 { x, y } = transform.apply(Vec2::from(self.center()));
+            self.set_center(x, y);
+        }
+    }
+
+    impl BBox for BitMask {
+        fn left(&self) -> f32 {
+            self.x
+        }
+
+        fn top(&self) -> f32 {
+            self.y
+        }
+
+        fn width(&self) -> f32 {
+            self.width as f32
+        }
+
+        fn height(&self) -> f32 {
+            self.height as f32
+        }
+
+        fn intersects_point(&self, other: &Point) -> bool {
+            let x = (other.x - self.x).floor();
+            let y = (other.y - self.y).floor();
+            x >= 0.0 && y >= 0.0 && self.get(x as u32, y as u32)
+        }
+
+        fn intersects_rect(&self, other: &Rect) -> bool {
+            if !self.bounds().intersects_rect(other) {
+                return false;
+            }
+
+            let columns = span(self.x, self.width, other.left(), other.right());
+            let rows = span(self.y, self.height, other.top(), other.bottom());
+
+            match (columns, rows) {
+                (Some((x0, x1)), Some((y0, y1))) => {
+                    (y0..y1).any(|row| self.any_in_row(row, x0, x1))
+                }
+                _ => false,
+            }
+        }
+
+        fn intersects_circle(&self, other: &Circle) -> bool {
+            if !self.bounds().intersects_circle(other) {
+                return false;
+            }
+
+            let (cx, cy, radius) = (other.center_x(), other.center_y(), other.radius);
+
+            let (y0, y1) = match span(self.y, self.height, other.top(), other.bottom()) {
+                Some(rows) => rows,
+                None => return false,
+            };
+
+            (y0..y1).any(|row| {
+                let top = self.y + row as f32;
+                let dy = (top - cy).max(cy - top - 1.0).max(0.0);
+
+                if dy > radius {
+                    return false;
+                }
+
+                let half = (radius * radius - dy * dy).sqrt();
+
+                match span(self.x, self.width, cx - half, cx + half) {
+                    Some((x0, x1)) => self.any_in_row(row, x0, x1),
+                    None => false,
+                }
+            })
+        }
+
+        fn intersects_bitmask(&self, other: &BitMask) -> bool {
+            if !self.bounds().intersects_rect(&other.bounds()) {
+                return false;
+            }
+
+            let dx = (other.x - self.x).round() as i64;
+            let dy = (other.y - self.y).round() as i64;
+
+            let x0 = dx.max(0);
+            let x1 = (dx + other.width as i64).min(self.width as i64);
+            let y0 = dy.max(0);
+            let y1 = (dy + other.height as i64).min(self.height as i64);
+
+            (y0..y1).any(|row| {
+                (x0..x1)
+                    .step_by(64)
+                    .any(|col| self.row_bits(row, col) & other.row_bits(row - dy, col - dx) != 0)
+            })
+        }
+
+        fn intersects<B: BBox>(&self, other: &B) -> bool {
+            other.intersects_bitmask(self)
+        }
+    }
+}
+pub use bitmask::*;
+
 mod mixed {
     use super::*;
 
@@ -464,6 +814,7 @@ mod mixed {
         Point(Point),
         Rect(Rect),
         Circle(Circle),
+        BitMask(BitMask),
     }
 
     impl Shape {
@@ -472,6 +823,7 @@ mod mixed {
                 Shape::Point(point) => ShapeRef::Point(point),
                 Shape::Rect(rect) => ShapeRef::Rect(rect),
                 Shape::Circle(circle) => ShapeRef::Circle(circle),
+                Shape::BitMask(bitmask) => ShapeRef::BitMask(bitmask),
             }
         }
     }
@@ -483,6 +835,7 @@ mod mixed {
                 Shape::Point(point) => point.set_position(x, y),
                 Shape::Rect(rect) => rect.set_position(x, y),
                 Shape::Circle(circle) => circle.set_position(x, y),
+                Shape::BitMask(bitmask) => bitmask.set_position(x, y),
             }
         }
 
@@ -492,6 +845,18 @@ mod mixed {
                 Shape::Point(point) => point.move_by(x, y),
                 Shape::Rect(rect) => rect.move_by(x, y),
                 Shape::Circle(circle) => circle.move_by(x, y),
+                Shape::BitMask(bitmask) => bitmask.move_by(x, y),
+            }
+        }
+    }
+
+    impl ApplyTransform for Shape {
+        fn transform(&mut self, transform: &Transform2D) {
+            match self {
+                Shape::Point(point) => point.transform(transform),
+                Shape::Rect(rect) => rect.transform(transform),
+                Shape::Circle(circle) => circle.transform(transform),
+                Shape::BitMask(bitmask) => bitmask.transform(transform),
             }
         }
     }
@@ -502,6 +867,7 @@ mod mixed {
                 Shape::Point(point) => point.left(),
                 Shape::Rect(rect) => rect.left(),
                 Shape::Circle(circle) => circle.left(),
+                Shape::BitMask(bitmask) => bitmask.left(),
             }
         }
 
@@ -510,6 +876,7 @@ mod mixed {
                 Shape::Point(point) => point.top(),
                 Shape::Rect(rect) => rect.top(),
                 Shape::Circle(circle) => circle.top(),
+                Shape::BitMask(bitmask) => bitmask.top(),
             }
         }
 
@@ -518,6 +885,7 @@ mod mixed {
                 Shape::Point(point) => point.width(),
                 Shape::Rect(rect) => rect.width(),
                 Shape::Circle(circle) => circle.width(),
+                Shape::BitMask(bitmask) => bitmask.width(),
             }
         }
 
@@ -526,6 +894,7 @@ mod mixed {
                 Shape::Point(point) => point.height(),
                 Shape::Rect(rect) => rect.height(),
                 Shape::Circle(circle) => circle.height(),
+                Shape::BitMask(bitmask) => bitmask.height(),
             }
         }
 
@@ -534,6 +903,7 @@ mod mixed {
                 Shape::Point(point) => point.intersects_point(other),
                 Shape::Rect(rect) => rect.intersects_point(other),
                 Shape::Circle(circle) => circle.intersects_point(other),
+                Shape::BitMask(bitmask) => bitmask.intersects_point(other),
             }
         }
 
@@ -542,6 +912,7 @@ mod mixed {
                 Shape::Point(point) => point.intersects_rect(other),
                 Shape::Rect(rect) => rect.intersects_rect(other),
                 Shape::Circle(circle) => circle.intersects_rect(other),
+                Shape::BitMask(bitmask) => bitmask.intersects_rect(other),
             }
         }
 
@@ -550,6 +921,16 @@ mod mixed {
                 Shape::Point(point) => point.intersects_circle(other),
                 Shape::Rect(rect) => rect.intersects_circle(other),
                 Shape::Circle(circle) => circle.intersects_circle(other),
+                Shape::BitMask(bitmask) => bitmask.intersects_circle(other),
+            }
+        }
+
+        fn intersects_bitmask(&self, other: &BitMask) -> bool {
+            match self {
+                Shape::Point(point) => point.intersects_bitmask(other),
+                Shape::Rect(rect) => rect.intersects_bitmask(other),
+                Shape::Circle(circle) => circle.intersects_bitmask(other),
+                Shape::BitMask(bitmask) => bitmask.intersects_bitmask(other),
             }
         }
 
@@ -558,6 +939,7 @@ mod mixed {
                 Shape::Point(point) => point.intersects(other),
                 Shape::Rect(rect) => rect.intersects(other),
                 Shape::Circle(circle) => circle.intersects(other),
+                Shape::BitMask(bitmask) => bitmask.intersects(other),
             }
         }
     }
@@ -623,59 +1005,265 @@ mod mixed {
                 .any(|shape| shape.intersects_circle(other))
         }
 
+        fn intersects_bitmask(&self, other: &BitMask) -> bool {
+            self.shapes
+                .iter()
+                .any(|shape| shape.intersects_bitmask(other))
+        }
+
         fn intersects<B: BBox>(&self, other: &B) -> bool {
             self.shapes.iter().any(|shape| shape.intersects(other))
         }
     }
+
+    pub struct Compound<S: ?Sized = [Shape]> {
+        origin: Point,
+        shapes: S,
+    }
+
+    impl<const N: usize> Compound<[Shape; N]> {
+        pub fn new(x: f32, y: f32, mut shapes: [Shape; N]) -> Self {
+            for shape in shapes.iter_mut() {
+                shape.move_by(x, y);
+            }
+
+            Compound {
+                origin: Point::new(x, y),
+                shapes,
+            }
+        }
+
+        pub fn as_ref(&self) -> ShapeRef {
+            ShapeRef::Compound(self)
+        }
+    }
+
+    impl<S: AsRef<[Shape]> + AsMut<[Shape]> + ?Sized> Compound<S> {
+        pub fn origin(&self) -> Point {
+            self.origin.clone()
+        }
+
+        pub fn set_origin(&mut self, x: f32, y: f32) {
+            let (dx, dy) = (x - self.origin.x, y - self.origin.y);
+            self.translate(dx, dy);
+        }
+
+        pub fn shapes(&self) -> &[Shape] {
+            self.shapes.as_ref()
+        }
+
+        pub fn offset(&self, index: usize) -> Option<Point> {
+            let shape = self.shapes().get(index)?;
+            Some(Point::new(
+                shape.left() - self.origin.x,
+                shape.top() - self.origin.y,
+            ))
+        }
+
+        pub fn set_offset(&mut self, index: usize, x: f32, y: f32) {
+            let Point { x: ox, y: oy } = self.origin;
+
+            if let Some(shape) = self.shapes.as_mut().get_mut(index) {
+                shape.set_position(ox + x, oy + y);
+            }
+        }
+
+        fn translate(&mut self, x: f32, y: f32) {
+            self.origin.move_by(x, y);
+
+            for shape in self.shapes.as_mut() {
+                shape.move_by(x, y);
+            }
+        }
+    }
+
+    impl<const N: usize> Movable for Compound<[Shape; N]> {
+        #[inline]
+        fn set_position(&mut self, x: f32, y: f32) {
+            let (dx, dy) = (x - self.left(), y - self.top());
+            self.translate(dx, dy);
+        }
+
+        #[inline]
+        fn move_by(&mut self, x: f32, y: f32) {
+            self.translate(x, y);
+        }
+    }
+
+    impl<const N: usize> ApplyTransform for Compound<[Shape; N]> {
+        fn transform(&mut self, transform: &Transform2D) {
+            self.origin.transform(transform);
+
+            for shape in self.shapes.iter_mut() {
+                shape.transform(transform);
+            }
+        }
+    }
+
+    impl BBox for &Compound {
+        fn left(&self) -> f32 {
+            self.shapes()
+                .iter()
+                .map(|shape| shape.left())
+                .min_by(|a, b| a.partial_cmp(b).unwrap())
+                .unwrap_or(self.origin.x)
+        }
+
+        fn top(&self) -> f32 {
+            self.shapes()
+                .iter()
+                .map(|shape| shape.top())
+                .min_by(|a, b| a.partial_cmp(b).unwrap())
+                .unwrap_or(self.origin.y)
+        }
+
+        fn right(&self) -> f32 {
+            self.shapes()
+                .iter()
+                .map(|shape| shape.right())
+                .max_by(|a, b| a.partial_cmp(b).unwrap())
+                .unwrap_or(self.origin.x)
+        }
+
+        fn bottom(&self) -> f32 {
+            self.shapes()
+                .iter()
+                .map(|shape| shape.bottom())
+                .max_by(|a, b| a.partial_cmp(b).unwrap())
+                .unwrap_or(self.origin.y)
+        }
+
+        fn width(&self) -> f32 {
+            self.right() - self.left()
+        }
+
+        fn height(&self) -> f32 {
+            self.bottom() - self.top()
+        }
+
+        fn intersects_point(&self, other: &Point) -> bool {
+            self.shapes()
+                .iter()
+                .any(|shape| shape.intersects_point(other))
+        }
+
+        fn intersects_rect(&self, other: &Rect) -> bool {
+            self.shapes()
+                .iter()
+                .any(|shape| shape.intersects_rect(other))
+        }
+
+        fn intersects_circle(&self, other: &Circle) -> bool {
+            self.shapes()
+                .iter()
+                .any(|shape| shape.intersects_circle(other))
+        }
+
+        fn intersects_bitmask(&self, other: &BitMask) -> bool {
+            self.shapes()
+                .iter()
+                .any(|shape| shape.intersects_bitmask(other))
+        }
+
+        fn intersects<B: BBox>(&self, other: &B) -> bool {
+            self.shapes().iter().any(|shape| shape.intersects(other))
+        }
+    }
+
+    impl<const N: usize> BBox for Compound<[Shape; N]> {
+        fn left(&self) -> f32 {
+            (self as &Compound).left()
+        }
+
+        fn top(&self) -> f32 {
+            (self as &Compound).top()
+        }
+
+        fn right(&self) -> f32 {
+            (self as &Compound).right()
+        }
+
+        fn bottom(&self) -> f32 {
+            (self as &Compound).bottom()
+        }
+
+        fn width(&self) -> f32 {
+            (self as &Compound).width()
+        }
+
+        fn height(&self) -> f32 {
+            (self as &Compound).height()
+        }
+
+        fn intersects_point(&self, other: &Point) -> bool {
+            (self as &Compound).intersects_point(other)
+        }
+
+        fn intersects_rect(&self, other: &Rect) -> bool {
+            (self as &Compound).intersects_rect(other)
+        }
+
+        fn intersects_circle(&self, other: &Circle) -> bool {
+            (self as &Compound).intersects_circle(other)
+        }
+
+        fn intersects_bitmask(&self, other: &BitMask) -> bool {
+            (self as &Compound).intersects_bitmask(other)
+        }
+
+        fn intersects<B: BBox>(&self, other: &B) -> bool {
+            (self as &Compound).intersects(other)
+        }
+    }
 }
 pub use mixed::*;
 
 mod draw {
     use super::*;
 
-    use sfml::graphics::{CircleShape, Color, RectangleShape, Shape as SfmlShape, Transformable};
+    use crate::{
+        graphics::{Color, ShapeStyle},
+        window::Canvas,
+    };
 
-    use crate::window::Canvas;
+    pub const DEBUG_COLOR: Color = Color::MAGENTA;
 
     pub trait DrawBBox {
-        fn draw(&self, target: &mut Canvas);
+        #[inline]
+        fn draw(&self, target: &mut Canvas) {
+            self.draw_colored(target, DEBUG_COLOR);
+        }
+
+        fn draw_colored(&self, target: &mut Canvas, color: Color);
     }
 
     impl DrawBBox for ShapeRef<'_> {
-        fn draw(&self, target: &mut Canvas) {
+        fn draw_colored(&self, target: &mut Canvas, color: Color) {
+            let style = ShapeStyle::outlined(color, 1.0);
+
             match self {
                 ShapeRef::Rect(rect) => {
-                    let mut rect_data = RectangleShape::new();
-                    rect_data.set_position((rect.left(), rect.top()));
-                    rect_data.set_size((rect.width(), rect.height()));
-                    rect_data.set_outline_thickness(1.0);
-                    rect_data.set_outline_color(Color::MAGENTA);
-                    rect_data.set_fill_color(Color::TRANSPARENT);
-                    target.draw(&rect_data);
+                    target.draw_rect(
+                        (rect.left(), rect.top()),
+                        (rect.width(), rect.height()),
+                        style,
+                    );
                 }
                 ShapeRef::Circle(circle) => {
-                    let mut circle_data = CircleShape::new(circle.radius(), 24);
-                    circle_data.set_position(circle.center());
-                    circle_data.set_outline_thickness(1.0);
-                    circle_data.set_outline_color(Color::MAGENTA);
-                    circle_data.set_fill_color(Color::TRANSPARENT);
-                    target.draw(&circle_data);
+                    target.draw_circle(circle.center(), circle.radius(), style);
                 }
                 ShapeRef::Point(point) => {
-                    let mut circle_data = CircleShape::new(1.0, 1);
-                    circle_data.set_position(point.position());
-                    circle_data.set_outline_thickness(1.0);
-                    circle_data.set_outline_color(Color::MAGENTA);
-                    circle_data.set_fill_color(Color::TRANSPARENT);
-                    target.draw(&circle_data);
+                    target.draw_circle(point.position(), 1.0, style);
                 }
+                ShapeRef::BitMask(bitmask) => bitmask.bounds().as_ref().draw_colored(target, color),
                 ShapeRef::Mixed(mixed) => {
                     for shape in mixed.shapes {
-                        match shape {
-                            Shape::Rect(rect) => rect.as_ref().draw(target),
-                            Shape::Circle(circle) => circle.as_ref().draw(target),
-                            Shape::Point(point) => point.as_ref().draw(target),
-                        }
+                        shape.as_ref().draw_colored(target, color);
+                    }
+                }
+                ShapeRef::Compound(compound) => {
+                    for shape in compound.shapes() {
+                        shape.as_ref().draw_colored(target, color);
                     }
                 }
                 ShapeRef::None => {}
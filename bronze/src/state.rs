@@ -0,0 +1,311 @@
+use std::time::Duration;
+
+use crate::{
+    engine::Engine,
+    game::Game,
+    input::InputManager,
+    transition::{Transition, TransitionPlayer},
+    window::Canvas,
+};
+
+pub enum StateChange<Ctx> {
+    None,
+    Push(Box<dyn State<Ctx>>),
+    Pop,
+    Replace(Box<dyn State<Ctx>>),
+    Quit,
+    WithTransition(Box<StateChange<Ctx>>, Transition),
+}
+
+impl<Ctx> StateChange<Ctx> {
+    pub fn with(self, transition: Transition) -> Self {
+        StateChange::WithTransition(Box::new(self), transition)
+    }
+}
+
+pub trait State<Ctx> {
+    #[inline]
+    fn is_overlay(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn on_enter(&mut self, ctx: &mut Ctx) {
+        let _ = ctx;
+    }
+
+    #[inline]
+    fn on_exit(&mut self, ctx: &mut Ctx) {
+        let _ = ctx;
+    }
+
+    #[inline]
+    fn on_pause(&mut self, ctx: &mut Ctx) {
+        let _ = ctx;
+    }
+
+    #[inline]
+    fn on_resume(&mut self, ctx: &mut Ctx) {
+        let _ = ctx;
+    }
+
+    #[inline]
+    fn input(&mut self, input: &InputManager) {
+        let _ = input;
+    }
+
+    #[inline]
+    fn pre_update(&mut self, ctx: &Ctx) {
+        let _ = ctx;
+    }
+
+    fn update(&mut self, ctx: &mut Ctx, frame_time: Duration) {
+        let _ = (ctx, frame_time);
+    }
+
+    #[inline]
+    fn post_update(&mut self, ctx: &Ctx) {
+        let _ = ctx;
+    }
+
+    #[inline]
+    fn draw(&self, ctx: &Ctx, target: &mut Canvas) {
+        let _ = (ctx, target);
+    }
+
+    fn next(&mut self, ctx: &mut Ctx) -> StateChange<Ctx> {
+        let _ = ctx;
+        StateChange::None
+    }
+}
+
+pub struct StateStack<Ctx> {
+    states: Vec<Box<dyn State<Ctx>>>,
+    ctx: Ctx,
+    running: bool,
+    transition: Option<TransitionPlayer>,
+    pending: Option<StateChange<Ctx>>,
+    frame_end: Option<fn(&mut Ctx)>,
+}
+
+impl<Ctx> StateStack<Ctx> {
+    pub fn new(ctx: Ctx, initial: Box<dyn State<Ctx>>) -> Self {
+        let mut stack = StateStack {
+            states: Vec::new(),
+            ctx,
+            running: true,
+            transition: None,
+            pending: None,
+            frame_end: None,
+        };
+        stack.push(initial);
+        stack
+    }
+
+    // Runs `frame_end` on the context once per frame, after the states have updated, e.g. to
+    // call `ResourcePool::end_frame`.
+    #[inline]
+    pub fn with_frame_end(mut self, frame_end: fn(&mut Ctx)) -> Self {
+        self.frame_end = Some(frame_end);
+        self
+    }
+
+    #[inline]
+    pub fn ctx(&self) -> &Ctx {
+        &self.ctx
+    }
+
+    #[inline]
+    pub fn ctx_mut(&mut self) -> &mut Ctx {
+        &mut self.ctx
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.states.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
+
+    #[inline]
+    pub fn is_transitioning(&self) -> bool {
+        self.transition.is_some()
+    }
+
+    #[inline]
+    pub fn quit(&mut self) {
+        self.running = false;
+    }
+
+    pub fn push(&mut self, mut state: Box<dyn State<Ctx>>) {
+        if let Some(top) = self.states.last_mut() {
+            top.on_pause(&mut self.ctx);
+        }
+
+        state.on_enter(&mut self.ctx);
+        self.states.push(state);
+    }
+
+    pub fn pop(&mut self) -> Option<Box<dyn State<Ctx>>> {
+        let mut state = self.states.pop()?;
+        state.on_exit(&mut self.ctx);
+
+        if let Some(top) = self.states.last_mut() {
+            top.on_resume(&mut self.ctx);
+        }
+
+        Some(state)
+    }
+
+    // Hands back the previous state after its `on_exit`. `StateChange::Replace` drops it
+    // before `state` enters instead, so whatever only it was holding, such as pool resources,
+    // is free by the time `on_enter` runs.
+    pub fn replace(&mut self, state: Box<dyn State<Ctx>>) -> Option<Box<dyn State<Ctx>>> {
+        let previous = self.exit_top();
+        self.enter(state);
+        previous
+    }
+
+    fn exit_top(&mut self) -> Option<Box<dyn State<Ctx>>> {
+        let mut state = self.states.pop()?;
+        state.on_exit(&mut self.ctx);
+        Some(state)
+    }
+
+    fn enter(&mut self, mut state: Box<dyn State<Ctx>>) {
+        state.on_enter(&mut self.ctx);
+        self.states.push(state);
+    }
+
+    pub fn apply(&mut self, change: StateChange<Ctx>) {
+        match change {
+            StateChange::None => {}
+            StateChange::Push(state) => self.push(state),
+            StateChange::Pop => {
+                self.pop();
+            }
+            StateChange::Replace(state) => {
+                drop(self.exit_top());
+                self.enter(state);
+            }
+            StateChange::Quit => self.quit(),
+            StateChange::WithTransition(change, transition) => self.transition(*change, transition),
+        }
+    }
+
+    pub fn transition(&mut self, change: StateChange<Ctx>, transition: Transition) {
+        if let Some(pending) = self.pending.take() {
+            self.apply(pending);
+        }
+
+        self.pending = Some(change);
+        self.transition = Some(TransitionPlayer::new(transition));
+    }
+
+    fn advance_transition(&mut self, frame_time: Duration) {
+        let switch = match &mut self.transition {
+            Some(transition) => transition.advance(frame_time),
+            None => return,
+        };
+
+        if switch {
+            if let Some(pending) = self.pending.take() {
+                self.apply(pending);
+            }
+        }
+
+        if self.transition.as_ref().is_some_and(|t| t.is_finished()) {
+            self.transition = None;
+        }
+    }
+
+    pub fn input(&mut self, input: &InputManager) {
+        if self.transition.is_some() {
+            return;
+        }
+
+        if let Some(top) = self.states.last_mut() {
+            top.input(input);
+        }
+    }
+
+    pub fn pre_update(&mut self) {
+        if let Some(top) = self.states.last_mut() {
+            top.pre_update(&self.ctx);
+        }
+    }
+
+    pub fn update(&mut self, frame_time: Duration) {
+        self.advance_transition(frame_time);
+
+        if let Some(top) = self.states.last_mut() {
+            top.update(&mut self.ctx, frame_time);
+        }
+    }
+
+    pub fn post_update(&mut self) {
+        if let Some(top) = self.states.last_mut() {
+            top.post_update(&self.ctx);
+
+            if self.transition.is_none() {
+                let change = top.next(&mut self.ctx);
+                self.apply(change);
+            }
+        }
+
+        if let Some(frame_end) = self.frame_end {
+            frame_end(&mut self.ctx);
+        }
+    }
+
+    pub fn draw(&self, target: &mut Canvas) {
+        let first = self
+            .states
+            .iter()
+            .rposition(|state| !state.is_overlay())
+            .unwrap_or(0);
+
+        for state in self.states.iter().skip(first) {
+            state.draw(&self.ctx, target);
+        }
+
+        if let Some(transition) = &self.transition {
+            transition.draw(target);
+        }
+    }
+}
+
+impl<Ctx> Game for StateStack<Ctx> {
+    #[inline]
+    fn is_running(&self) -> bool {
+        self.running && !self.states.is_empty()
+    }
+
+    #[inline]
+    fn input(&mut self, input: &InputManager) {
+        StateStack::input(self, input);
+    }
+
+    #[inline]
+    fn pre_update(&mut self, _engine: &Engine) {
+        StateStack::pre_update(self);
+    }
+
+    #[inline]
+    fn update(&mut self, _engine: &mut Engine, frame_time: Duration) {
+        StateStack::update(self, frame_time);
+    }
+
+    #[inline]
+    fn post_update(&mut self, _engine: &Engine) {
+        StateStack::post_update(self);
+    }
+
+    #[inline]
+    fn draw(&self, target: &mut Canvas) {
+        StateStack::draw(self, target);
+    }
+}
@@ -0,0 +1,976 @@
+use std::{
+    collections::HashMap,
+    io::Read,
+    path::{Path, PathBuf},
+    rc::Rc,
+    time::Duration,
+};
+
+use flate2::read::{GzDecoder, ZlibDecoder};
+use roxmltree::{Document, Node};
+use serde_json::Value;
+
+use crate::{
+    graphics::Color,
+    math::Vec2,
+    resources::{Image, ResourceError},
+    scene::{Collision, Entity, Scene, Spawn},
+    shape::Rect,
+    tilemap::{Flip, TileId, TileMap, Tileset},
+    vfs::Vfs,
+};
+
+const FLIPPED_HORIZONTALLY: u32 = 0x8000_0000;
+const FLIPPED_VERTICALLY: u32 = 0x4000_0000;
+const FLIPPED_DIAGONALLY: u32 = 0x2000_0000;
+const ROTATED_HEXAGONAL: u32 = 0x1000_0000;
+const GID_MASK: u32 =
+    !(FLIPPED_HORIZONTALLY | FLIPPED_VERTICALLY | FLIPPED_DIAGONALLY | ROTATED_HEXAGONAL);
+
+// Tiled ids start at 1 with 0 meaning an empty cell, tile map ids start at 0.
+fn decode_gid(gid: u32) -> Option<(TileId, Flip)> {
+    let id = (gid & GID_MASK).checked_sub(1)?;
+
+    let flip = Flip {
+        horizontal: gid & FLIPPED_HORIZONTALLY != 0,
+        vertical: gid & FLIPPED_VERTICALLY != 0,
+        diagonal: gid & FLIPPED_DIAGONALLY != 0,
+    };
+
+    Some((id, flip))
+}
+
+mod object {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum Property {
+        Bool(bool),
+        Int(i64),
+        Float(f64),
+        String(String),
+        Color(Color),
+        File(String),
+        Object(u32),
+    }
+
+    impl Property {
+        #[inline]
+        pub fn as_bool(&self) -> Option<bool> {
+            match self {
+                Property::Bool(value) => Some(*value),
+                _ => None,
+            }
+        }
+
+        #[inline]
+        pub fn as_int(&self) -> Option<i64> {
+            match self {
+                Property::Int(value) => Some(*value),
+                _ => None,
+            }
+        }
+
+        #[inline]
+        pub fn as_float(&self) -> Option<f64> {
+            match self {
+                Property::Float(value) => Some(*value),
+                Property::Int(value) => Some(*value as f64),
+                _ => None,
+            }
+        }
+
+        #[inline]
+        pub fn as_str(&self) -> Option<&str> {
+            match self {
+                Property::String(value) | Property::File(value) => Some(value),
+                _ => None,
+            }
+        }
+
+        #[inline]
+        pub fn as_color(&self) -> Option<Color> {
+            match self {
+                Property::Color(value) => Some(*value),
+                _ => None,
+            }
+        }
+    }
+
+    pub type Properties = HashMap<String, Property>;
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum ObjectShape {
+        Rect,
+        Ellipse,
+        Point,
+        // Points are relative to the object position.
+        Polygon(Vec<Vec2>),
+        Polyline(Vec<Vec2>),
+        Text(String),
+    }
+
+    #[derive(Debug, Clone)]
+    pub struct MapObject {
+        pub id: u32,
+        pub name: String,
+        pub class: String,
+        // Top left corner, also for tile objects which Tiled anchors at the bottom left.
+        pub position: Vec2,
+        pub size: Vec2,
+        pub rotation: f32,
+        pub visible: bool,
+        pub tile: Option<(TileId, Flip)>,
+        pub shape: ObjectShape,
+        pub properties: Properties,
+    }
+
+    impl MapObject {
+        #[inline]
+        pub fn property(&self, name: &str) -> Option<&Property> {
+            self.properties.get(name)
+        }
+
+        #[inline]
+        pub fn bounds(&self) -> Rect {
+            Rect::new(self.position.x, self.position.y, self.size.x, self.size.y)
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    pub struct ObjectLayer {
+        pub name: String,
+        pub visible: bool,
+        pub objects: Vec<MapObject>,
+        pub properties: Properties,
+    }
+
+    impl ObjectLayer {
+        #[inline]
+        pub fn property(&self, name: &str) -> Option<&Property> {
+            self.properties.get(name)
+        }
+    }
+
+    pub(super) fn parse_property(kind: &str, value: &str) -> Option<Property> {
+        Some(match kind {
+            "bool" => Property::Bool(value.parse().ok()?),
+            "int" => Property::Int(value.parse().ok()?),
+            "float" => Property::Float(value.parse().ok()?),
+            "color" => Property::Color(parse_color(value)?),
+            "file" => Property::File(value.to_string()),
+            "object" => Property::Object(value.parse().ok()?),
+            _ => Property::String(value.to_string()),
+        })
+    }
+
+    // Tiled writes colors as `#AARRGGBB` or `#RRGGBB`.
+    pub(super) fn parse_color(value: &str) -> Option<Color> {
+        let hex = value.trim_start_matches('#');
+        let value = u32::from_str_radix(hex, 16).ok()?;
+
+        let [a, r, g, b] = match hex.len() {
+            6 => (0xFF00_0000 | value).to_be_bytes(),
+            8 => value.to_be_bytes(),
+            _ => return None,
+        };
+
+        Some(Color::rgba(r, g, b, a))
+    }
+}
+pub use object::*;
+
+struct RawTile {
+    id: TileId,
+    animation: Vec<(TileId, u64)>,
+    properties: Properties,
+}
+
+struct RawTileset {
+    first_gid: u32,
+    image: PathBuf,
+    tile_width: u32,
+    tile_height: u32,
+    margin: u32,
+    spacing: u32,
+    tiles: Vec<RawTile>,
+}
+
+enum RawLayer {
+    Tiles {
+        name: String,
+        visible: bool,
+        gids: Vec<u32>,
+        properties: Properties,
+    },
+    Objects(ObjectLayer),
+}
+
+struct RawMap {
+    width: u32,
+    height: u32,
+    tile_width: u32,
+    tile_height: u32,
+    tilesets: Vec<RawTileset>,
+    layers: Vec<RawLayer>,
+    properties: Properties,
+}
+
+fn decode_tile_data(
+    data: &str,
+    encoding: Option<&str>,
+    compression: Option<&str>,
+) -> Option<Vec<u32>> {
+    match encoding {
+        Some("csv") => data
+            .split(',')
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(|value| value.parse().ok())
+            .collect(),
+        Some("base64") => {
+            let bytes = decode_base64(data)?;
+
+            let bytes = match compression {
+                None | Some("") => bytes,
+                Some("zlib") => inflate(ZlibDecoder::new(bytes.as_slice()))?,
+                Some("gzip") => inflate(GzDecoder::new(bytes.as_slice()))?,
+                _ => return None,
+            };
+
+            Some(
+                bytes
+                    .chunks_exact(4)
+                    .map(|gid| u32::from_le_bytes([gid[0], gid[1], gid[2], gid[3]]))
+                    .collect(),
+            )
+        }
+        _ => None,
+    }
+}
+
+fn inflate<R: Read>(mut decoder: R) -> Option<Vec<u8>> {
+    let mut bytes = Vec::new();
+    decoder.read_to_end(&mut bytes).ok()?;
+    Some(bytes)
+}
+
+fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(text.len() * 3 / 4);
+    let (mut buffer, mut bits) = (0u32, 0);
+
+    for c in text.bytes().filter(|c| !c.is_ascii_whitespace()) {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            b'=' => break,
+            _ => return None,
+        };
+
+        buffer = (buffer << 6) | value as u32;
+        bits += 6;
+
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+
+    Some(bytes)
+}
+
+fn invalid(path: &Path, reason: impl Into<String>) -> ResourceError {
+    ResourceError::invalid(&path.to_string_lossy(), reason)
+}
+
+fn is_json(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|extension| extension.to_str()),
+        Some("json" | "tmj" | "tsj")
+    )
+}
+
+mod json {
+    use super::*;
+
+    fn uint(value: &Value, key: &str) -> Option<u32> {
+        value[key].as_u64().map(|value| value as u32)
+    }
+
+    fn float(value: &Value, key: &str) -> f32 {
+        value[key].as_f64().unwrap_or(0.0) as f32
+    }
+
+    fn string(value: &Value, key: &str) -> String {
+        value[key].as_str().unwrap_or_default().to_string()
+    }
+
+    fn required(value: &Value, key: &str, path: &Path) -> Result<u32, ResourceError> {
+        uint(value, key).ok_or_else(|| invalid(path, format!("missing `{}`", key)))
+    }
+
+    fn read(vfs: &Vfs, path: &Path) -> Result<Value, ResourceError> {
+        serde_json::from_str(&vfs.read_to_string(path)?)
+            .map_err(|error| invalid(path, error.to_string()))
+    }
+
+    pub(super) fn map(vfs: &Vfs, path: &Path) -> Result<RawMap, ResourceError> {
+        let value = read(vfs, path)?;
+        let directory = path.parent().unwrap_or(Path::new(""));
+
+        // Infinite maps store their layers in chunks, which `layers` can't read.
+        if value["infinite"].as_bool() == Some(true) {
+            return Err(invalid(path, "infinite maps are not supported"));
+        }
+
+        let mut layers = Vec::new();
+        self::layers(&value["layers"], &mut layers)
+            .ok_or_else(|| invalid(path, "malformed layer data"))?;
+
+        Ok(RawMap {
+            width: required(&value, "width", path)?,
+            height: required(&value, "height", path)?,
+            tile_width: required(&value, "tilewidth", path)?,
+            tile_height: required(&value, "tileheight", path)?,
+            tilesets: value["tilesets"]
+                .as_array()
+                .ok_or_else(|| invalid(path, "missing `tilesets`"))?
+                .iter()
+                .map(|tileset| self::tileset(vfs, tileset, path, directory))
+                .collect::<Result<_, _>>()?,
+            layers,
+            properties: properties(&value["properties"]),
+        })
+    }
+
+    fn tileset(
+        vfs: &Vfs,
+        value: &Value,
+        path: &Path,
+        directory: &Path,
+    ) -> Result<RawTileset, ResourceError> {
+        let first_gid = required(value, "firstgid", path)?;
+
+        match value["source"].as_str() {
+            Some(source) => external_tileset(vfs, &directory.join(source), first_gid),
+            None => tileset_body(value, path, directory, first_gid),
+        }
+    }
+
+    pub(super) fn tileset_file(
+        vfs: &Vfs,
+        path: &Path,
+        first_gid: u32,
+    ) -> Result<RawTileset, ResourceError> {
+        let directory = path.parent().unwrap_or(Path::new(""));
+        tileset_body(&read(vfs, path)?, path, directory, first_gid)
+    }
+
+    fn tileset_body(
+        value: &Value,
+        path: &Path,
+        directory: &Path,
+        first_gid: u32,
+    ) -> Result<RawTileset, ResourceError> {
+        let tiles = value["tiles"].as_array().map_or_else(Vec::new, |tiles| {
+            tiles
+                .iter()
+                .filter_map(|tile| {
+                    Some(RawTile {
+                        id: uint(tile, "id")?,
+                        animation: tile["animation"]
+                            .as_array()
+                            .map_or_else(Vec::new, |frames| {
+                                frames
+                                    .iter()
+                                    .filter_map(|frame| {
+                                        Some((uint(frame, "tileid")?, frame["duration"].as_u64()?))
+                                    })
+                                    .collect()
+                            }),
+                        properties: properties(&tile["properties"]),
+                    })
+                })
+                .collect()
+        });
+
+        let image = value["image"]
+            .as_str()
+            .ok_or_else(|| invalid(path, "image collection tilesets are not supported"))?;
+
+        Ok(RawTileset {
+            first_gid,
+            image: directory.join(image),
+            tile_width: required(value, "tilewidth", path)?,
+            tile_height: required(value, "tileheight", path)?,
+            margin: uint(value, "margin").unwrap_or(0),
+            spacing: uint(value, "spacing").unwrap_or(0),
+            tiles,
+        })
+    }
+
+    fn layers(value: &Value, layers: &mut Vec<RawLayer>) -> Option<()> {
+        for layer in value.as_array()? {
+            let name = string(layer, "name");
+            let visible = layer["visible"].as_bool().unwrap_or(true);
+
+            match layer["type"].as_str()? {
+                "tilelayer" => {
+                    let data = &layer["data"];
+
+                    let gids = match data.as_str() {
+                        Some(text) => decode_tile_data(
+                            text,
+                            layer["encoding"].as_str(),
+                            layer["compression"].as_str(),
+                        )?,
+                        None => data
+                            .as_array()?
+                            .iter()
+                            .map(|gid| gid.as_u64().map(|gid| gid as u32))
+                            .collect::<Option<_>>()?,
+                    };
+
+                    layers.push(RawLayer::Tiles {
+                        name,
+                        visible,
+                        gids,
+                        properties: properties(&layer["properties"]),
+                    });
+                }
+                "objectgroup" => layers.push(RawLayer::Objects(ObjectLayer {
+                    name,
+                    visible,
+                    objects: layer["objects"]
+                        .as_array()?
+                        .iter()
+                        .filter_map(object)
+                        .collect(),
+                    properties: properties(&layer["properties"]),
+                })),
+                "group" => self::layers(&layer["layers"], layers)?,
+                _ => {}
+            }
+        }
+
+        Some(())
+    }
+
+    fn points(value: &Value) -> Vec<Vec2> {
+        value.as_array().map_or_else(Vec::new, |points| {
+            points
+                .iter()
+                .map(|point| Vec2::new(float(point, "x"), float(point, "y")))
+                .collect()
+        })
+    }
+
+    fn object(value: &Value) -> Option<MapObject> {
+        let tile = uint(value, "gid").and_then(decode_gid);
+        let size = Vec2::new(float(value, "width"), float(value, "height"));
+
+        let mut position = Vec2::new(float(value, "x"), float(value, "y"));
+        if tile.is_some() {
+            position.y -= size.y;
+        }
+
+        let shape = if value["ellipse"].as_bool() == Some(true) {
+            ObjectShape::Ellipse
+        } else if value["point"].as_bool() == Some(true) {
+            ObjectShape::Point
+        } else if value["polygon"].is_array() {
+            ObjectShape::Polygon(points(&value["polygon"]))
+        } else if value["polyline"].is_array() {
+            ObjectShape::Polyline(points(&value["polyline"]))
+        } else if value["text"].is_object() {
+            ObjectShape::Text(string(&value["text"], "text"))
+        } else {
+            ObjectShape::Rect
+        };
+
+        let class = match value["class"].as_str() {
+            Some(class) => class.to_string(),
+            None => string(value, "type"),
+        };
+
+        Some(MapObject {
+            id: uint(value, "id")?,
+            name: string(value, "name"),
+            class,
+            position,
+            size,
+            rotation: float(value, "rotation"),
+            visible: value["visible"].as_bool().unwrap_or(true),
+            tile,
+            shape,
+            properties: properties(&value["properties"]),
+        })
+    }
+
+    fn properties(value: &Value) -> Properties {
+        let properties = match value.as_array() {
+            Some(properties) => properties,
+            None => return Properties::new(),
+        };
+
+        properties
+            .iter()
+            .filter_map(|property| {
+                let kind = property["type"].as_str().unwrap_or("string");
+                let value = match &property["value"] {
+                    Value::String(value) => value.clone(),
+                    value => value.to_string(),
+                };
+
+                Some((
+                    property["name"].as_str()?.to_string(),
+                    parse_property(kind, &value)?,
+                ))
+            })
+            .collect()
+    }
+}
+
+mod xml {
+    use super::*;
+
+    fn attribute<T: std::str::FromStr>(node: Node, name: &str) -> Option<T> {
+        node.attribute(name)?.parse().ok()
+    }
+
+    fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+        node.children().find(|child| child.has_tag_name(name))
+    }
+
+    fn string(node: Node, name: &str) -> String {
+        node.attribute(name).unwrap_or_default().to_string()
+    }
+
+    fn required(node: Node, name: &str, path: &Path) -> Result<u32, ResourceError> {
+        attribute(node, name).ok_or_else(|| invalid(path, format!("missing `{}`", name)))
+    }
+
+    pub(super) fn map(vfs: &Vfs, path: &Path) -> Result<RawMap, ResourceError> {
+        let text = vfs.read_to_string(path)?;
+        let document = Document::parse(&text).map_err(|error| invalid(path, error.to_string()))?;
+        let root = document.root_element();
+        let directory = path.parent().unwrap_or(Path::new(""));
+
+        if root.attribute("infinite") == Some("1") {
+            return Err(invalid(path, "infinite maps are not supported"));
+        }
+
+        let mut layers = Vec::new();
+        self::layers(root, &mut layers).ok_or_else(|| invalid(path, "malformed layer data"))?;
+
+        Ok(RawMap {
+            width: required(root, "width", path)?,
+            height: required(root, "height", path)?,
+            tile_width: required(root, "tilewidth", path)?,
+            tile_height: required(root, "tileheight", path)?,
+            tilesets: root
+                .children()
+                .filter(|node| node.has_tag_name("tileset"))
+                .map(|node| tileset(vfs, node, path, directory))
+                .collect::<Result<_, _>>()?,
+            layers,
+            properties: properties(root),
+        })
+    }
+
+    fn tileset(
+        vfs: &Vfs,
+        node: Node,
+        path: &Path,
+        directory: &Path,
+    ) -> Result<RawTileset, ResourceError> {
+        let first_gid = required(node, "firstgid", path)?;
+
+        match node.attribute("source") {
+            Some(source) => external_tileset(vfs, &directory.join(source), first_gid),
+            None => tileset_body(node, path, directory, first_gid),
+        }
+    }
+
+    pub(super) fn tileset_file(
+        vfs: &Vfs,
+        path: &Path,
+        first_gid: u32,
+    ) -> Result<RawTileset, ResourceError> {
+        let text = vfs.read_to_string(path)?;
+        let document = Document::parse(&text).map_err(|error| invalid(path, error.to_string()))?;
+        let directory = path.parent().unwrap_or(Path::new(""));
+        tileset_body(document.root_element(), path, directory, first_gid)
+    }
+
+    fn tileset_body(
+        node: Node,
+        path: &Path,
+        directory: &Path,
+        first_gid: u32,
+    ) -> Result<RawTileset, ResourceError> {
+        let tiles = node
+            .children()
+            .filter(|child| child.has_tag_name("tile"))
+            .filter_map(|tile| {
+                let animation = child(tile, "animation").map_or_else(Vec::new, |animation| {
+                    animation
+                        .children()
+                        .filter(|frame| frame.has_tag_name("frame"))
+                        .filter_map(|frame| {
+                            Some((attribute(frame, "tileid")?, attribute(frame, "duration")?))
+                        })
+                        .collect()
+                });
+
+                Some(RawTile {
+                    id: attribute(tile, "id")?,
+                    animation,
+                    properties: properties(tile),
+                })
+            })
+            .collect();
+
+        let image = child(node, "image")
+            .and_then(|image| image.attribute("source"))
+            .ok_or_else(|| invalid(path, "image collection tilesets are not supported"))?;
+
+        Ok(RawTileset {
+            first_gid,
+            image: directory.join(image),
+            tile_width: required(node, "tilewidth", path)?,
+            tile_height: required(node, "tileheight", path)?,
+            margin: attribute(node, "margin").unwrap_or(0),
+            spacing: attribute(node, "spacing").unwrap_or(0),
+            tiles,
+        })
+    }
+
+    fn layers(node: Node, layers: &mut Vec<RawLayer>) -> Option<()> {
+        for layer in node.children().filter(Node::is_element) {
+            let name = string(layer, "name");
+            let visible = layer.attribute("visible") != Some("0");
+
+            match layer.tag_name().name() {
+                "layer" => {
+                    let data = child(layer, "data")?;
+
+                    let gids = match data.attribute("encoding") {
+                        Some(encoding) => decode_tile_data(
+                            data.text().unwrap_or_default(),
+                            Some(encoding),
+                            data.attribute("compression"),
+                        )?,
+                        None => data
+                            .children()
+                            .filter(|tile| tile.has_tag_name("tile"))
+                            .map(|tile| attribute(tile, "gid").unwrap_or(0))
+                            .collect(),
+                    };
+
+                    layers.push(RawLayer::Tiles {
+                        name,
+                        visible,
+                        gids,
+                        properties: properties(layer),
+                    });
+                }
+                "objectgroup" => layers.push(RawLayer::Objects(ObjectLayer {
+                    name,
+                    visible,
+                    objects: layer
+                        .children()
+                        .filter(|object| object.has_tag_name("object"))
+                        .filter_map(object)
+                        .collect(),
+                    properties: properties(layer),
+                })),
+                "group" => self::layers(layer, layers)?,
+                _ => {}
+            }
+        }
+
+        Some(())
+    }
+
+    fn points(node: Node) -> Vec<Vec2> {
+        node.attribute("points")
+            .unwrap_or_default()
+            .split_whitespace()
+            .filter_map(|point| {
+                let (x, y) = point.split_once(',')?;
+                Some(Vec2::new(x.parse().ok()?, y.parse().ok()?))
+            })
+            .collect()
+    }
+
+    fn object(node: Node) -> Option<MapObject> {
+        let tile = attribute(node, "gid").and_then(decode_gid);
+        let size = Vec2::new(
+            attribute(node, "width").unwrap_or(0.0),
+            attribute(node, "height").unwrap_or(0.0),
+        );
+
+        let mut position = Vec2::new(
+            attribute(node, "x").unwrap_or(0.0),
+            attribute(node, "y").unwrap_or(0.0),
+        );
+        if tile.is_some() {
+            position.y -= size.y;
+        }
+
+        let shape = if child(node, "ellipse").is_some() {
+            ObjectShape::Ellipse
+        } else if child(node, "point").is_some() {
+            ObjectShape::Point
+        } else if let Some(polygon) = child(node, "polygon") {
+            ObjectShape::Polygon(points(polygon))
+        } else if let Some(polyline) = child(node, "polyline") {
+            ObjectShape::Polyline(points(polyline))
+        } else if let Some(text) = child(node, "text") {
+            ObjectShape::Text(text.text().unwrap_or_default().to_string())
+        } else {
+            ObjectShape::Rect
+        };
+
+        let class = match node.attribute("class") {
+            Some(class) => class.to_string(),
+            None => string(node, "type"),
+        };
+
+        Some(MapObject {
+            id: attribute(node, "id")?,
+            name: string(node, "name"),
+            class,
+            position,
+            size,
+            rotation: attribute(node, "rotation").unwrap_or(0.0),
+            visible: node.attribute("visible") != Some("0"),
+            tile,
+            shape,
+            properties: properties(node),
+        })
+    }
+
+    fn properties(node: Node) -> Properties {
+        let properties = match child(node, "properties") {
+            Some(properties) => properties,
+            None => return Properties::new(),
+        };
+
+        properties
+            .children()
+            .filter(|property| property.has_tag_name("property"))
+            .filter_map(|property| {
+                let kind = property.attribute("type").unwrap_or("string");
+                // Multiline strings are stored as the element text instead of an attribute.
+                let value = property
+                    .attribute("value")
+                    .or_else(|| property.text())
+                    .unwrap_or_default();
+
+                Some((
+                    property.attribute("name")?.to_string(),
+                    parse_property(kind, value)?,
+                ))
+            })
+            .collect()
+    }
+}
+
+fn external_tileset(vfs: &Vfs, path: &Path, first_gid: u32) -> Result<RawTileset, ResourceError> {
+    if is_json(path) {
+        json::tileset_file(vfs, path, first_gid)
+    } else {
+        xml::tileset_file(vfs, path, first_gid)
+    }
+}
+
+mod loader {
+    use super::*;
+
+    pub struct TiledMap {
+        pub tilemap: TileMap,
+        pub object_layers: Vec<ObjectLayer>,
+        pub properties: Properties,
+    }
+
+    impl TiledMap {
+        // Picks the format from the extension, `.tmj`/`.json` for JSON and XML otherwise.
+        // Tiles with a `solid` bool property become solid and tile layers with a `collides`
+        // property set to false are left out of collisions. Infinite maps and image
+        // collection tilesets are not supported.
+        #[inline]
+        pub fn load(path: &str) -> Result<Self, ResourceError> {
+            TiledMap::load_from(&Vfs::new(), path)
+        }
+
+        // Reads the map, its external tilesets and their images through `vfs`.
+        pub fn load_from(vfs: &Vfs, path: &str) -> Result<Self, ResourceError> {
+            let path = Path::new(path);
+
+            let raw = if is_json(path) {
+                json::map(vfs, path)?
+            } else {
+                xml::map(vfs, path)?
+            };
+
+            TiledMap::from_raw(vfs, path, raw)
+        }
+
+        fn from_raw(vfs: &Vfs, path: &Path, raw: RawMap) -> Result<Self, ResourceError> {
+            let cells = raw
+                .width
+                .checked_mul(raw.height)
+                .ok_or_else(|| invalid(path, "map size is too large"))?;
+
+            let tilesets = raw
+                .tilesets
+                .into_iter()
+                .map(|tileset| TiledMap::tileset(vfs, path, tileset))
+                .collect::<Result<_, _>>()?;
+
+            let mut tilemap = TileMap::with_tile_size(
+                tilesets,
+                raw.width,
+                raw.height,
+                raw.tile_width,
+                raw.tile_height,
+            );
+
+            let mut object_layers = Vec::new();
+
+            for layer in raw.layers {
+                let (name, visible, gids, properties) = match layer {
+                    RawLayer::Tiles {
+                        name,
+                        visible,
+                        gids,
+                        properties,
+                    } => (name, visible, gids, properties),
+                    RawLayer::Objects(layer) => {
+                        object_layers.push(layer);
+                        continue;
+                    }
+                };
+
+                if gids.len() != cells as usize {
+                    let reason = format!(
+                        "layer `{}` has {} tiles, expected {}x{}",
+                        name,
+                        gids.len(),
+                        raw.width,
+                        raw.height
+                    );
+                    return Err(invalid(path, reason));
+                }
+
+                for &gid in &gids {
+                    if let Some((id, _)) = decode_gid(gid) {
+                        if tilemap.resolve(id).is_none() {
+                            let reason = format!(
+                                "tile gid {} in layer `{}` is not in any tileset",
+                                gid & GID_MASK,
+                                name
+                            );
+                            return Err(invalid(path, reason));
+                        }
+                    }
+                }
+
+                let index = tilemap.add_layer(name);
+                let Some(layer) = tilemap.layer_mut(index) else {
+                    continue;
+                };
+                layer.visible = visible;
+                layer.collides = properties
+                    .get("collides")
+                    .and_then(Property::as_bool)
+                    .unwrap_or(true);
+
+                for (i, gid) in gids.into_iter().enumerate() {
+                    if let Some((id, flip)) = decode_gid(gid) {
+                        let (x, y) = (i as u32 % raw.width, i as u32 / raw.width);
+                        layer.set_flipped(x, y, Some(id), flip);
+                    }
+                }
+            }
+
+            Ok(TiledMap {
+                tilemap,
+                object_layers,
+                properties: raw.properties,
+            })
+        }
+
+        fn tileset(vfs: &Vfs, path: &Path, raw: RawTileset) -> Result<Tileset, ResourceError> {
+            let image = raw.image.display();
+            let bytes = vfs
+                .read(&raw.image)
+                .map_err(|_| invalid(path, format!("missing tileset image {}", image)))?;
+            let image = Image::from_memory(&bytes).ok_or_else(|| {
+                invalid(path, format!("could not decode tileset image {}", image))
+            })?;
+
+            let first_id = raw
+                .first_gid
+                .checked_sub(1)
+                .ok_or_else(|| invalid(path, "tileset `firstgid` must be at least 1"))?;
+
+            let mut tileset = Tileset::new(&Rc::new(image), raw.tile_width, raw.tile_height)
+                .with_spacing(raw.margin, raw.spacing)
+                .with_first_id(first_id);
+
+            for tile in raw.tiles {
+                let solid = tile.properties.get("solid").and_then(Property::as_bool);
+                tileset.set_solid(tile.id, solid == Some(true));
+
+                let frames = tile
+                    .animation
+                    .into_iter()
+                    .map(|(frame, millis)| (frame, Duration::from_millis(millis)))
+                    .collect();
+                tileset.set_animation(tile.id, frames);
+            }
+
+            Ok(tileset)
+        }
+
+        #[inline]
+        pub fn property(&self, name: &str) -> Option<&Property> {
+            self.properties.get(name)
+        }
+
+        #[inline]
+        pub fn object_layer(&self, name: &str) -> Option<&ObjectLayer> {
+            self.object_layers.iter().find(|layer| layer.name == name)
+        }
+
+        #[inline]
+        pub fn objects(&self) -> impl Iterator<Item = &MapObject> {
+            self.object_layers
+                .iter()
+                .flat_map(|layer| layer.objects.iter())
+        }
+
+        // Lets `spawn` turn each object into an entity, skipping the ones it returns `None`
+        // for. Solid tiles are added separately through `tilemap.colliders()`.
+        pub fn spawn<S, D, Ctx, F>(&self, scene: &mut Scene<S, D, Ctx>, mut spawn: F)
+        where
+            S: Entity<Ctx> + Collision<D, Ctx>,
+            D: Entity<Ctx> + Collision<D, Ctx> + Collision<S, Ctx>,
+            F: FnMut(&MapObject) -> Option<Spawn<S, D>>,
+        {
+            for object in self.objects() {
+                if let Some(entity) = spawn(object) {
+                    scene.spawn(entity);
+                }
+            }
+        }
+    }
+}
+pub use loader::*;
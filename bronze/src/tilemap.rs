@@ -0,0 +1,811 @@
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    rc::Rc,
+    time::Duration,
+};
+
+use sfml::{
+    graphics::{PrimitiveType, Vertex},
+    system::Vector2f,
+};
+
+use crate::{
+    graphics::Color,
+    math::{Transform2D, Vec2},
+    physics::Body,
+    resources::Image,
+    scene::Entity,
+    shape::{BBox, Rect, Shape, ShapeRef},
+    window::Canvas,
+};
+
+pub type TileId = u32;
+
+// Side of the square chunks layers are batched into, in tiles.
+pub const CHUNK_SIZE: u32 = 16;
+
+mod tileset {
+    use super::*;
+
+    // Tiles are numbered from zero within a tileset. Maps with several tilesets give each a
+    // range of ids starting at its `first_id`.
+    pub struct Tileset {
+        image: Rc<Image>,
+        tile_width: u32,
+        tile_height: u32,
+        margin: u32,
+        spacing: u32,
+        first_id: TileId,
+        solid: HashSet<TileId>,
+        animations: HashMap<TileId, Vec<(TileId, Duration)>>,
+    }
+
+    impl Tileset {
+        #[inline]
+        pub fn new(image: &Rc<Image>, tile_width: u32, tile_height: u32) -> Self {
+            Tileset {
+                image: Rc::clone(image),
+                tile_width,
+                tile_height,
+                margin: 0,
+                spacing: 0,
+                first_id: 0,
+                solid: HashSet::new(),
+                animations: HashMap::new(),
+            }
+        }
+
+        #[inline]
+        pub fn with_first_id(mut self, first_id: TileId) -> Self {
+            self.first_id = first_id;
+            self
+        }
+
+        // `margin` is the border around the whole image and `spacing` the gap between tiles.
+        #[inline]
+        pub fn with_spacing(mut self, margin: u32, spacing: u32) -> Self {
+            self.margin = margin;
+            self.spacing = spacing;
+            self
+        }
+
+        #[inline]
+        pub fn with_solid<I: IntoIterator<Item = TileId>>(mut self, tiles: I) -> Self {
+            self.solid.extend(tiles);
+            self
+        }
+
+        #[inline]
+        pub fn image(&self) -> &Rc<Image> {
+            &self.image
+        }
+
+        #[inline]
+        pub fn first_id(&self) -> TileId {
+            self.first_id
+        }
+
+        #[inline]
+        pub(super) fn set_first_id(&mut self, first_id: TileId) {
+            self.first_id = first_id;
+        }
+
+        #[inline]
+        pub fn contains(&self, id: TileId) -> bool {
+            id >= self.first_id && id - self.first_id < self.tile_count()
+        }
+
+        #[inline]
+        pub fn tile_width(&self) -> u32 {
+            self.tile_width
+        }
+
+        #[inline]
+        pub fn tile_height(&self) -> u32 {
+            self.tile_height
+        }
+
+        #[inline]
+        pub fn columns(&self) -> u32 {
+            let width = self
+                .image
+                .texture()
+                .size()
+                .x
+                .saturating_sub(self.margin * 2);
+            (width + self.spacing) / (self.tile_width + self.spacing).max(1)
+        }
+
+        #[inline]
+        pub fn rows(&self) -> u32 {
+            let height = self
+                .image
+                .texture()
+                .size()
+                .y
+                .saturating_sub(self.margin * 2);
+            (height + self.spacing) / (self.tile_height + self.spacing).max(1)
+        }
+
+        #[inline]
+        pub fn tile_count(&self) -> u32 {
+            self.columns() * self.rows()
+        }
+
+        // Top left pixel of the tile in the tileset image.
+        pub fn tile_origin(&self, tile: TileId) -> Option<(u32, u32)> {
+            let columns = self.columns();
+
+            if columns == 0 || tile >= self.tile_count() {
+                return None;
+            }
+
+            let (column, row) = (tile % columns, tile / columns);
+
+            Some((
+                self.margin + column * (self.tile_width + self.spacing),
+                self.margin + row * (self.tile_height + self.spacing),
+            ))
+        }
+
+        #[inline]
+        pub fn is_solid(&self, tile: TileId) -> bool {
+            self.solid.contains(&tile)
+        }
+
+        #[inline]
+        pub fn set_solid(&mut self, tile: TileId, solid: bool) {
+            if solid {
+                self.solid.insert(tile);
+            } else {
+                self.solid.remove(&tile);
+            }
+        }
+
+        // Frames are local tile ids shown for the given duration, looping.
+        #[inline]
+        pub fn set_animation(&mut self, tile: TileId, frames: Vec<(TileId, Duration)>) {
+            if frames.is_empty() {
+                self.animations.remove(&tile);
+            } else {
+                self.animations.insert(tile, frames);
+            }
+        }
+
+        #[inline]
+        pub fn animation(&self, tile: TileId) -> Option<&[(TileId, Duration)]> {
+            self.animations.get(&tile).map(Vec::as_slice)
+        }
+
+        #[inline]
+        pub fn is_animated(&self, tile: TileId) -> bool {
+            self.animations.contains_key(&tile)
+        }
+
+        #[inline]
+        pub(super) fn has_animations(&self) -> bool {
+            !self.animations.is_empty()
+        }
+
+        // The tile shown in place of `tile` after `elapsed` time.
+        pub fn frame_at(&self, tile: TileId, elapsed: Duration) -> TileId {
+            let frames = match self.animations.get(&tile) {
+                Some(frames) => frames,
+                None => return tile,
+            };
+
+            let total: Duration = frames.iter().map(|(_, duration)| *duration).sum();
+
+            if total.is_zero() {
+                return frames[0].0;
+            }
+
+            let mut time = elapsed.as_nanos() % total.as_nanos();
+
+            for (frame, duration) in frames {
+                if time < duration.as_nanos() {
+                    return *frame;
+                }
+                time -= duration.as_nanos();
+            }
+
+            tile
+        }
+    }
+
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+    pub struct Flip {
+        pub horizontal: bool,
+        pub vertical: bool,
+        // Swaps the x and y axes, applied before the other two.
+        pub diagonal: bool,
+    }
+}
+pub use tileset::*;
+
+mod layer {
+    use super::*;
+
+    struct Chunk {
+        // One batch of vertices per tileset, in map coordinates.
+        batches: Vec<Vec<Vertex>>,
+        animated: bool,
+    }
+
+    pub struct TileLayer {
+        name: String,
+        width: u32,
+        height: u32,
+        tiles: Vec<Option<(TileId, Flip)>>,
+        pub visible: bool,
+        pub collides: bool,
+        // Rebuilt when a tile inside changes or an animation inside moves to its next frame.
+        chunks: RefCell<Vec<Option<Chunk>>>,
+    }
+
+    impl TileLayer {
+        pub fn new<S: Into<String>>(name: S, width: u32, height: u32) -> Self {
+            let chunk_count =
+                width.div_ceil(CHUNK_SIZE) as usize * height.div_ceil(CHUNK_SIZE) as usize;
+
+            TileLayer {
+                name: name.into(),
+                width,
+                height,
+                tiles: vec![None; width as usize * height as usize],
+                visible: true,
+                collides: true,
+                chunks: RefCell::new((0..chunk_count).map(|_| None).collect()),
+            }
+        }
+
+        #[inline]
+        pub fn name(&self) -> &str {
+            &self.name
+        }
+
+        #[inline]
+        pub fn width(&self) -> u32 {
+            self.width
+        }
+
+        #[inline]
+        pub fn height(&self) -> u32 {
+            self.height
+        }
+
+        #[inline]
+        fn index(&self, x: u32, y: u32) -> Option<usize> {
+            (x < self.width && y < self.height)
+                .then(|| y as usize * self.width as usize + x as usize)
+        }
+
+        #[inline]
+        pub fn get(&self, x: u32, y: u32) -> Option<TileId> {
+            self.tiles[self.index(x, y)?].map(|(tile, _)| tile)
+        }
+
+        #[inline]
+        pub fn flip(&self, x: u32, y: u32) -> Flip {
+            self.index(x, y)
+                .and_then(|index| self.tiles[index])
+                .map_or(Flip::default(), |(_, flip)| flip)
+        }
+
+        #[inline]
+        pub fn set(&mut self, x: u32, y: u32, tile: Option<TileId>) {
+            self.set_flipped(x, y, tile, Flip::default());
+        }
+
+        pub fn set_flipped(&mut self, x: u32, y: u32, tile: Option<TileId>, flip: Flip) {
+            if let Some(index) = self.index(x, y) {
+                let chunk = self.chunk_index(x / CHUNK_SIZE, y / CHUNK_SIZE);
+                self.tiles[index] = tile.map(|tile| (tile, flip));
+                self.chunks.get_mut()[chunk] = None;
+            }
+        }
+
+        pub fn fill(&mut self, tile: Option<TileId>) {
+            self.tiles.fill(tile.map(|tile| (tile, Flip::default())));
+            self.invalidate();
+        }
+
+        // Tiles listed row by row, `None` for empty cells.
+        pub fn set_all(&mut self, tiles: &[Option<TileId>]) {
+            for (cell, tile) in self.tiles.iter_mut().zip(tiles) {
+                *cell = tile.map(|tile| (tile, Flip::default()));
+            }
+            self.invalidate();
+        }
+
+        #[inline]
+        pub(super) fn invalidate(&mut self) {
+            self.chunks
+                .get_mut()
+                .iter_mut()
+                .for_each(|chunk| *chunk = None);
+        }
+
+        pub(super) fn invalidate_animated(&mut self) {
+            for chunk in self.chunks.get_mut().iter_mut() {
+                if chunk.as_ref().is_some_and(|chunk| chunk.animated) {
+                    *chunk = None;
+                }
+            }
+        }
+
+        #[inline]
+        fn chunk_index(&self, chunk_x: u32, chunk_y: u32) -> usize {
+            chunk_y as usize * self.width.div_ceil(CHUNK_SIZE) as usize + chunk_x as usize
+        }
+
+        pub(super) fn draw_chunk(
+            &self,
+            target: &mut Canvas,
+            map: &TileMap,
+            (chunk_x, chunk_y): (u32, u32),
+            transform: &Transform2D,
+        ) {
+            let index = self.chunk_index(chunk_x, chunk_y);
+            let mut chunks = self.chunks.borrow_mut();
+            let chunk =
+                chunks[index].get_or_insert_with(|| self.build_chunk(map, chunk_x, chunk_y));
+
+            for (tileset, vertices) in map.tilesets().iter().zip(&chunk.batches) {
+                if !vertices.is_empty() {
+                    target.draw_vertices_transformed(
+                        vertices,
+                        PrimitiveType::QUADS,
+                        Some(tileset.image().texture()),
+                        transform,
+                    );
+                }
+            }
+        }
+
+        fn build_chunk(&self, map: &TileMap, chunk_x: u32, chunk_y: u32) -> Chunk {
+            let mut chunk = Chunk {
+                batches: vec![Vec::new(); map.tilesets().len()],
+                animated: false,
+            };
+
+            let tile_size = map.tile_size();
+            let xs = chunk_x * CHUNK_SIZE..((chunk_x + 1) * CHUNK_SIZE).min(self.width);
+            let ys = chunk_y * CHUNK_SIZE..((chunk_y + 1) * CHUNK_SIZE).min(self.height);
+
+            for y in ys {
+                for x in xs.clone() {
+                    let (index, local) = match self.get(x, y).and_then(|id| map.resolve(id)) {
+                        Some(found) => found,
+                        None => continue,
+                    };
+
+                    let tileset = &map.tilesets()[index];
+                    chunk.animated |= tileset.is_animated(local);
+
+                    let shown = tileset.frame_at(local, map.elapsed());
+                    let (u, v) = match tileset.tile_origin(shown) {
+                        Some((u, v)) => (u as f32, v as f32),
+                        None => continue,
+                    };
+
+                    // Tiles taller than the grid stick out of their cell upwards, like in Tiled.
+                    let (w, h) = (tileset.tile_width() as f32, tileset.tile_height() as f32);
+                    let left = x as f32 * tile_size.x;
+                    let top = (y + 1) as f32 * tile_size.y - h;
+                    let flip = self.flip(x, y);
+
+                    chunk.batches[index].extend(
+                        [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)].map(|(cx, cy)| {
+                            let (mut tx, mut ty) = (cx, cy);
+
+                            if flip.vertical {
+                                ty = 1.0 - ty;
+                            }
+                            if flip.horizontal {
+                                tx = 1.0 - tx;
+                            }
+                            if flip.diagonal {
+                                (tx, ty) = (ty, tx);
+                            }
+
+                            Vertex::new(
+                                Vector2f::new(left + cx * w, top + cy * h),
+                                Color::WHITE,
+                                Vector2f::new(u + tx * w, v + ty * h),
+                            )
+                        }),
+                    );
+                }
+            }
+
+            chunk
+        }
+    }
+}
+pub use layer::*;
+
+mod map {
+    use super::*;
+
+    pub struct TileMap {
+        tilesets: Vec<Tileset>,
+        width: u32,
+        height: u32,
+        tile_width: u32,
+        tile_height: u32,
+        position: Vec2,
+        layers: Vec<TileLayer>,
+        elapsed: Duration,
+        frames: Vec<TileId>,
+    }
+
+    impl TileMap {
+        // The grid takes its cell size from the first tileset.
+        #[inline]
+        pub fn new(tileset: Tileset, width: u32, height: u32) -> Self {
+            let (tile_width, tile_height) = (tileset.tile_width(), tileset.tile_height());
+            TileMap::with_tile_size(vec![tileset], width, height, tile_width, tile_height)
+        }
+
+        pub fn with_tile_size(
+            tilesets: Vec<Tileset>,
+            width: u32,
+            height: u32,
+            tile_width: u32,
+            tile_height: u32,
+        ) -> Self {
+            let mut map = TileMap {
+                tilesets: Vec::new(),
+                width,
+                height,
+                tile_width,
+                tile_height,
+                position: Vec2::ZERO,
+                layers: Vec::new(),
+                elapsed: Duration::ZERO,
+                frames: Vec::new(),
+            };
+
+            for tileset in tilesets {
+                map.add_tileset(tileset);
+            }
+
+            map
+        }
+
+        // Moves the tileset's ids past the ones already in use if they would overlap.
+        pub fn add_tileset(&mut self, mut tileset: Tileset) -> usize {
+            if let Some(last) = self.tilesets.last() {
+                let next_id = last.first_id() + last.tile_count();
+                tileset.set_first_id(tileset.first_id().max(next_id));
+            }
+
+            self.tilesets.push(tileset);
+            self.layers.iter_mut().for_each(TileLayer::invalidate);
+            self.tilesets.len() - 1
+        }
+
+        #[inline]
+        pub fn tilesets(&self) -> &[Tileset] {
+            &self.tilesets
+        }
+
+        #[inline]
+        pub fn tileset(&self) -> &Tileset {
+            &self.tilesets[0]
+        }
+
+        #[inline]
+        pub fn tileset_mut(&mut self) -> &mut Tileset {
+            &mut self.tilesets[0]
+        }
+
+        // Index of the tileset holding a map tile id, and the id within that tileset.
+        #[inline]
+        pub fn resolve(&self, id: TileId) -> Option<(usize, TileId)> {
+            let index = self
+                .tilesets
+                .iter()
+                .rposition(|tileset| tileset.first_id() <= id)?;
+            let tileset = &self.tilesets[index];
+
+            tileset
+                .contains(id)
+                .then(|| (index, id - tileset.first_id()))
+        }
+
+        #[inline]
+        pub fn elapsed(&self) -> Duration {
+            self.elapsed
+        }
+
+        // Advances animated tiles.
+        pub fn update(&mut self, frame_time: Duration) {
+            if !self.tilesets.iter().any(Tileset::has_animations) {
+                return;
+            }
+
+            self.elapsed += frame_time;
+
+            let elapsed = self.elapsed;
+            let frames: Vec<TileId> = self
+                .tilesets
+                .iter()
+                .flat_map(|tileset| {
+                    (0..tileset.tile_count())
+                        .filter(|&tile| tileset.is_animated(tile))
+                        .map(move |tile| tileset.frame_at(tile, elapsed))
+                })
+                .collect();
+
+            if frames != self.frames {
+                self.frames = frames;
+                self.layers
+                    .iter_mut()
+                    .for_each(TileLayer::invalidate_animated);
+            }
+        }
+
+        #[inline]
+        pub fn width(&self) -> u32 {
+            self.width
+        }
+
+        #[inline]
+        pub fn height(&self) -> u32 {
+            self.height
+        }
+
+        #[inline]
+        pub fn tile_size(&self) -> Vec2 {
+            Vec2::new(self.tile_width as f32, self.tile_height as f32)
+        }
+
+        #[inline]
+        pub fn position(&self) -> Vec2 {
+            self.position
+        }
+
+        #[inline]
+        pub fn set_position(&mut self, x: f32, y: f32) {
+            self.position = Vec2::new(x, y);
+        }
+
+        pub fn bounds(&self) -> Rect {
+            let tile_size = self.tile_size();
+
+            Rect::new(
+                self.position.x,
+                self.position.y,
+                self.width as f32 * tile_size.x,
+                self.height as f32 * tile_size.y,
+            )
+        }
+
+        #[inline]
+        pub fn add_layer<S: Into<String>>(&mut self, name: S) -> usize {
+            self.layers
+                .push(TileLayer::new(name, self.width, self.height));
+            self.layers.len() - 1
+        }
+
+        #[inline]
+        pub fn layers(&self) -> &[TileLayer] {
+            &self.layers
+        }
+
+        #[inline]
+        pub fn layer(&self, index: usize) -> Option<&TileLayer> {
+            self.layers.get(index)
+        }
+
+        #[inline]
+        pub fn layer_mut(&mut self, index: usize) -> Option<&mut TileLayer> {
+            self.layers.get_mut(index)
+        }
+
+        #[inline]
+        pub fn layer_by_name(&self, name: &str) -> Option<&TileLayer> {
+            self.layers.iter().find(|layer| layer.name() == name)
+        }
+
+        #[inline]
+        pub fn layer_by_name_mut(&mut self, name: &str) -> Option<&mut TileLayer> {
+            self.layers.iter_mut().find(|layer| layer.name() == name)
+        }
+
+        #[inline]
+        pub fn tile(&self, layer: usize, x: u32, y: u32) -> Option<TileId> {
+            self.layers.get(layer)?.get(x, y)
+        }
+
+        #[inline]
+        pub fn set_tile(&mut self, layer: usize, x: u32, y: u32, tile: Option<TileId>) {
+            if let Some(layer) = self.layers.get_mut(layer) {
+                layer.set(x, y, tile);
+            }
+        }
+
+        #[inline]
+        pub fn is_solid_tile(&self, id: TileId) -> bool {
+            self.resolve(id)
+                .is_some_and(|(index, local)| self.tilesets[index].is_solid(local))
+        }
+
+        // Grid coordinates of the tile under a world position.
+        pub fn tile_coords<P: Into<Vec2>>(&self, position: P) -> Option<(u32, u32)> {
+            let local = position.into() - self.position;
+            let tile_size = self.tile_size();
+
+            let x = (local.x / tile_size.x).floor();
+            let y = (local.y / tile_size.y).floor();
+
+            let inside = x >= 0.0 && y >= 0.0 && x < self.width as f32 && y < self.height as f32;
+            inside.then_some((x as u32, y as u32))
+        }
+
+        #[inline]
+        pub fn tile_at<P: Into<Vec2>>(&self, layer: usize, position: P) -> Option<TileId> {
+            let (x, y) = self.tile_coords(position)?;
+            self.tile(layer, x, y)
+        }
+
+        pub fn tile_rect(&self, x: u32, y: u32) -> Rect {
+            let tile_size = self.tile_size();
+
+            Rect::new(
+                self.position.x + x as f32 * tile_size.x,
+                self.position.y + y as f32 * tile_size.y,
+                tile_size.x,
+                tile_size.y,
+            )
+        }
+
+        pub fn is_solid(&self, x: u32, y: u32) -> bool {
+            self.layers.iter().any(|layer| {
+                layer.collides && layer.get(x, y).is_some_and(|id| self.is_solid_tile(id))
+            })
+        }
+
+        #[inline]
+        pub fn is_solid_at<P: Into<Vec2>>(&self, position: P) -> bool {
+            self.tile_coords(position)
+                .is_some_and(|(x, y)| self.is_solid(x, y))
+        }
+
+        // Solid tiles merged greedily into as few rects as possible, row spans first.
+        pub fn collision_rects(&self) -> Vec<Rect> {
+            let (width, height) = (self.width as usize, self.height as usize);
+            let mut pending: Vec<bool> = (0..width * height)
+                .map(|i| self.is_solid((i % width) as u32, (i / width) as u32))
+                .collect();
+
+            let mut rects = Vec::new();
+
+            for y in 0..height {
+                let mut x = 0;
+
+                while x < width {
+                    if !pending[y * width + x] {
+                        x += 1;
+                        continue;
+                    }
+
+                    let span = (x..width).take_while(|&x| pending[y * width + x]).count();
+
+                    let rows = 1
+                        + (y + 1..height)
+                            .take_while(|&row| (x..x + span).all(|x| pending[row * width + x]))
+                            .count();
+
+                    for row in y..y + rows {
+                        pending[row * width + x..row * width + x + span].fill(false);
+                    }
+
+                    let mut rect = self.tile_rect(x as u32, y as u32);
+                    rect.width *= span as f32;
+                    rect.height *= rows as f32;
+                    rects.push(rect);
+
+                    x += span;
+                }
+            }
+
+            rects
+        }
+
+        #[inline]
+        pub fn colliders(&self) -> Vec<TileCollider> {
+            self.collision_rects()
+                .into_iter()
+                .map(TileCollider::new)
+                .collect()
+        }
+
+        // Draws the map with the canvas showing the world from the origin.
+        #[inline]
+        pub fn draw(&self, target: &mut Canvas) {
+            let size = target.size();
+            let view = Rect::new(0.0, 0.0, size.x as f32, size.y as f32);
+            self.draw_view(target, &view);
+        }
+
+        // Draws the part of the map under `view`, a world space rect whose top left corner
+        // ends up at the top left of the canvas.
+        pub fn draw_view(&self, target: &mut Canvas, view: &Rect) {
+            let tile_size = self.tile_size();
+            let chunk_width = tile_size.x * CHUNK_SIZE as f32;
+            let chunk_height = tile_size.y * CHUNK_SIZE as f32;
+
+            let columns = self.width.div_ceil(CHUNK_SIZE);
+            let rows = self.height.div_ceil(CHUNK_SIZE);
+
+            let left = view.left() - self.position.x;
+            let top = view.top() - self.position.y;
+
+            let chunk_range = |from: f32, to: f32, size: f32, count: u32| {
+                let first = (from / size).floor().max(0.0) as u32;
+                let last = ((to / size).ceil().max(0.0) as u32).min(count);
+                first..last
+            };
+
+            // Oversized tiles can reach into the view from the chunk row below it.
+            let xs = chunk_range(left, left + view.width(), chunk_width, columns);
+            let ys = chunk_range(top, top + view.height() + chunk_height, chunk_height, rows);
+
+            let transform = Transform2D::translation(-left, -top);
+
+            for layer in self.layers.iter().filter(|layer| layer.visible) {
+                for chunk_y in ys.clone() {
+                    for chunk_x in xs.clone() {
+                        layer.draw_chunk(target, self, (chunk_x, chunk_y), &transform);
+                    }
+                }
+            }
+        }
+    }
+}
+pub use map::*;
+
+mod collider {
+    use super::*;
+
+    pub struct TileCollider {
+        body: Body,
+    }
+
+    impl TileCollider {
+        #[inline]
+        pub fn new(rect: Rect) -> Self {
+            TileCollider {
+                body: Body::fixed(Shape::Rect(rect)),
+            }
+        }
+
+        #[inline]
+        pub fn rect(&self) -> Rect {
+            let shape = self.body.as_ref();
+            Rect::new(shape.left(), shape.top(), shape.width(), shape.height())
+        }
+    }
+
+    impl<Ctx> Entity<Ctx> for TileCollider {
+        #[inline]
+        fn bbox(&self) -> ShapeRef<'_> {
+            self.body.as_ref()
+        }
+
+        #[inline]
+        fn body(&self) -> Option<&Body> {
+            Some(&self.body)
+        }
+
+        #[inline]
+        fn body_mut(&mut self) -> Option<&mut Body> {
+            Some(&mut self.body)
+        }
+    }
+}
+pub use collider::*;
@@ -0,0 +1,262 @@
+use std::{
+    cell::{Cell, RefCell},
+    f32::consts::PI,
+    time::Duration,
+};
+
+use sfml::{
+    graphics::{PrimitiveType, Texture, Vertex},
+    system::Vector2f,
+    SfBox,
+};
+
+use crate::{graphics::Color, tween::Easing, window::Canvas};
+
+mod config {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum WipeDirection {
+        Left,
+        Right,
+        Up,
+        Down,
+    }
+
+    #[derive(Debug, Clone, Copy)]
+    pub enum TransitionKind {
+        Fade(Color),
+        Crossfade,
+        Wipe(WipeDirection),
+        Iris(Color),
+    }
+
+    #[derive(Debug, Clone, Copy)]
+    pub struct Transition {
+        pub kind: TransitionKind,
+        pub duration: Duration,
+        pub easing: Easing,
+    }
+
+    impl Transition {
+        pub fn new(kind: TransitionKind, duration: Duration) -> Self {
+            Transition {
+                kind,
+                duration,
+                easing: Easing::Linear,
+            }
+        }
+
+        pub fn fade(color: Color, duration: Duration) -> Self {
+            Transition::new(TransitionKind::Fade(color), duration)
+        }
+
+        pub fn crossfade(duration: Duration) -> Self {
+            Transition::new(TransitionKind::Crossfade, duration)
+        }
+
+        pub fn wipe(direction: WipeDirection, duration: Duration) -> Self {
+            Transition::new(TransitionKind::Wipe(direction), duration)
+        }
+
+        pub fn iris(color: Color, duration: Duration) -> Self {
+            Transition::new(TransitionKind::Iris(color), duration)
+        }
+
+        pub fn ease(mut self, easing: Easing) -> Self {
+            self.easing = easing;
+            self
+        }
+
+        // Snapshot transitions capture the outgoing frame and switch right away, the others
+        // cover the outgoing state first and switch halfway through.
+        #[inline]
+        pub(crate) fn uses_snapshot(&self) -> bool {
+            matches!(
+                self.kind,
+                TransitionKind::Crossfade | TransitionKind::Wipe(_)
+            )
+        }
+    }
+}
+pub use config::*;
+
+mod player {
+    use super::*;
+
+    const IRIS_SEGMENTS: usize = 64;
+
+    enum Phase {
+        Out,
+        In,
+    }
+
+    pub(crate) struct TransitionPlayer {
+        transition: Transition,
+        elapsed: f32,
+        switched: bool,
+        captured: Cell<bool>,
+        snapshot: RefCell<Option<SfBox<Texture>>>,
+    }
+
+    impl TransitionPlayer {
+        pub fn new(transition: Transition) -> Self {
+            TransitionPlayer {
+                transition,
+                elapsed: 0.0,
+                switched: false,
+                captured: Cell::new(false),
+                snapshot: RefCell::new(None),
+            }
+        }
+
+        fn duration(&self) -> f32 {
+            self.transition.duration.as_secs_f32().max(f32::EPSILON)
+        }
+
+        fn phase(&self) -> (Phase, f32) {
+            let duration = self.duration();
+
+            let (phase, t) = if self.transition.uses_snapshot() {
+                (Phase::In, self.elapsed / duration)
+            } else if !self.switched {
+                (Phase::Out, self.elapsed / (duration / 2.0))
+            } else {
+                (
+                    Phase::In,
+                    (self.elapsed - duration / 2.0) / (duration / 2.0),
+                )
+            };
+
+            (phase, self.transition.easing.apply(t.clamp(0.0, 1.0)))
+        }
+
+        // Returns true on the frame the pending state change should be applied.
+        pub fn advance(&mut self, frame_time: Duration) -> bool {
+            if self.switched {
+                self.elapsed += frame_time.as_secs_f32();
+                return false;
+            }
+
+            if self.transition.uses_snapshot() {
+                if !self.captured.get() {
+                    return false;
+                }
+            } else {
+                self.elapsed += frame_time.as_secs_f32();
+
+                if self.elapsed < self.duration() / 2.0 {
+                    return false;
+                }
+            }
+
+            self.switched = true;
+            true
+        }
+
+        pub fn is_finished(&self) -> bool {
+            self.switched && self.elapsed >= self.duration()
+        }
+
+        pub fn draw(&self, target: &mut Canvas) {
+            if self.transition.uses_snapshot() && !self.switched {
+                if !self.captured.get() {
+                    *self.snapshot.borrow_mut() = target.snapshot();
+                    self.captured.set(true);
+                }
+                return;
+            }
+
+            let size = target.size();
+            let (width, height) = (size.x as f32, size.y as f32);
+            let (phase, t) = self.phase();
+
+            match self.transition.kind {
+                TransitionKind::Fade(color) => {
+                    let alpha = match phase {
+                        Phase::Out => t,
+                        Phase::In => 1.0 - t,
+                    };
+                    fill_rect(target, 0.0, 0.0, width, height, with_alpha(color, alpha));
+                }
+                TransitionKind::Iris(color) => {
+                    let radius = (width * width + height * height).sqrt() / 2.0;
+                    let radius = match phase {
+                        Phase::Out => radius * (1.0 - t),
+                        Phase::In => radius * t,
+                    };
+                    iris(target, width, height, radius, color);
+                }
+                TransitionKind::Crossfade => {
+                    if let Some(snapshot) = self.snapshot.borrow().as_deref() {
+                        let color = with_alpha(Color::WHITE, 1.0 - t);
+                        textured_rect(target, snapshot, (0.0, 0.0, width, height), color);
+                    }
+                }
+                TransitionKind::Wipe(direction) => {
+                    if let Some(snapshot) = self.snapshot.borrow().as_deref() {
+                        let region = match direction {
+                            WipeDirection::Right => (width * t, 0.0, width * (1.0 - t), height),
+                            WipeDirection::Left => (0.0, 0.0, width * (1.0 - t), height),
+                            WipeDirection::Down => (0.0, height * t, width, height * (1.0 - t)),
+                            WipeDirection::Up => (0.0, 0.0, width, height * (1.0 - t)),
+                        };
+                        textured_rect(target, snapshot, region, Color::WHITE);
+                    }
+                }
+            }
+        }
+    }
+
+    fn with_alpha(mut color: Color, alpha: f32) -> Color {
+        color.a = (color.a as f32 * alpha.clamp(0.0, 1.0)) as u8;
+        color
+    }
+
+    fn fill_rect(target: &mut Canvas, x: f32, y: f32, width: f32, height: f32, color: Color) {
+        let vertices = [
+            Vertex::with_pos_color(Vector2f::new(x, y), color),
+            Vertex::with_pos_color(Vector2f::new(x + width, y), color),
+            Vertex::with_pos_color(Vector2f::new(x + width, y + height), color),
+            Vertex::with_pos_color(Vector2f::new(x, y + height), color),
+        ];
+        target.draw_vertices(&vertices, PrimitiveType::QUADS, None);
+    }
+
+    // Draws the part of the texture under `region` at the same place on screen.
+    fn textured_rect(
+        target: &mut Canvas,
+        texture: &Texture,
+        (x, y, width, height): (f32, f32, f32, f32),
+        color: Color,
+    ) {
+        let corners = [
+            (x, y),
+            (x + width, y),
+            (x + width, y + height),
+            (x, y + height),
+        ];
+        let vertices =
+            corners.map(|(x, y)| Vertex::new(Vector2f::new(x, y), color, Vector2f::new(x, y)));
+        target.draw_vertices(&vertices, PrimitiveType::QUADS, Some(texture));
+    }
+
+    fn iris(target: &mut Canvas, width: f32, height: f32, radius: f32, color: Color) {
+        let center = Vector2f::new(width / 2.0, height / 2.0);
+        let outer = width + height;
+
+        let vertices: Vec<Vertex> = (0..=IRIS_SEGMENTS)
+            .flat_map(|i| {
+                let angle = i as f32 / IRIS_SEGMENTS as f32 * 2.0 * PI;
+                let direction = Vector2f::new(angle.cos(), angle.sin());
+                [
+                    Vertex::with_pos_color(center + direction * radius, color),
+                    Vertex::with_pos_color(center + direction * outer, color),
+                ]
+            })
+            .collect();
+
+        target.draw_vertices(&vertices, PrimitiveType::TRIANGLE_STRIP, None);
+    }
+}
+pub(crate) use player::*;
@@ -0,0 +1,653 @@
+use std::{cell::RefCell, f32::consts::PI, rc::Rc, time::Duration};
+
+use crate::math::Lerp;
+
+mod easing {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, Default)]
+    pub enum Easing {
+        #[default]
+        Linear,
+        QuadIn,
+        QuadOut,
+        QuadInOut,
+        CubicIn,
+        CubicOut,
+        CubicInOut,
+        QuartIn,
+        QuartOut,
+        QuartInOut,
+        QuintIn,
+        QuintOut,
+        QuintInOut,
+        SineIn,
+        SineOut,
+        SineInOut,
+        ExpoIn,
+        ExpoOut,
+        ExpoInOut,
+        CircIn,
+        CircOut,
+        CircInOut,
+        BackIn,
+        BackOut,
+        BackInOut,
+        ElasticIn,
+        ElasticOut,
+        ElasticInOut,
+        BounceIn,
+        BounceOut,
+        BounceInOut,
+        Custom(fn(f32) -> f32),
+    }
+
+    const BACK: f32 = 1.70158;
+    const ELASTIC: f32 = 2.0 * PI / 3.0;
+    const ELASTIC_IN_OUT: f32 = 2.0 * PI / 4.5;
+
+    fn power_in(t: f32, power: i32) -> f32 {
+        t.powi(power)
+    }
+
+    fn power_out(t: f32, power: i32) -> f32 {
+        1.0 - (1.0 - t).powi(power)
+    }
+
+    fn power_in_out(t: f32, power: i32) -> f32 {
+        if t < 0.5 {
+            2f32.powi(power - 1) * t.powi(power)
+        } else {
+            1.0 - (-2.0 * t + 2.0).powi(power) / 2.0
+        }
+    }
+
+    fn bounce_out(t: f32) -> f32 {
+        const N: f32 = 7.5625;
+        const D: f32 = 2.75;
+
+        if t < 1.0 / D {
+            N * t * t
+        } else if t < 2.0 / D {
+            let t = t - 1.5 / D;
+            N * t * t + 0.75
+        } else if t < 2.5 / D {
+            let t = t - 2.25 / D;
+            N * t * t + 0.9375
+        } else {
+            let t = t - 2.625 / D;
+            N * t * t + 0.984375
+        }
+    }
+
+    impl Easing {
+        pub fn apply(self, t: f32) -> f32 {
+            let t = t.clamp(0.0, 1.0);
+
+            match self {
+                Easing::Linear => t,
+                Easing::QuadIn => power_in(t, 2),
+                Easing::QuadOut => power_out(t, 2),
+                Easing::QuadInOut => power_in_out(t, 2),
+                Easing::CubicIn => power_in(t, 3),
+                Easing::CubicOut => power_out(t, 3),
+                Easing::CubicInOut => power_in_out(t, 3),
+                Easing::QuartIn => power_in(t, 4),
+                Easing::QuartOut => power_out(t, 4),
+                Easing::QuartInOut => power_in_out(t, 4),
+                Easing::QuintIn => power_in(t, 5),
+                Easing::QuintOut => power_out(t, 5),
+                Easing::QuintInOut => power_in_out(t, 5),
+                Easing::SineIn => 1.0 - (t * PI / 2.0).cos(),
+                Easing::SineOut => (t * PI / 2.0).sin(),
+                Easing::SineInOut => -((PI * t).cos() - 1.0) / 2.0,
+                Easing::ExpoIn if t == 0.0 => 0.0,
+                Easing::ExpoIn => 2f32.powf(10.0 * t - 10.0),
+                Easing::ExpoOut if t == 1.0 => 1.0,
+                Easing::ExpoOut => 1.0 - 2f32.powf(-10.0 * t),
+                Easing::ExpoInOut if t == 0.0 || t == 1.0 => t,
+                Easing::ExpoInOut if t < 0.5 => 2f32.powf(20.0 * t - 10.0) / 2.0,
+                Easing::ExpoInOut => (2.0 - 2f32.powf(-20.0 * t + 10.0)) / 2.0,
+                Easing::CircIn => 1.0 - (1.0 - t * t).sqrt(),
+                Easing::CircOut => (1.0 - (t - 1.0).powi(2)).sqrt(),
+                Easing::CircInOut if t < 0.5 => (1.0 - (1.0 - (2.0 * t).powi(2)).sqrt()) / 2.0,
+                Easing::CircInOut => ((1.0 - (-2.0 * t + 2.0).powi(2)).sqrt() + 1.0) / 2.0,
+                Easing::BackIn => (BACK + 1.0) * t.powi(3) - BACK * t.powi(2),
+                Easing::BackOut => {
+                    1.0 + (BACK + 1.0) * (t - 1.0).powi(3) + BACK * (t - 1.0).powi(2)
+                }
+                Easing::BackInOut => {
+                    let c = BACK * 1.525;
+
+                    if t < 0.5 {
+                        (2.0 * t).powi(2) * ((c + 1.0) * 2.0 * t - c) / 2.0
+                    } else {
+                        ((2.0 * t - 2.0).powi(2) * ((c + 1.0) * (2.0 * t - 2.0) + c) + 2.0) / 2.0
+                    }
+                }
+                Easing::ElasticIn if t == 0.0 || t == 1.0 => t,
+                Easing::ElasticIn => {
+                    -(2f32.powf(10.0 * t - 10.0)) * ((10.0 * t - 10.75) * ELASTIC).sin()
+                }
+                Easing::ElasticOut if t == 0.0 || t == 1.0 => t,
+                Easing::ElasticOut => {
+                    2f32.powf(-10.0 * t) * ((10.0 * t - 0.75) * ELASTIC).sin() + 1.0
+                }
+                Easing::ElasticInOut if t == 0.0 || t == 1.0 => t,
+                Easing::ElasticInOut if t < 0.5 => {
+                    -(2f32.powf(20.0 * t - 10.0) * ((20.0 * t - 11.125) * ELASTIC_IN_OUT).sin())
+                        / 2.0
+                }
+                Easing::ElasticInOut => {
+                    2f32.powf(-20.0 * t + 10.0) * ((20.0 * t - 11.125) * ELASTIC_IN_OUT).sin() / 2.0
+                        + 1.0
+                }
+                Easing::BounceIn => 1.0 - bounce_out(1.0 - t),
+                Easing::BounceOut => bounce_out(t),
+                Easing::BounceInOut if t < 0.5 => (1.0 - bounce_out(1.0 - 2.0 * t)) / 2.0,
+                Easing::BounceInOut => (1.0 + bounce_out(2.0 * t - 1.0)) / 2.0,
+                Easing::Custom(function) => function(t),
+            }
+        }
+    }
+}
+pub use easing::*;
+
+mod animation {
+    pub trait Animation {
+        // Advances the animation and returns the part of `dt` left over after it finished.
+        fn advance(&mut self, dt: f32) -> f32;
+
+        fn is_finished(&self) -> bool;
+
+        fn restart(&mut self);
+
+        // Length in seconds including repeats, infinite when it repeats forever.
+        fn duration(&self) -> f32;
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Repeat {
+        Times(u32),
+        Forever,
+    }
+
+    // Length of `cycle` seconds played `repeat` times, a zero length cycle never repeats.
+    pub(super) fn repeated(cycle: f32, repeat: Repeat) -> f32 {
+        match repeat {
+            Repeat::Times(times) => cycle * (times + 1) as f32,
+            Repeat::Forever if cycle > 0.0 => f32::INFINITY,
+            Repeat::Forever => 0.0,
+        }
+    }
+
+    pub(super) struct Completion {
+        finished: bool,
+        callback: Option<Box<dyn FnMut()>>,
+    }
+
+    impl Completion {
+        pub(super) fn new() -> Self {
+            Completion {
+                finished: false,
+                callback: None,
+            }
+        }
+
+        pub(super) fn set_callback<F: FnMut() + 'static>(&mut self, callback: F) {
+            self.callback = Some(Box::new(callback));
+        }
+
+        pub(super) fn is_finished(&self) -> bool {
+            self.finished
+        }
+
+        pub(super) fn finish(&mut self) {
+            if !self.finished {
+                self.finished = true;
+
+                if let Some(callback) = &mut self.callback {
+                    callback();
+                }
+            }
+        }
+
+        pub(super) fn reset(&mut self) {
+            self.finished = false;
+        }
+    }
+}
+pub use animation::*;
+
+mod value {
+    use super::*;
+
+    pub struct Tweened<T>(Rc<RefCell<T>>);
+
+    impl<T> Clone for Tweened<T> {
+        fn clone(&self) -> Self {
+            Tweened(Rc::clone(&self.0))
+        }
+    }
+
+    impl<T: Clone> Tweened<T> {
+        pub fn get(&self) -> T {
+            self.0.borrow().clone()
+        }
+
+        fn set(&self, value: T) {
+            *self.0.borrow_mut() = value;
+        }
+    }
+
+    pub struct Tween<T: Lerp> {
+        from: T,
+        to: T,
+        duration: f32,
+        time: f32,
+        easing: Easing,
+        repeat: Repeat,
+        yoyo: bool,
+        output: Tweened<T>,
+        completion: Completion,
+    }
+
+    impl<T: Lerp> Tween<T> {
+        pub fn new(from: T, to: T, duration: Duration) -> Self {
+            Tween {
+                output: Tweened(Rc::new(RefCell::new(from.clone()))),
+                from,
+                to,
+                duration: duration.as_secs_f32(),
+                time: 0.0,
+                easing: Easing::Linear,
+                repeat: Repeat::Times(0),
+                yoyo: false,
+                completion: Completion::new(),
+            }
+        }
+
+        pub fn ease(mut self, easing: Easing) -> Self {
+            self.easing = easing;
+            self.apply();
+            self
+        }
+
+        pub fn repeat(mut self, times: u32) -> Self {
+            self.repeat = Repeat::Times(times);
+            self
+        }
+
+        pub fn repeat_forever(mut self) -> Self {
+            self.repeat = Repeat::Forever;
+            self
+        }
+
+        pub fn yoyo(mut self) -> Self {
+            self.yoyo = true;
+            self
+        }
+
+        pub fn on_complete<F: FnMut() + 'static>(mut self, callback: F) -> Self {
+            self.completion.set_callback(callback);
+            self
+        }
+
+        pub fn output(&self) -> Tweened<T> {
+            self.output.clone()
+        }
+
+        pub fn value(&self) -> T {
+            self.output.get()
+        }
+
+        pub fn update(&mut self, frame_time: Duration) {
+            self.advance(frame_time.as_secs_f32());
+        }
+
+        fn cycle(&self) -> f32 {
+            if self.yoyo {
+                self.duration * 2.0
+            } else {
+                self.duration
+            }
+        }
+
+        pub fn progress(&self) -> f32 {
+            let cycle = self.cycle();
+
+            if cycle <= 0.0 {
+                return 1.0;
+            }
+
+            let local = match self.repeat {
+                Repeat::Times(times) if self.time >= cycle * (times + 1) as f32 => cycle,
+                _ => self.time % cycle,
+            };
+
+            let t = local / self.duration;
+
+            if t > 1.0 {
+                2.0 - t
+            } else {
+                t
+            }
+        }
+
+        fn apply(&self) {
+            let t = self.easing.apply(self.progress());
+            self.output.set(self.from.lerp(&self.to, t));
+        }
+    }
+
+    impl<T: Lerp> Animation for Tween<T> {
+        fn advance(&mut self, dt: f32) -> f32 {
+            if self.completion.is_finished() {
+                return dt;
+            }
+
+            self.time += dt;
+            self.apply();
+
+            let total = match self.repeat {
+                Repeat::Times(times) => self.cycle() * (times + 1) as f32,
+                Repeat::Forever if self.cycle() > 0.0 => return 0.0,
+                Repeat::Forever => 0.0,
+            };
+
+            if self.time >= total {
+                let leftover = self.time - total;
+                self.time = total;
+                self.completion.finish();
+                leftover
+            } else {
+                0.0
+            }
+        }
+
+        fn duration(&self) -> f32 {
+            repeated(self.cycle(), self.repeat)
+        }
+
+        fn is_finished(&self) -> bool {
+            self.completion.is_finished()
+        }
+
+        fn restart(&mut self) {
+            self.time = 0.0;
+            self.completion.reset();
+            self.apply();
+        }
+    }
+}
+pub use value::*;
+
+mod group {
+    use super::*;
+
+    pub struct Sequence {
+        animations: Vec<Box<dyn Animation>>,
+        current: usize,
+        repeat: Repeat,
+        cycles: u32,
+        completion: Completion,
+    }
+
+    impl Default for Sequence {
+        fn default() -> Self {
+            Sequence::new()
+        }
+    }
+
+    impl Sequence {
+        pub fn new() -> Self {
+            Sequence {
+                animations: Vec::new(),
+                current: 0,
+                repeat: Repeat::Times(0),
+                cycles: 0,
+                completion: Completion::new(),
+            }
+        }
+
+        pub fn then<A: Animation + 'static>(mut self, animation: A) -> Self {
+            self.animations.push(Box::new(animation));
+            self
+        }
+
+        pub fn repeat(mut self, times: u32) -> Self {
+            self.repeat = Repeat::Times(times);
+            self
+        }
+
+        pub fn repeat_forever(mut self) -> Self {
+            self.repeat = Repeat::Forever;
+            self
+        }
+
+        pub fn on_complete<F: FnMut() + 'static>(mut self, callback: F) -> Self {
+            self.completion.set_callback(callback);
+            self
+        }
+
+        pub fn update(&mut self, frame_time: Duration) {
+            self.advance(frame_time.as_secs_f32());
+        }
+
+        fn cycle_duration(&self) -> f32 {
+            self.animations.iter().map(|a| a.duration()).sum()
+        }
+    }
+
+    impl Animation for Sequence {
+        fn advance(&mut self, mut dt: f32) -> f32 {
+            if self.completion.is_finished() {
+                return dt;
+            }
+
+            loop {
+                while let Some(animation) = self.animations.get_mut(self.current) {
+                    dt = animation.advance(dt);
+
+                    if !animation.is_finished() {
+                        return 0.0;
+                    }
+
+                    self.current += 1;
+                }
+
+                let again = match self.repeat {
+                    Repeat::Times(times) => self.cycles < times,
+                    // Every cycle uses up some time unless the sequence is empty or made of
+                    // zero length animations, which would otherwise restart within this frame
+                    // forever.
+                    Repeat::Forever => self.cycle_duration() > 0.0,
+                };
+
+                if !again {
+                    break;
+                }
+
+                self.cycles = self.cycles.saturating_add(1);
+                self.current = 0;
+                self.animations.iter_mut().for_each(|a| a.restart());
+            }
+
+            self.completion.finish();
+            dt
+        }
+
+        fn is_finished(&self) -> bool {
+            self.completion.is_finished()
+        }
+
+        fn restart(&mut self) {
+            self.current = 0;
+            self.cycles = 0;
+            self.completion.reset();
+            self.animations.iter_mut().for_each(|a| a.restart());
+        }
+
+        fn duration(&self) -> f32 {
+            repeated(self.cycle_duration(), self.repeat)
+        }
+    }
+
+    pub struct Parallel {
+        animations: Vec<Box<dyn Animation>>,
+        repeat: Repeat,
+        cycles: u32,
+        completion: Completion,
+    }
+
+    impl Default for Parallel {
+        fn default() -> Self {
+            Parallel::new()
+        }
+    }
+
+    impl Parallel {
+        pub fn new() -> Self {
+            Parallel {
+                animations: Vec::new(),
+                repeat: Repeat::Times(0),
+                cycles: 0,
+                completion: Completion::new(),
+            }
+        }
+
+        pub fn with<A: Animation + 'static>(mut self, animation: A) -> Self {
+            self.animations.push(Box::new(animation));
+            self
+        }
+
+        pub fn repeat(mut self, times: u32) -> Self {
+            self.repeat = Repeat::Times(times);
+            self
+        }
+
+        pub fn repeat_forever(mut self) -> Self {
+            self.repeat = Repeat::Forever;
+            self
+        }
+
+        pub fn on_complete<F: FnMut() + 'static>(mut self, callback: F) -> Self {
+            self.completion.set_callback(callback);
+            self
+        }
+
+        pub fn update(&mut self, frame_time: Duration) {
+            self.advance(frame_time.as_secs_f32());
+        }
+    }
+
+    impl Animation for Parallel {
+        fn advance(&mut self, dt: f32) -> f32 {
+            if self.completion.is_finished() {
+                return dt;
+            }
+
+            let leftover = self
+                .animations
+                .iter_mut()
+                .map(|animation| animation.advance(dt))
+                .fold(dt, f32::min);
+
+            if !self.animations.iter().all(|a| a.is_finished()) {
+                return 0.0;
+            }
+
+            let again = match self.repeat {
+                Repeat::Times(times) => self.cycles < times,
+                Repeat::Forever => true,
+            };
+
+            if again {
+                self.cycles += 1;
+                self.animations.iter_mut().for_each(|a| a.restart());
+                return 0.0;
+            }
+
+            self.completion.finish();
+            leftover
+        }
+
+        fn is_finished(&self) -> bool {
+            self.completion.is_finished()
+        }
+
+        fn restart(&mut self) {
+            self.cycles = 0;
+            self.completion.reset();
+            self.animations.iter_mut().for_each(|a| a.restart());
+        }
+
+        fn duration(&self) -> f32 {
+            let cycle = self
+                .animations
+                .iter()
+                .map(|a| a.duration())
+                .fold(0.0, f32::max);
+            repeated(cycle, self.repeat)
+        }
+    }
+}
+pub use group::*;
+
+mod manager {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct TweenId(u64);
+
+    #[derive(Default)]
+    pub struct TweenManager {
+        animations: Vec<(TweenId, Box<dyn Animation>)>,
+        next_id: u64,
+    }
+
+    impl TweenManager {
+        pub fn new() -> Self {
+            TweenManager::default()
+        }
+
+        pub fn add<A: Animation + 'static>(&mut self, animation: A) -> TweenId {
+            let id = TweenId(self.next_id);
+            self.next_id += 1;
+            self.animations.push((id, Box::new(animation)));
+            id
+        }
+
+        pub fn cancel(&mut self, id: TweenId) {
+            self.animations.retain(|(other, _)| *other != id);
+        }
+
+        pub fn is_running(&self, id: TweenId) -> bool {
+            self.animations.iter().any(|(other, _)| *other == id)
+        }
+
+        pub fn len(&self) -> usize {
+            self.animations.len()
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.animations.is_empty()
+        }
+
+        pub fn clear(&mut self) {
+            self.animations.clear();
+        }
+
+        pub fn update(&mut self, frame_time: Duration) {
+            let dt = frame_time.as_secs_f32();
+
+            for (_, animation) in self.animations.iter_mut() {
+                animation.advance(dt);
+            }
+
+            self.animations
+                .retain(|(_, animation)| !animation.is_finished());
+        }
+    }
+}
+pub use manager::*;
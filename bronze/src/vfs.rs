@@ -0,0 +1,534 @@
+use std::{
+    collections::HashMap,
+    fmt, fs,
+    io::{Read, Seek, SeekFrom, Write},
+    path::{Component, Path, PathBuf},
+    sync::Arc,
+};
+
+use flate2::{read::DeflateDecoder, write::DeflateEncoder, Compression};
+
+use crate::resources::ResourceError;
+
+// Archive and embedded entries are keyed by their relative path with `/` separators, so
+// `./images/../images/ball.png` and `images\ball.png` on Windows find the same entry.
+fn normalize(path: &Path) -> String {
+    let mut parts: Vec<&str> = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => parts.push(part.to_str().unwrap_or_default()),
+            Component::ParentDir => {
+                parts.pop();
+            }
+            _ => {}
+        }
+    }
+    parts.join("/")
+}
+
+mod source {
+    use super::*;
+
+    // Anything files can be read from. Sources are shared with the loading threads, so they
+    // must be `Send` and `Sync`.
+    pub trait Source: Send + Sync {
+        fn read(&self, path: &Path) -> Option<Vec<u8>>;
+
+        fn size(&self, path: &Path) -> Option<u64>;
+    }
+
+    // Loose files under a directory on disk.
+    pub struct Directory {
+        root: PathBuf,
+    }
+
+    impl Directory {
+        pub fn new<P: Into<PathBuf>>(root: P) -> Self {
+            Directory { root: root.into() }
+        }
+    }
+
+    impl Source for Directory {
+        fn read(&self, path: &Path) -> Option<Vec<u8>> {
+            fs::read(self.root.join(path)).ok()
+        }
+
+        fn size(&self, path: &Path) -> Option<u64> {
+            let metadata = fs::metadata(self.root.join(path)).ok()?;
+            metadata.is_file().then_some(metadata.len())
+        }
+    }
+
+    // Files compiled into the executable with `include_bytes!`.
+    #[derive(Default)]
+    pub struct Embedded {
+        files: HashMap<String, &'static [u8]>,
+    }
+
+    impl Embedded {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        pub fn with_file(mut self, path: &str, bytes: &'static [u8]) -> Self {
+            self.files.insert(normalize(Path::new(path)), bytes);
+            self
+        }
+    }
+
+    impl Source for Embedded {
+        fn read(&self, path: &Path) -> Option<Vec<u8>> {
+            self.files.get(&normalize(path)).map(|bytes| bytes.to_vec())
+        }
+
+        fn size(&self, path: &Path) -> Option<u64> {
+            self.files
+                .get(&normalize(path))
+                .map(|bytes| bytes.len() as u64)
+        }
+    }
+}
+pub use source::*;
+
+mod archive {
+    use super::*;
+
+    const MAGIC: &[u8; 4] = b"BRZA";
+    const VERSION: u32 = 1;
+    // Magic, version and index length.
+    const HEADER_LEN: u64 = 16;
+
+    const STORED: u8 = 0;
+    const DEFLATE: u8 = 1;
+
+    // Name length, offset, stored length, size and compression of an entry with an empty name.
+    const MIN_ENTRY_LEN: usize = 27;
+    // Deflate can't shrink data by more than about this much.
+    const MAX_DEFLATE_RATIO: u64 = 1032;
+
+    struct Entry {
+        offset: u64,
+        stored_len: u64,
+        size: u64,
+        compression: u8,
+    }
+
+    enum Storage {
+        File(PathBuf),
+        Static(&'static [u8]),
+    }
+
+    // A bronze archive is a header, an index of every entry and then the entry blobs:
+    //
+    //     "BRZA" version:u32 index_len:u64
+    //     count:u32 { name_len:u16 name offset:u64 stored_len:u64 size:u64 compression:u8 }
+    //     blobs
+    //
+    // Integers are little endian and offsets start after the index. Blobs are deflated unless
+    // that would not make them smaller, as with PNG and JPEG files.
+    pub struct Archive {
+        storage: Storage,
+        data_start: u64,
+        entries: HashMap<String, Entry>,
+    }
+
+    impl Archive {
+        // Only the index is read up front, entries are read from the file as they are needed.
+        pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, ResourceError> {
+            let path = path.as_ref();
+            let name = path.to_string_lossy();
+            let mut file = fs::File::open(path).map_err(|_| ResourceError::not_found(&name))?;
+            let file_len = file
+                .metadata()
+                .map_err(|_| ResourceError::not_found(&name))?
+                .len();
+
+            let mut header = [0; HEADER_LEN as usize];
+            file.read_exact(&mut header)
+                .map_err(|_| ResourceError::invalid(&name, "not a bronze archive"))?;
+            let index_len = parse_header(&header).map_err(|error| error.at(&name))?;
+            let data_start = data_start(index_len, file_len).map_err(|error| error.at(&name))?;
+
+            let mut index = vec![0; index_len as usize];
+            file.read_exact(&mut index)
+                .map_err(|_| ResourceError::invalid(&name, "truncated archive index"))?;
+            let entries =
+                parse_index(&index, file_len - data_start).map_err(|error| error.at(&name))?;
+
+            Ok(Archive {
+                storage: Storage::File(path.to_path_buf()),
+                data_start,
+                entries,
+            })
+        }
+
+        // For an archive compiled in with `include_bytes!`.
+        pub fn from_static(bytes: &'static [u8]) -> Result<Self, ResourceError> {
+            let name = "<embedded archive>";
+            let header = bytes
+                .get(..HEADER_LEN as usize)
+                .ok_or_else(|| ResourceError::invalid(name, "not a bronze archive"))?;
+            let index_len = parse_header(header).map_err(|error| error.at(name))?;
+            let total_len = bytes.len() as u64;
+            let data_start = data_start(index_len, total_len).map_err(|error| error.at(name))?;
+
+            let index = &bytes[HEADER_LEN as usize..data_start as usize];
+            let entries =
+                parse_index(index, total_len - data_start).map_err(|error| error.at(name))?;
+
+            Ok(Archive {
+                storage: Storage::Static(bytes),
+                data_start,
+                entries,
+            })
+        }
+
+        #[inline]
+        pub fn len(&self) -> usize {
+            self.entries.len()
+        }
+
+        #[inline]
+        pub fn is_empty(&self) -> bool {
+            self.entries.is_empty()
+        }
+
+        pub fn contains(&self, path: &str) -> bool {
+            self.entries.contains_key(&normalize(Path::new(path)))
+        }
+
+        pub fn names(&self) -> impl Iterator<Item = &str> {
+            self.entries.keys().map(String::as_str)
+        }
+
+        // Entries were checked against the archive length when the index was parsed.
+        fn read_stored(&self, entry: &Entry) -> Option<Vec<u8>> {
+            let start = self.data_start + entry.offset;
+
+            match &self.storage {
+                Storage::File(path) => {
+                    let mut file = fs::File::open(path).ok()?;
+                    file.seek(SeekFrom::Start(start)).ok()?;
+                    let mut stored = vec![0; entry.stored_len as usize];
+                    file.read_exact(&mut stored).ok()?;
+                    Some(stored)
+                }
+                Storage::Static(bytes) => bytes
+                    .get(start as usize..(start + entry.stored_len) as usize)
+                    .map(<[u8]>::to_vec),
+            }
+        }
+    }
+
+    impl Source for Archive {
+        fn read(&self, path: &Path) -> Option<Vec<u8>> {
+            let entry = self.entries.get(&normalize(path))?;
+            let stored = self.read_stored(entry)?;
+
+            match entry.compression {
+                DEFLATE => {
+                    let capacity = entry.size.min(stored.len() as u64 * MAX_DEFLATE_RATIO);
+                    let mut bytes = Vec::with_capacity(capacity as usize);
+                    DeflateDecoder::new(stored.as_slice())
+                        .read_to_end(&mut bytes)
+                        .ok()?;
+                    Some(bytes)
+                }
+                _ => Some(stored),
+            }
+        }
+
+        fn size(&self, path: &Path) -> Option<u64> {
+            self.entries.get(&normalize(path)).map(|entry| entry.size)
+        }
+    }
+
+    // Parse errors without a path, the caller knows which archive it was reading.
+    struct FormatError(&'static str);
+
+    impl FormatError {
+        fn at(self, path: &str) -> ResourceError {
+            ResourceError::invalid(path, self.0)
+        }
+    }
+
+    fn parse_header(header: &[u8]) -> Result<u64, FormatError> {
+        let mut reader = Reader(header);
+        if reader.bytes(4)? != MAGIC {
+            return Err(FormatError("not a bronze archive"));
+        }
+        if reader.u32()? != VERSION {
+            return Err(FormatError("unsupported archive version"));
+        }
+        reader.u64()
+    }
+
+    // Where the entry blobs start, making sure the index fits in the archive.
+    fn data_start(index_len: u64, total_len: u64) -> Result<u64, FormatError> {
+        HEADER_LEN
+            .checked_add(index_len)
+            .filter(|&start| start <= total_len)
+            .ok_or(FormatError("truncated archive index"))
+    }
+
+    fn parse_index(index: &[u8], data_len: u64) -> Result<HashMap<String, Entry>, FormatError> {
+        let mut reader = Reader(index);
+        let count = reader.u32()?;
+
+        let capacity = (count as usize).min(index.len() / MIN_ENTRY_LEN);
+        let mut entries = HashMap::with_capacity(capacity);
+        for _ in 0..count {
+            let name_len = reader.u16()? as usize;
+            let name = std::str::from_utf8(reader.bytes(name_len)?)
+                .map_err(|_| FormatError("entry name is not valid UTF-8"))?;
+
+            let entry = Entry {
+                offset: reader.u64()?,
+                stored_len: reader.u64()?,
+                size: reader.u64()?,
+                compression: reader.bytes(1)?[0],
+            };
+            if entry.compression != STORED && entry.compression != DEFLATE {
+                return Err(FormatError("unknown entry compression"));
+            }
+            let end = entry
+                .offset
+                .checked_add(entry.stored_len)
+                .filter(|&end| end <= data_len);
+            if end.is_none() {
+                return Err(FormatError("archive entry is out of bounds"));
+            }
+
+            entries.insert(name.to_string(), entry);
+        }
+
+        Ok(entries)
+    }
+
+    struct Reader<'a>(&'a [u8]);
+
+    impl<'a> Reader<'a> {
+        fn bytes(&mut self, len: usize) -> Result<&'a [u8], FormatError> {
+            if self.0.len() < len {
+                return Err(FormatError("truncated archive index"));
+            }
+            let (bytes, rest) = self.0.split_at(len);
+            self.0 = rest;
+            Ok(bytes)
+        }
+
+        fn u16(&mut self) -> Result<u16, FormatError> {
+            Ok(u16::from_le_bytes(self.bytes(2)?.try_into().unwrap()))
+        }
+
+        fn u32(&mut self) -> Result<u32, FormatError> {
+            Ok(u32::from_le_bytes(self.bytes(4)?.try_into().unwrap()))
+        }
+
+        fn u64(&mut self) -> Result<u64, FormatError> {
+            Ok(u64::from_le_bytes(self.bytes(8)?.try_into().unwrap()))
+        }
+    }
+
+    struct PackedEntry {
+        name: String,
+        stored: Vec<u8>,
+        size: u64,
+        compression: u8,
+    }
+
+    #[derive(Default)]
+    pub struct ArchiveWriter {
+        entries: Vec<PackedEntry>,
+    }
+
+    impl ArchiveWriter {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        #[inline]
+        pub fn len(&self) -> usize {
+            self.entries.len()
+        }
+
+        #[inline]
+        pub fn is_empty(&self) -> bool {
+            self.entries.is_empty()
+        }
+
+        // Returns the stored size of the entry, replacing an earlier entry with the same path.
+        pub fn add(&mut self, path: &str, bytes: &[u8]) -> u64 {
+            let name = normalize(Path::new(path));
+
+            let mut encoder = DeflateEncoder::new(Vec::new(), Compression::best());
+            let deflated = encoder
+                .write_all(bytes)
+                .and_then(|_| encoder.finish())
+                .ok()
+                .filter(|deflated| deflated.len() < bytes.len());
+
+            let entry = match deflated {
+                Some(stored) => PackedEntry {
+                    name,
+                    stored,
+                    size: bytes.len() as u64,
+                    compression: DEFLATE,
+                },
+                None => PackedEntry {
+                    name,
+                    stored: bytes.to_vec(),
+                    size: bytes.len() as u64,
+                    compression: STORED,
+                },
+            };
+            let stored_len = entry.stored.len() as u64;
+
+            self.entries.retain(|other| other.name != entry.name);
+            self.entries.push(entry);
+
+            stored_len
+        }
+
+        // Adds every file under `directory`, named by its path relative to `directory`.
+        pub fn add_dir<P: AsRef<Path>>(&mut self, directory: P) -> std::io::Result<usize> {
+            let directory = directory.as_ref();
+
+            let mut files = Vec::new();
+            collect_files(directory, &mut files)?;
+            files.sort();
+
+            for file in &files {
+                let bytes = fs::read(file)?;
+                let name = file.strip_prefix(directory).unwrap_or(file);
+                self.add(&name.to_string_lossy(), &bytes);
+            }
+
+            Ok(files.len())
+        }
+
+        pub fn to_bytes(&self) -> Vec<u8> {
+            let mut index = Vec::new();
+            index.extend_from_slice(&(self.entries.len() as u32).to_le_bytes());
+
+            let mut offset = 0u64;
+            for entry in &self.entries {
+                index.extend_from_slice(&(entry.name.len() as u16).to_le_bytes());
+                index.extend_from_slice(entry.name.as_bytes());
+                index.extend_from_slice(&offset.to_le_bytes());
+                index.extend_from_slice(&(entry.stored.len() as u64).to_le_bytes());
+                index.extend_from_slice(&entry.size.to_le_bytes());
+                index.push(entry.compression);
+                offset += entry.stored.len() as u64;
+            }
+
+            let mut bytes = Vec::with_capacity(HEADER_LEN as usize + index.len() + offset as usize);
+            bytes.extend_from_slice(MAGIC);
+            bytes.extend_from_slice(&VERSION.to_le_bytes());
+            bytes.extend_from_slice(&(index.len() as u64).to_le_bytes());
+            bytes.extend_from_slice(&index);
+            for entry in &self.entries {
+                bytes.extend_from_slice(&entry.stored);
+            }
+
+            bytes
+        }
+
+        pub fn save<P: AsRef<Path>>(&self, path: P) -> bool {
+            fs::write(path, self.to_bytes()).is_ok()
+        }
+    }
+
+    fn collect_files(directory: &Path, files: &mut Vec<PathBuf>) -> std::io::Result<()> {
+        for entry in fs::read_dir(directory)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                collect_files(&path, files)?;
+            } else {
+                files.push(path);
+            }
+        }
+        Ok(())
+    }
+}
+pub use archive::*;
+
+mod file_system {
+    use super::*;
+
+    // Reads files from a stack of sources, the first source that has a file wins. A `Vfs`
+    // without sources reads paths straight from the file system.
+    #[derive(Clone, Default)]
+    pub struct Vfs {
+        sources: Vec<Arc<dyn Source>>,
+    }
+
+    impl Vfs {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        pub fn with_source<S: Source + 'static>(mut self, source: S) -> Self {
+            self.sources.push(Arc::new(source));
+            self
+        }
+
+        #[inline]
+        pub fn with_dir<P: Into<PathBuf>>(self, root: P) -> Self {
+            self.with_source(Directory::new(root))
+        }
+
+        #[inline]
+        pub fn with_archive(self, archive: Archive) -> Self {
+            self.with_source(archive)
+        }
+
+        #[inline]
+        pub fn with_embedded(self, embedded: Embedded) -> Self {
+            self.with_source(embedded)
+        }
+
+        pub fn read<P: AsRef<Path>>(&self, path: P) -> Result<Vec<u8>, ResourceError> {
+            let path = path.as_ref();
+
+            let bytes = if self.sources.is_empty() {
+                fs::read(path).ok()
+            } else {
+                self.sources.iter().find_map(|source| source.read(path))
+            };
+
+            bytes.ok_or_else(|| ResourceError::not_found(&path.to_string_lossy()))
+        }
+
+        pub fn read_to_string<P: AsRef<Path>>(&self, path: P) -> Result<String, ResourceError> {
+            let path = path.as_ref();
+            String::from_utf8(self.read(path)?)
+                .map_err(|_| ResourceError::decode(&path.to_string_lossy()))
+        }
+
+        pub fn size<P: AsRef<Path>>(&self, path: P) -> Option<u64> {
+            let path = path.as_ref();
+
+            if self.sources.is_empty() {
+                let metadata = fs::metadata(path).ok()?;
+                metadata.is_file().then_some(metadata.len())
+            } else {
+                self.sources.iter().find_map(|source| source.size(path))
+            }
+        }
+
+        #[inline]
+        pub fn exists<P: AsRef<Path>>(&self, path: P) -> bool {
+            self.size(path).is_some()
+        }
+    }
+
+    impl fmt::Debug for Vfs {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.debug_struct("Vfs")
+                .field("sources", &self.sources.len())
+                .finish()
+        }
+    }
+}
+pub use file_system::*;
@@ -1,10 +1,20 @@
+use std::time::Duration;
+
 use sfml::{
-    graphics::{Color, Drawable, RenderTarget, RenderWindow},
-    system::Vector2u,
+    graphics::{
+        Color, Drawable, PrimitiveType, RenderStates, RenderTarget, RenderTexture, RenderWindow,
+        Sprite as SfmlSprite, Texture, Transformable, Vertex,
+    },
+    system::{Vector2i, Vector2u},
     window::{Event, Style, VideoMode},
+    SfBox,
 };
 
-use crate::resources::{Cursor, Icon};
+use crate::{
+    graphics::{DrawArgs, Sprite},
+    math::Transform2D,
+    resources::{AnimatedCursor, Cursor, Icon, Image, SystemCursor},
+};
 
 pub enum FPSConfig {
     VSync,
@@ -56,19 +66,92 @@ impl Default for WindowConfig {
     }
 }
 
+fn copy_window(window: &RenderWindow) -> Option<SfBox<Texture>> {
+    let size = window.size();
+    let mut texture = Texture::new()?;
+
+    if !texture.create(size.x, size.y) {
+        return None;
+    }
+
+    unsafe {
+        texture.update_from_render_window(window, 0, 0);
+    }
+
+    Some(texture)
+}
+
+enum Target<'w> {
+    Window(&'w mut RenderWindow),
+    Texture(&'w mut RenderTexture),
+}
+
 pub struct Canvas<'w> {
-    target: &'w mut RenderWindow,
+    target: Target<'w>,
 }
 
 impl Canvas<'_> {
+    #[inline]
+    fn target(&mut self) -> &mut dyn RenderTarget {
+        match &mut self.target {
+            Target::Window(window) => *window,
+            Target::Texture(texture) => *texture,
+        }
+    }
+
+    #[inline]
+    pub fn size(&self) -> Vector2u {
+        match &self.target {
+            Target::Window(window) => window.size(),
+            Target::Texture(texture) => texture.size(),
+        }
+    }
+
+    pub fn snapshot(&self) -> Option<SfBox<Texture>> {
+        match &self.target {
+            Target::Window(window) => copy_window(window),
+            Target::Texture(texture) => {
+                texture.display();
+                Some(texture.texture().to_owned())
+            }
+        }
+    }
+
     pub fn draw<D: Drawable>(&mut self, drawable: &D) {
-        self.target.draw(drawable)
+        self.target().draw(drawable)
+    }
+
+    pub fn draw_vertices(
+        &mut self,
+        vertices: &[Vertex],
+        primitive: PrimitiveType,
+        texture: Option<&Texture>,
+    ) {
+        let mut states = RenderStates::default();
+        states.set_texture(texture);
+        self.target().draw_primitives(vertices, primitive, &states);
+    }
+
+    pub fn draw_vertices_transformed(
+        &mut self,
+        vertices: &[Vertex],
+        primitive: PrimitiveType,
+        texture: Option<&Texture>,
+        transform: &Transform2D,
+    ) {
+        let mut states = RenderStates::default();
+        states.set_texture(texture);
+        states.transform = (*transform).into();
+        self.target().draw_primitives(vertices, primitive, &states);
     }
 }
 
 pub struct Window {
     sfml_window: RenderWindow,
     config: WindowConfig,
+    animated_cursor: Option<AnimatedCursor>,
+    software_cursor: bool,
+    mouse_inside: bool,
 }
 
 impl Window {
@@ -90,29 +173,131 @@ impl Window {
             }
         }
 
-        if let Some(cursor) = &config.cursor {
-            unsafe {
-                sfml_window.set_mouse_cursor(cursor.cursor());
-            }
-        }
-
-        sfml_window.set_mouse_cursor_visible(config.show_cursor);
-
         sfml_window.set_vertical_sync_enabled(matches!(config.fps_config, FPSConfig::VSync));
         sfml_window.set_framerate_limit(match config.fps_config {
             FPSConfig::Limited(limit) => limit,
             _ => 0,
         });
 
-        Window {
+        let mut window = Window {
             sfml_window,
             config,
-        }
+            animated_cursor: None,
+            software_cursor: false,
+            mouse_inside: true,
+        };
+        window.apply_cursor();
+
+        window
     }
 
     pub fn show_cursor(&mut self, show: bool) {
         self.config.show_cursor = show;
-        self.sfml_window.set_mouse_cursor_visible(show);
+        self.apply_cursor();
+    }
+
+    fn current_cursor(&self) -> Option<&Cursor> {
+        match &self.animated_cursor {
+            Some(animated) => Some(animated.current()),
+            None => self.config.cursor.as_ref(),
+        }
+    }
+
+    // Image cursors are drawn by the window when asked to, or when the platform couldn't
+    // create them.
+    fn draws_cursor(&self) -> bool {
+        self.current_cursor().is_some_and(|cursor| {
+            cursor.image().is_some() && (self.software_cursor || cursor.cursor().is_none())
+        })
+    }
+
+    fn apply_cursor(&mut self) {
+        let software = self.draws_cursor();
+
+        let cursor = match &self.animated_cursor {
+            Some(animated) => Some(animated.current()),
+            None => self.config.cursor.as_ref(),
+        };
+        if let Some(cursor) = cursor.and_then(Cursor::cursor).filter(|_| !software) {
+            // SAFETY: the cursor is owned by the window, and replaced cursors are only dropped
+            // after the new one is set.
+            unsafe { self.sfml_window.set_mouse_cursor(cursor) };
+        }
+
+        self.sfml_window
+            .set_mouse_cursor_visible(self.config.show_cursor && !software);
+    }
+
+    pub fn set_cursor(&mut self, cursor: Cursor) {
+        let _animated = self.animated_cursor.take();
+        let _previous = self.config.cursor.replace(cursor);
+        self.apply_cursor();
+    }
+
+    // Returns `false`, keeping the current cursor, when the platform has no such cursor.
+    pub fn set_system_cursor(&mut self, kind: SystemCursor) -> bool {
+        match Cursor::system(kind) {
+            Some(cursor) => {
+                self.set_cursor(cursor);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn set_animated_cursor(&mut self, cursor: AnimatedCursor) {
+        let _previous = self.animated_cursor.replace(cursor);
+        self.apply_cursor();
+    }
+
+    pub fn cursor(&self) -> Option<&Cursor> {
+        self.current_cursor()
+    }
+
+    // Hides the system cursor and draws image cursors on top of every frame instead, which
+    // also keeps them in sync with the rendered frame.
+    pub fn set_software_cursor(&mut self, software: bool) {
+        self.software_cursor = software;
+        self.apply_cursor();
+    }
+
+    #[inline]
+    pub fn is_software_cursor(&self) -> bool {
+        self.software_cursor
+    }
+
+    pub fn update_cursor(&mut self, frame_time: Duration) {
+        let changed = match &mut self.animated_cursor {
+            Some(animated) => animated.update(frame_time),
+            None => false,
+        };
+
+        if changed {
+            self.apply_cursor();
+        }
+    }
+
+    pub fn draw_cursor(&mut self, mouse: Vector2i) {
+        if !self.config.show_cursor || !self.mouse_inside || !self.draws_cursor() {
+            return;
+        }
+
+        let cursor = match &self.animated_cursor {
+            Some(animated) => animated.current(),
+            None => match &self.config.cursor {
+                Some(cursor) => cursor,
+                None => return,
+            },
+        };
+        let Some(image) = cursor.image() else { return };
+        let hotspot = cursor.hotspot();
+
+        let mut sprite_data = SfmlSprite::with_texture(image.texture());
+        sprite_data.set_position((
+            (mouse.x - hotspot.x as i32) as f32,
+            (mouse.y - hotspot.y as i32) as f32,
+        ));
+        self.sfml_window.draw(&sprite_data);
     }
 
     pub fn is_open(&self) -> bool {
@@ -124,7 +309,15 @@ impl Window {
     }
 
     pub fn poll_event(&mut self) -> Option<Event> {
-        self.sfml_window.poll_event()
+        let event = self.sfml_window.poll_event();
+
+        match event {
+            Some(Event::MouseEntered) => self.mouse_inside = true,
+            Some(Event::MouseLeft) => self.mouse_inside = false,
+            _ => {}
+        }
+
+        event
     }
 
     pub fn clear(&mut self) {
@@ -155,9 +348,83 @@ impl Window {
         self.height() as f32 / 2.0
     }
 
+    pub fn capture(&self) -> Option<Image> {
+        let texture = copy_window(&self.sfml_window)?;
+        Image::from_texture(&texture)
+    }
+
+    // Saves the window contents without uploading them to a texture again, as `capture`
+    // does, for screenshots and recordings.
+    pub(crate) fn save_capture(&self, path: &str) -> bool {
+        copy_window(&self.sfml_window)
+            .and_then(|texture| texture.copy_to_image())
+            .is_some_and(|image| image.save_to_file(path))
+    }
+
     pub fn canvas(&mut self) -> Canvas {
         Canvas {
-            target: &mut self.sfml_window,
+            target: Target::Window(&mut self.sfml_window),
         }
     }
 }
+
+pub struct RenderCanvas {
+    texture: RenderTexture,
+}
+
+impl RenderCanvas {
+    pub fn new(width: u32, height: u32) -> Option<Self> {
+        Some(RenderCanvas {
+            texture: RenderTexture::new(width, height)?,
+        })
+    }
+
+    pub fn width(&self) -> u32 {
+        self.texture.size().x
+    }
+
+    pub fn height(&self) -> u32 {
+        self.texture.size().y
+    }
+
+    pub fn set_smooth(&mut self, smooth: bool) {
+        self.texture.set_smooth(smooth);
+    }
+
+    pub fn clear(&mut self, color: Color) {
+        self.texture.clear(color);
+    }
+
+    pub fn canvas(&mut self) -> Canvas<'_> {
+        Canvas {
+            target: Target::Texture(&mut self.texture),
+        }
+    }
+
+    pub fn display(&mut self) {
+        self.texture.display();
+    }
+
+    pub fn texture(&self) -> &Texture {
+        self.texture.texture()
+    }
+
+    pub fn capture(&self) -> Option<Image> {
+        self.texture.display();
+        Image::from_texture(self.texture.texture())
+    }
+
+    pub fn sprite(&self) -> Sprite {
+        self.texture.display();
+        Sprite::from_texture(self.texture.texture().to_owned())
+    }
+
+    pub fn draw<Args: DrawArgs>(&self, target: &mut Canvas, args: Args) {
+        let mut sprite_data = SfmlSprite::with_texture(self.texture.texture());
+        sprite_data.set_position(args.position());
+        sprite_data.set_rotation(args.rotation());
+        sprite_data.set_scale(args.scale());
+        sprite_data.set_color(args.color());
+        target.draw(&sprite_data);
+    }
+}
@@ -1,8 +1,9 @@
 use std::{rc::Rc, time::Duration};
 
 use bronze::{
-    graphics::Sprite,
+    graphics::{Color, Sprite},
     input::{InputManager, Key},
+    particles::{Curve, Emitter, EmitterConfig},
     resources::Image,
     scene::{Collision, Entity},
     shape::{BBox, Circle, Movable, ShapeRef},
@@ -14,6 +15,7 @@ use crate::{GameContext, Player, StaticEntity, WINDOW_HEIGHT, WINDOW_WIDTH};
 
 pub struct Ball {
     sprite: Sprite,
+    trail: Emitter,
     velocity: Vector2<f32>,
     moving: bool,
     bbox: Circle,
@@ -37,8 +39,24 @@ impl Ball {
             radius: width / 2.0,
         };
 
+        let sprite = Sprite::new(image);
+
+        let mut trail = Emitter::with_sprite(
+            EmitterConfig {
+                rate: 60.0,
+                lifetime: (0.2, 0.35),
+                color: Curve::constant(Color::rgb(255, 220, 160)),
+                alpha: Curve::linear(0.5, 0.0),
+                scale: Curve::linear(0.8, 0.1),
+                ..Default::default()
+            },
+            &sprite,
+        );
+        trail.stop();
+
         Ball {
-            sprite: Sprite::new(image),
+            sprite,
+            trail,
             velocity: Vector2::new(0.0, 0.0),
             bbox,
             moving: false,
@@ -61,6 +79,7 @@ impl Entity<GameContext> for Ball {
         if !self.moving && input.key_down(Key::Space) {
             self.velocity = Vector2::new(Self::SPEED, -Self::SPEED);
             self.moving = true;
+            self.trail.start();
         }
     }
 
@@ -86,6 +105,10 @@ impl Entity<GameContext> for Ball {
                 .move_by(0.0, WINDOW_HEIGHT as f32 - self.bbox.y - self.bbox.height());
             self.velocity.y = -self.velocity.y;
         }
+
+        let center = self.bbox.center();
+        self.trail.set_position(center.x, center.y);
+        self.trail.update(frame_time);
     }
 
     #[inline]
@@ -100,6 +123,7 @@ impl Entity<GameContext> for Ball {
 
     #[inline]
     fn draw(&self, _ctx: &GameContext, target: &mut Canvas) {
+        self.trail.draw(target);
         self.sprite.draw(target, self.bbox.position());
     }
 }
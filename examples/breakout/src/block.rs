@@ -2,6 +2,8 @@ use std::{rc::Rc, time::Duration};
 
 use bronze::{
     graphics::{Color, Sprite},
+    math::Vec2,
+    particles::{Affector, Curve, Emitter, EmitterConfig},
     resources::Image,
     scene::{Collision, Entity},
     shape::{BBox, Movable, Rect, ShapeRef},
@@ -13,6 +15,7 @@ use crate::{ball::Ball, GameContext, StaticEntity, WINDOW_HEIGHT};
 
 pub struct Block {
     sprite: Sprite,
+    sparks: Emitter,
     bbox: Rect,
     falling: bool,
 }
@@ -37,14 +40,33 @@ impl Block {
 
         let sprite = Sprite::new(&image);
 
+        let sparks = Emitter::with_sprite(
+            EmitterConfig {
+                lifetime: (0.3, 0.6),
+                speed: (120.0, 320.0),
+                alpha: Curve::linear(1.0, 0.0),
+                scale: Curve::linear(0.12, 0.0),
+                affectors: vec![Affector::Gravity(Vec2::new(0.0, 900.0))],
+                ..Default::default()
+            },
+            &sprite,
+        );
+
         Block {
             sprite,
+            sparks,
             bbox,
             falling: false,
         }
     }
 
     pub fn fall(&mut self) {
+        if !self.falling {
+            let center = self.bbox.center();
+            self.sparks.set_position(center.x, center.y);
+            self.sparks.burst(24);
+        }
+
         self.falling = true;
     }
 
@@ -67,6 +89,8 @@ impl Entity<GameContext> for Block {
         if self.bbox.y > WINDOW_HEIGHT as f32 {
             self.falling = false;
         }
+
+        self.sparks.update(frame_time);
     }
 
     fn draw(&self, _ctx: &GameContext, target: &mut Canvas) {
@@ -76,6 +100,8 @@ impl Entity<GameContext> for Block {
             self.sprite
                 .draw(target, (self.bbox.position(), Color::from(0xffffff7f)));
         }
+
+        self.sparks.draw(target);
     }
 }
 
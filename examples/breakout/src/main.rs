@@ -3,9 +3,9 @@ use std::time::Duration;
 use bronze::{
     engine::Engine,
     game::Game,
-    graphics::{Color, Sprite},
+    graphics::{Color, HAlign, Sprite, Text, VAlign},
     input::{InputManager, Key},
-    resources::{Cursor, Icon, ResourcePool},
+    resources::{Cursor, Icon, Manifest, ResourcePool},
     scene::{Collision, Entity, Scene},
     shape::{BBox, ShapeRef},
     system::{Vector2, Vector2f},
@@ -95,6 +95,7 @@ impl Collision<Ball, GameContext> for StaticEntity {
 pub struct Breakout {
     bg: Sprite,
     debugger: Debugger,
+    paused_text: Text,
     scene: Scene<StaticEntity, Ball, GameContext>,
     ctx: GameContext,
     running: bool,
@@ -117,6 +118,15 @@ impl Breakout {
         let debug_font = resource_pool.get_font(Fonts::Debug);
         let debugger = Debugger::new(false, &debug_font, 10);
 
+        let mut paused_text = Text::with_markup(
+            &debug_font,
+            48,
+            "PAUSED\n[size=20]press [color=yellow]P[/color] to resume[/size]",
+        );
+        paused_text.set_align(HAlign::Center, VAlign::Center);
+        paused_text.set_outline(Color::BLACK, 3.0);
+        paused_text.set_bold(true);
+
         let player = resource_pool.get_image(Images::Player);
         let player = Player::new(&player);
 
@@ -190,6 +200,7 @@ impl Breakout {
         Breakout {
             bg,
             debugger,
+            paused_text,
             scene,
             ctx: GameContext { player_top },
             running: true,
@@ -250,16 +261,40 @@ impl Game for Breakout {
         self.bg.draw(target, (0.0, 0.0));
         self.scene.draw(&self.ctx, target);
         self.debugger.draw(&self.ctx, target);
+
+        if self.paused {
+            let size = target.size();
+            let center = (size.x as f32 / 2.0, size.y as f32 / 2.0, Color::WHITE);
+            self.paused_text.draw(target, center);
+        }
     }
 }
 
 fn main() {
-    let resource_pool = ResourcePool::new(load_image, load_audio, load_font);
+    let manifest = Manifest::load_from(assets(), "manifest.ron")
+        .unwrap_or_else(|error| panic!("Failed to load asset manifest: {}", error));
+    let resource_pool = ResourcePool::from_manifest(manifest)
+        .unwrap_or_else(|errors| {
+            for error in &errors {
+                eprintln!("{}", error);
+            }
+            panic!("Invalid asset manifest")
+        })
+        .with_placeholders();
+
+    if let Err(errors) = resource_pool.preload("game") {
+        for error in errors {
+            eprintln!("{}", error);
+        }
+    }
 
     let win_config = WindowConfig {
         title: "My Game".to_string(),
         icon: Some(Icon::from_image(&resource_pool.get_image(Images::Icon))),
-        cursor: Cursor::from_image(&resource_pool.get_image(Images::Cursor)),
+        // The arrow in cursor.png points at its top left corner.
+        cursor: Some(
+            Cursor::from_image(&resource_pool.get_image(Images::Cursor)).with_hotspot(0, 0),
+        ),
         bg_color: Color::BLACK,
         show_cursor: true,
         fps_config: FPSConfig::Unlimited,
@@ -267,6 +302,7 @@ fn main() {
     };
 
     let mut engine = Engine::new(Window::new(win_config));
+    engine.capture_mut().config_mut().screenshot_key = Some(Key::F12);
 
     engine.run(Breakout::new(&resource_pool, engine.window()));
 }
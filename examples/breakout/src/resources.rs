@@ -1,4 +1,4 @@
-use bronze::resources::{Audio, Font, Image};
+use bronze::vfs::{Embedded, Vfs};
 use strum_macros::EnumIter;
 
 #[derive(Debug, PartialEq, Eq, Hash, EnumIter)]
@@ -23,45 +23,26 @@ pub enum Fonts {
     Debug,
 }
 
-pub fn load_image(id: &Images) -> Image {
-    use Images::*;
-
-    let path = match id {
-        Icon => "examples/breakout/assets/images/icon.png",
-        Cursor => "examples/breakout/assets/images/cursor.png",
-        Background => "examples/breakout/assets/images/bg.jpg",
-        Player => "examples/breakout/assets/images/player.png",
-        Ball => "examples/breakout/assets/images/ball.png",
-        Tile1 => "examples/breakout/assets/images/tile1.png",
-        Tile2 => "examples/breakout/assets/images/tile2.png",
-        Tile3 => "examples/breakout/assets/images/tile3.png",
-        Tile4 => "examples/breakout/assets/images/tile4.png",
-        Tile5 => "examples/breakout/assets/images/tile5.png",
+macro_rules! embed {
+    ($($path:literal),* $(,)?) => {
+        Embedded::new()$(.with_file($path, include_bytes!(concat!("../assets/", $path))))*
     };
-
-    if let Some(image) = Image::new(path) {
-        println!("Loaded image {:?} from {}", id, path);
-        image
-    } else {
-        panic!("Failed to load image {:?} from {}", id, path)
-    }
-}
-
-pub fn load_audio(_id: &Audios) -> Audio {
-    panic!("Audio not implemented");
 }
 
-pub fn load_font(id: &Fonts) -> Font {
-    use Fonts::*;
-
-    let path = match id {
-        Debug => "examples/breakout/assets/fonts/JetBrainsMono[wght].ttf",
-    };
-
-    if let Some(font) = Font::new(path) {
-        println!("Loaded font {:?}", id);
-        font
-    } else {
-        panic!("Failed to load font {:?}", id)
-    }
+// Breakout's assets are compiled into the executable, so the game ships as a single file.
+pub fn assets() -> Vfs {
+    Vfs::new().with_embedded(embed![
+        "manifest.ron",
+        "images/icon.png",
+        "images/cursor.png",
+        "images/bg.jpg",
+        "images/player.png",
+        "images/ball.png",
+        "images/tile1.png",
+        "images/tile2.png",
+        "images/tile3.png",
+        "images/tile4.png",
+        "images/tile5.png",
+        "fonts/JetBrainsMono[wght].ttf",
+    ])
 }
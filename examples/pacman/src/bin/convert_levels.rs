@@ -0,0 +1,138 @@
+// Converts the old whitespace separated level files into the bronze level format.
+//
+//     cargo run -p pacman --bin convert_levels -- <prefix> <background> <output>
+//
+// Reads `<prefix>_pivots.txt`, `<prefix>_foods.txt` and `<prefix>_specials.txt`, skipping the
+// ones that do not exist, and writes a single `.ron` level. Pivot directions may be written as
+// `T/F` or `1/0`. Pacman and the ghosts were placed in code and are not part of these files.
+
+use std::{env, fs::read_to_string, path::Path, process::exit};
+
+use bronze::level::{Level, SpawnPoint};
+
+fn parse_bool(word: &str) -> Option<bool> {
+    match word {
+        "T" | "t" | "1" => Some(true),
+        "F" | "f" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+fn parse_coord(word: Option<&str>) -> Result<f32, String> {
+    let word = word.ok_or("missing coordinate")?;
+    word.parse::<u32>()
+        .map(|value| value as f32)
+        .map_err(|_| format!("expected a coordinate, found `{}`", word))
+}
+
+// Returns the words of every line that is not blank or a comment, along with its line number.
+fn read_lines(path: &str) -> Option<Vec<(usize, Vec<String>)>> {
+    if !Path::new(path).exists() {
+        return None;
+    }
+
+    let content = read_to_string(path).unwrap_or_else(|error| {
+        eprintln!("{}: {}", path, error);
+        exit(1);
+    });
+
+    let lines = content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .map(|(i, line)| (i + 1, line.split_whitespace().map(String::from).collect()))
+        .collect();
+
+    Some(lines)
+}
+
+fn read_pivots(path: &str, errors: &mut Vec<String>) -> Vec<SpawnPoint> {
+    let mut spawns = Vec::new();
+
+    for (line, words) in read_lines(path).unwrap_or_default() {
+        let mut words = words.iter().map(String::as_str);
+
+        let mut pivot = || -> Result<SpawnPoint, String> {
+            let mut dirs = [false; 4];
+            for (dir, name) in dirs.iter_mut().zip(["up", "down", "left", "right"]) {
+                let word = words.next().ok_or(format!("missing `{}`", name))?;
+                *dir = parse_bool(word).ok_or(format!(
+                    "`{}`: expected T, F, 1 or 0, found `{}`",
+                    name, word
+                ))?;
+            }
+            let x = parse_coord(words.next())?;
+            let y = parse_coord(words.next())?;
+
+            Ok(SpawnPoint::new("pivot", x, y)
+                .with_property("up", dirs[0])
+                .with_property("down", dirs[1])
+                .with_property("left", dirs[2])
+                .with_property("right", dirs[3]))
+        };
+
+        match pivot() {
+            Ok(spawn) => spawns.push(spawn),
+            Err(error) => errors.push(format!("{}:{}: {}", path, line, error)),
+        }
+    }
+
+    spawns
+}
+
+fn read_positions(path: &str, kind: &str, errors: &mut Vec<String>) -> Vec<SpawnPoint> {
+    let mut spawns = Vec::new();
+
+    for (line, words) in read_lines(path).unwrap_or_default() {
+        let mut words = words.iter().map(String::as_str);
+
+        let position = parse_coord(words.next()).and_then(|x| Ok((x, parse_coord(words.next())?)));
+
+        match position {
+            Ok((x, y)) => spawns.push(SpawnPoint::new(kind, x, y)),
+            Err(error) => errors.push(format!("{}:{}: {}", path, line, error)),
+        }
+    }
+
+    spawns
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let [prefix, background, output] = args.as_slice() else {
+        eprintln!("usage: convert_levels <prefix> <background> <output>");
+        exit(2);
+    };
+
+    let mut errors = Vec::new();
+
+    let mut level = Level::new();
+    level.background = Some(background.clone());
+    level
+        .spawns
+        .extend(read_pivots(&format!("{}_pivots.txt", prefix), &mut errors));
+    level.spawns.extend(read_positions(
+        &format!("{}_foods.txt", prefix),
+        "food",
+        &mut errors,
+    ));
+    level.spawns.extend(read_positions(
+        &format!("{}_specials.txt", prefix),
+        "special",
+        &mut errors,
+    ));
+
+    if !errors.is_empty() {
+        for error in errors {
+            eprintln!("{}", error);
+        }
+        exit(1);
+    }
+
+    if !level.save(output) {
+        eprintln!("failed to write {}", output);
+        exit(1);
+    }
+
+    println!("Wrote {} spawns to {}", level.spawns.len(), output);
+}
@@ -1,15 +1,25 @@
-use std::{fs::read_to_string, time::Duration};
+use std::{path::Path, str::FromStr, time::Duration};
 
 use bronze::{
-    engine::Engine, game::Game as BronzeGame, graphics::Sprite, input::InputManager, input::Key,
-    scene::Scene, shape::Point, window::Canvas,
+    graphics::{Color, ShapeStyle, Sprite},
+    input::InputManager,
+    input::Key,
+    level::{Level, LevelSchema, PropertySchema, Value, ValueKind},
+    resources::{asset_path, AsyncLoad, Reload},
+    scene::{Scene, Spawn},
+    shape::Point,
+    state::{State, StateChange},
+    transition::Transition,
+    tween::Easing,
+    window::Canvas,
 };
 use rand::seq::SliceRandom;
+use strum::IntoEnumIterator;
 
 use crate::{
     entities::{DynamicEntity, Food, Ghost, Pacman, Pivot, Special, StaticEntity},
     resources::*,
-    Pool,
+    Pool, WINDOW_HEIGHT, WINDOW_WIDTH,
 };
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -37,8 +47,8 @@ impl Dir {
     }
 }
 
-#[derive(Clone)]
 pub struct GameCtx {
+    pub resource_pool: Pool,
     pub score: u32,
     pub pacman_alive: bool,
     pub food_count: u32,
@@ -48,14 +58,15 @@ pub struct GameCtx {
 }
 
 impl GameCtx {
-    pub fn new(resource_pool: &Pool) -> GameCtx {
+    pub fn new(resource_pool: Pool) -> GameCtx {
         GameCtx {
             score: 0,
             pacman_alive: true,
             pacman_center: Point::new(0.0, 0.0),
-            pacman_sprites: PacmanSprites::new(resource_pool),
-            ghost_sprites: GhostSprites::new(resource_pool),
+            pacman_sprites: PacmanSprites::new(&resource_pool),
+            ghost_sprites: GhostSprites::new(&resource_pool),
             food_count: 0,
+            resource_pool,
         }
     }
 }
@@ -63,84 +74,139 @@ impl GameCtx {
 mod level {
     use super::*;
 
-    pub enum Levels {
-        TitleScreen,
-        Level1,
-        Level2,
+    pub fn level_transition() -> Transition {
+        Transition::iris(Color::BLACK, Duration::from_millis(900)).ease(Easing::QuadInOut)
     }
 
-    pub trait Level {
-        fn is_running(&self) -> bool;
-
-        fn input(&mut self, input: &InputManager) {
-            let _ = input;
-        }
-
-        fn pre_update(&mut self, ctx: &GameCtx) {
-            let _ = ctx;
-        }
-
-        fn update(&mut self, ctx: &mut GameCtx, frame_time: Duration) {
-            let _ = (ctx, frame_time);
-        }
+    fn schema() -> LevelSchema {
+        let pivot = ["up", "down", "left", "right"]
+            .into_iter()
+            .fold(PropertySchema::new(), |schema, dir| {
+                schema.with_required(dir, ValueKind::Bool)
+            });
+
+        let color = PropertySchema::new()
+            .with_required("color", ValueKind::String)
+            .with_allowed(
+                "color",
+                GhostColor::iter().map(|color| Value::from(color.as_ref())),
+            );
+
+        LevelSchema::new()
+            .with_backgrounds(Images::iter())
+            .with_kind("pacman", PropertySchema::new())
+            .with_kind("ghost", color)
+            .with_kind("pivot", pivot)
+            .with_kind("food", PropertySchema::new())
+            .with_kind("special", PropertySchema::new())
+    }
 
-        fn post_update(&mut self, ctx: &GameCtx) {
-            let _ = ctx;
-        }
+    type LevelScene = (Sprite, Scene<StaticEntity, DynamicEntity, GameCtx>);
 
-        fn draw(&self, ctx: &GameCtx, target: &mut Canvas) {
-            let _ = (ctx, target);
-        }
-
-        fn next_level(&self) -> Option<Levels>;
+    // Loads a level file into its background and scene. A broken level is reported in full and
+    // then panics, since there is no previous level to fall back to.
+    pub fn load_level(ctx: &mut GameCtx, path: &str) -> LevelScene {
+        let path = &level_file(path);
+        try_load_level(ctx, path).unwrap_or_else(|| panic!("Failed to load level from {}", path))
     }
 
-    pub fn char_to_bool(char: char) -> Option<bool> {
-        match char {
-            'T' | 't' => Some(true),
-            'F' | 'f' => Some(false),
-            _ => None,
+    // Builds the level again when its file changed on disk, keeping the current one if the
+    // new file doesn't validate.
+    pub fn reload_level(ctx: &mut GameCtx, path: &str) -> Option<LevelScene> {
+        let path = &level_file(path);
+        let changed =
+            ctx.resource_pool.poll_reload().into_iter().any(
+                |event| matches!(event, Reload::File(file) if file.as_path() == Path::new(path)),
+            );
+
+        if changed {
+            try_load_level(ctx, path)
+        } else {
+            None
         }
     }
 
-    pub fn read_pivots(path: &str) -> Option<Vec<(bool, bool, bool, bool, u32, u32)>> {
-        let content = read_to_string(path).ok()?;
-        content
-            .lines()
-            .filter(|line| !line.starts_with('#') && !line.is_empty())
-            .map(|line| {
-                let mut parts = line.split_whitespace();
-
-                let up = char_to_bool(parts.next()?.parse::<char>().ok()?)?;
-                let down = char_to_bool(parts.next()?.parse::<char>().ok()?)?;
-                let left = char_to_bool(parts.next()?.parse::<char>().ok()?)?;
-                let right = char_to_bool(parts.next()?.parse::<char>().ok()?)?;
-                let x = parts.next()?.parse::<u32>().ok()?;
-                let y = parts.next()?.parse::<u32>().ok()?;
-
-                Some((up, down, left, right, x, y))
-            })
-            .collect()
+    // Levels are looked up like the manifest, so the game runs from any directory.
+    fn level_file(path: &str) -> String {
+        asset_path(path).map_or_else(|| path.to_string(), |file| file.display().to_string())
     }
 
-    pub fn read_positions(path: &str) -> Option<Vec<(u32, u32)>> {
-        let str = read_to_string(path).ok()?;
-        let lines = str
-            .lines()
-            .filter(|line| !line.starts_with('#') && !line.is_empty());
+    fn try_load_level(ctx: &mut GameCtx, path: &str) -> Option<LevelScene> {
+        let level = match Level::load_validated(path, &schema()) {
+            Ok(level) => level,
+            Err(errors) => {
+                for error in &errors {
+                    eprintln!("{}", error);
+                }
+                return None;
+            }
+        };
 
-        let mut positions = Vec::new();
+        ctx.resource_pool.watch_file(path);
+        let resource_pool = &ctx.resource_pool;
 
-        for line in lines {
-            let mut parts = line.split_whitespace();
+        let background = level.background.as_deref().unwrap_or_default();
+        let bg = match Images::from_str(background) {
+            Ok(id) => Sprite::new(&resource_pool.get_image(id)),
+            Err(_) => {
+                eprintln!("{}: background: unknown image `{}`", path, background);
+                return None;
+            }
+        };
+
+        let food = resource_pool.get_image(Images::Food);
+        let special = resource_pool.get_image(Images::Special);
+
+        let mut scene = Scene::new();
+        let mut food_count = 0;
+        let mut valid = true;
+
+        level.spawn(&mut scene, |point| {
+            let (x, y) = (point.x as u32, point.y as u32);
+            let flag = |name| point.property(name).and_then(Value::as_bool) == Some(true);
+
+            let entity = match point.kind.as_str() {
+                "pacman" => Spawn::Dynamic(Pacman::new(x, y).into()),
+                "ghost" => {
+                    let color = point.property("color").and_then(Value::as_str);
+                    match color.map(GhostColor::from_str) {
+                        Some(Ok(color)) => Spawn::Dynamic(Ghost::new(x, y, color).into()),
+                        _ => {
+                            eprintln!(
+                                "{}:{}: unknown ghost color {:?}",
+                                path,
+                                point.line().unwrap_or_default(),
+                                color.unwrap_or_default()
+                            );
+                            valid = false;
+                            return None;
+                        }
+                    }
+                }
+                "pivot" => Spawn::Static(
+                    Pivot::new(flag("up"), flag("down"), flag("left"), flag("right"), x, y).into(),
+                ),
+                "food" => {
+                    food_count += 1;
+                    Spawn::Static(Food::new(&food, x, y).into())
+                }
+                "special" => {
+                    food_count += 1;
+                    Spawn::Static(Special::new(&special, x, y).into())
+                }
+                _ => return None,
+            };
 
-            let x = parts.next()?.parse::<u32>().ok()?;
-            let y = parts.next()?.parse::<u32>().ok()?;
+            Some(entity)
+        });
 
-            positions.push((x, y));
+        if !valid {
+            return None;
         }
 
-        Some(positions)
+        ctx.food_count = food_count;
+
+        Some((bg, scene))
     }
 }
 pub use level::*;
@@ -150,7 +216,7 @@ mod title_screen {
 
     pub struct TitleScreen {
         bg: Sprite,
-        running: bool,
+        quit: bool,
         next: bool,
     }
 
@@ -160,20 +226,20 @@ mod title_screen {
 
             Box::new(TitleScreen {
                 bg,
-                running: true,
+                quit: false,
                 next: false,
             })
         }
     }
 
-    impl Level for TitleScreen {
-        fn is_running(&self) -> bool {
-            self.running
+    impl State<GameCtx> for TitleScreen {
+        fn on_enter(&mut self, ctx: &mut GameCtx) {
+            ctx.resource_pool.try_clear();
         }
 
         fn input(&mut self, input: &InputManager) {
             if input.key_down(Key::Escape) {
-                self.running = false;
+                self.quit = true;
             }
             if input.key_pressed(Key::Enter) {
                 self.next = true;
@@ -186,77 +252,141 @@ mod title_screen {
             self.bg.draw(target, (0.0, 0.0));
         }
 
-        fn next_level(&self) -> Option<Levels> {
-            self.next.then_some(Levels::Level1)
+        fn next(&mut self, ctx: &mut GameCtx) -> StateChange<GameCtx> {
+            if self.quit {
+                StateChange::Quit
+            } else if self.next {
+                StateChange::Replace(LoadingScreen::new(ctx, "level1", |ctx| Level1::new(ctx)))
+                    .with(Transition::fade(Color::BLACK, Duration::from_millis(600)))
+            } else {
+                StateChange::None
+            }
         }
     }
 }
 pub use title_screen::*;
 
-mod level1 {
+mod loading_screen {
     use super::*;
 
-    pub struct Level1 {
-        bg: Sprite,
-        running: bool,
-        next: bool,
-        scene: Scene<StaticEntity, DynamicEntity, GameCtx>,
-    }
+    const BAR_WIDTH: f32 = 480.0;
+    const BAR_HEIGHT: f32 = 24.0;
 
-    impl Level1 {
-        pub fn new(resource_pool: &Pool, ctx: &mut GameCtx) -> Box<Self> {
-            let bg = Sprite::new(&resource_pool.get_image(Images::Level1Bg));
+    type NextState = fn(&mut GameCtx) -> Box<dyn State<GameCtx>>;
 
-            let mut scene = Scene::new();
+    // Loads a manifest group in the background while drawing a progress bar, and only builds
+    // the next state once everything in the group is in the pool.
+    pub struct LoadingScreen {
+        load: Option<AsyncLoad<Images, Audios, Fonts>>,
+        fraction: f32,
+        next: NextState,
+        quit: bool,
+    }
 
-            scene.add_dynamic(Pacman::new(480, 450));
+    impl LoadingScreen {
+        pub fn new(ctx: &GameCtx, group: &str, next: NextState) -> Box<Self> {
+            let load = ctx
+                .resource_pool
+                .load_async(group)
+                .map_err(|error| eprintln!("{}", error))
+                .ok();
+
+            Box::new(LoadingScreen {
+                load,
+                fraction: 0.0,
+                next,
+                quit: false,
+            })
+        }
+    }
 
-            scene.add_dynamic(Ghost::new(405, 360, GhostColor::Blue));
-            scene.add_dynamic(Ghost::new(455, 340, GhostColor::Orange));
-            scene.add_dynamic(Ghost::new(505, 340, GhostColor::Pink));
-            scene.add_dynamic(Ghost::new(555, 360, GhostColor::Red));
+    impl State<GameCtx> for LoadingScreen {
+        fn on_enter(&mut self, ctx: &mut GameCtx) {
+            ctx.resource_pool.try_clear();
+        }
 
-            if let Some(pivots) = read_pivots("examples/pacman/assets/levels/level1_pivots.txt") {
-                for (up, down, left, right, x, y) in pivots {
-                    scene.add_static(Pivot::new(up, down, left, right, x, y));
-                }
+        fn input(&mut self, input: &InputManager) {
+            if input.key_down(Key::Escape) {
+                self.quit = true;
             }
+        }
 
-            let sprite = resource_pool.get_image(Images::Food);
-
-            if let Some(ps) = read_positions("examples/pacman/assets/levels/level1_foods.txt") {
-                ctx.food_count = ps.len() as u32;
-                for (x, y) in ps {
-                    scene.add_static(Food::new(&sprite, x, y));
-                }
+        fn update(&mut self, ctx: &mut GameCtx, _frame_time: Duration) {
+            if let Some(load) = &mut self.load {
+                self.fraction = load.poll(&ctx.resource_pool).fraction();
             }
+        }
 
-            let sprite = resource_pool.get_image(Images::Special);
+        fn draw(&self, _ctx: &GameCtx, target: &mut Canvas) {
+            let x = (WINDOW_WIDTH as f32 - BAR_WIDTH) / 2.0;
+            let y = (WINDOW_HEIGHT as f32 - BAR_HEIGHT) / 2.0;
+
+            target.draw_rect(
+                (x, y),
+                (BAR_WIDTH * self.fraction, BAR_HEIGHT),
+                Color::YELLOW,
+            );
+            target.draw_rect(
+                (x, y),
+                (BAR_WIDTH, BAR_HEIGHT),
+                ShapeStyle::outlined(Color::WHITE, 2.0),
+            );
+        }
+
+        fn next(&mut self, ctx: &mut GameCtx) -> StateChange<GameCtx> {
+            if self.quit {
+                return StateChange::Quit;
+            }
 
-            if let Some(ps) = read_positions("examples/pacman/assets/levels/level1_specials.txt") {
-                ctx.food_count += ps.len() as u32;
-                for (x, y) in ps {
-                    scene.add_static(Special::new(&sprite, x, y));
+            match &self.load {
+                Some(load) if !load.is_complete() => StateChange::None,
+                _ => {
+                    // Whatever failed is loaded again, or replaced by a placeholder, when the
+                    // level asks for it.
+                    for error in self.load.iter().flat_map(AsyncLoad::errors) {
+                        eprintln!("{}", error);
+                    }
+                    StateChange::Replace((self.next)(ctx)).with(level_transition())
                 }
             }
+        }
+    }
+}
+pub use loading_screen::*;
+
+mod level1 {
+    use super::*;
+
+    const LEVEL1: &str = "examples/pacman/assets/levels/level1.ron";
+
+    pub struct Level1 {
+        bg: Sprite,
+        quit: bool,
+        next: bool,
+        scene: Scene<StaticEntity, DynamicEntity, GameCtx>,
+    }
+
+    impl Level1 {
+        pub fn new(ctx: &mut GameCtx) -> Box<Self> {
+            let (bg, scene) = load_level(ctx, LEVEL1);
 
             Box::new(Level1 {
                 bg,
-                running: true,
+                quit: false,
                 next: false,
                 scene,
             })
         }
     }
 
-    impl Level for Level1 {
-        fn is_running(&self) -> bool {
-            self.running
+    impl State<GameCtx> for Level1 {
+        fn on_enter(&mut self, ctx: &mut GameCtx) {
+            ctx.resource_pool.try_clear();
         }
 
         fn input(&mut self, input: &InputManager) {
             if input.key_down(Key::Escape) {
-                self.running = false;
+                self.quit = true;
             }
 
             self.scene.input(input);
@@ -267,6 +397,11 @@ mod level1 {
         }
 
         fn update(&mut self, ctx: &mut GameCtx, frame_time: Duration) {
+            if let Some((bg, scene)) = reload_level(ctx, LEVEL1) {
+                self.bg = bg;
+                self.scene = scene;
+            }
+
             self.scene.update(ctx, frame_time);
 
             self.scene.collisions(ctx);
@@ -284,8 +419,15 @@ mod level1 {
             self.scene.draw(ctx, target);
         }
 
-        fn next_level(&self) -> Option<Levels> {
-            self.next.then_some(Levels::Level2)
+        fn next(&mut self, ctx: &mut GameCtx) -> StateChange<GameCtx> {
+            if self.quit {
+                StateChange::Quit
+            } else if self.next {
+                StateChange::Replace(LoadingScreen::new(ctx, "level2", |ctx| Level2::new(ctx)))
+                    .with(level_transition())
+            } else {
+                StateChange::None
+            }
         }
     }
 }
@@ -294,68 +436,47 @@ pub use level1::*;
 mod level2 {
     use super::*;
 
+    const LEVEL2: &str = "examples/pacman/assets/levels/level2.ron";
+
     pub struct Level2 {
         bg: Sprite,
-        running: bool,
+        quit: bool,
         next: bool,
         scene: Scene<StaticEntity, DynamicEntity, GameCtx>,
     }
 
     impl Level2 {
-        pub fn new(resource_pool: &Pool, ctx: &mut GameCtx) -> Box<Self> {
-            let bg = Sprite::new(&resource_pool.get_image(Images::Level2Bg));
-
-            let mut scene = Scene::new();
-
-            scene.add_dynamic(Pacman::new(480, 450));
-
-            if let Some(pivots) = read_pivots("examples/pacman/assets/levels/level2_pivots.txt") {
-                for (up, down, left, right, x, y) in pivots {
-                    scene.add_static(Pivot::new(up, down, left, right, x, y));
-                }
-            }
-
-            let sprite = resource_pool.get_image(Images::Food);
-
-            if let Some(ps) = read_positions("examples/pacman/assets/levels/level2_foods.txt") {
-                ctx.food_count = ps.len() as u32;
-                for (x, y) in ps {
-                    scene.add_static(Food::new(&sprite, x, y));
-                }
-            }
-
-            let sprite = resource_pool.get_image(Images::Special);
-
-            if let Some(ps) = read_positions("examples/pacman/assets/levels/level2_specials.txt") {
-                ctx.food_count += ps.len() as u32;
-                for (x, y) in ps {
-                    scene.add_static(Special::new(&sprite, x, y));
-                }
-            }
+        pub fn new(ctx: &mut GameCtx) -> Box<Self> {
+            let (bg, scene) = load_level(ctx, LEVEL2);
 
             Box::new(Level2 {
                 bg,
-                running: true,
+                quit: false,
                 next: false,
                 scene,
             })
         }
     }
 
-    impl Level for Level2 {
-        fn is_running(&self) -> bool {
-            self.running
+    impl State<GameCtx> for Level2 {
+        fn on_enter(&mut self, ctx: &mut GameCtx) {
+            ctx.resource_pool.try_clear();
         }
 
         fn input(&mut self, input: &InputManager) {
             if input.key_down(Key::Escape) {
-                self.running = false;
+                self.quit = true;
             }
 
             self.scene.input(input);
         }
 
         fn update(&mut self, ctx: &mut GameCtx, frame_time: Duration) {
+            if let Some((bg, scene)) = reload_level(ctx, LEVEL2) {
+                self.bg = bg;
+                self.scene = scene;
+            }
+
             self.scene.update(ctx, frame_time);
 
             self.scene.collisions(ctx);
@@ -373,68 +494,16 @@ mod level2 {
             self.scene.draw(ctx, target);
         }
 
-        fn next_level(&self) -> Option<Levels> {
-            self.next.then_some(Levels::TitleScreen)
-        }
-    }
-}
-pub use level2::*;
-
-mod game {
-    use super::*;
-
-    pub struct Game {
-        resource_pool: Pool,
-        level: Box<dyn Level>,
-        ctx: GameCtx,
-    }
-
-    impl Game {
-        pub fn new(resource_pool: Pool) -> Game {
-            let title_screen = TitleScreen::new(&resource_pool);
-            let ctx = GameCtx::new(&resource_pool);
-
-            Game {
-                resource_pool,
-                level: title_screen,
-                ctx,
-            }
-        }
-    }
-
-    impl BronzeGame for Game {
-        fn is_running(&self) -> bool {
-            self.level.is_running()
-        }
-
-        fn input(&mut self, input: &InputManager) {
-            self.level.input(input);
-        }
-
-        fn pre_update(&mut self, _engine: &Engine) {
-            self.level.pre_update(&self.ctx);
-        }
-
-        fn update(&mut self, _engine: &mut Engine, frame_time: Duration) {
-            self.level.update(&mut self.ctx, frame_time);
-        }
-
-        fn post_update(&mut self, _engine: &Engine) {
-            self.level.post_update(&self.ctx);
-
-            if let Some(next_level) = self.level.next_level() {
-                self.level = match next_level {
-                    Levels::TitleScreen => TitleScreen::new(&self.resource_pool),
-                    Levels::Level1 => Level1::new(&self.resource_pool, &mut self.ctx),
-                    Levels::Level2 => Level2::new(&self.resource_pool, &mut self.ctx),
-                };
-                self.resource_pool.try_clear();
+        fn next(&mut self, ctx: &mut GameCtx) -> StateChange<GameCtx> {
+            if self.quit {
+                StateChange::Quit
+            } else if self.next {
+                StateChange::Replace(TitleScreen::new(&ctx.resource_pool))
+                    .with(Transition::crossfade(Duration::from_millis(800)))
+            } else {
+                StateChange::None
             }
         }
-
-        fn draw(&self, target: &mut Canvas) {
-            self.level.draw(&self.ctx, target);
-        }
     }
 }
-pub use game::*;
+pub use level2::*;
@@ -1,6 +1,9 @@
+use std::time::Duration;
+
 use bronze::{
     engine::Engine,
-    resources::ResourcePool,
+    resources::{Manifest, ResourcePool},
+    state::StateStack,
     window::{Window, WindowConfig},
 };
 
@@ -8,7 +11,10 @@ mod entities;
 mod game;
 mod resources;
 
-use crate::{game::Game, resources::*};
+use crate::{
+    game::{GameCtx, TitleScreen},
+    resources::*,
+};
 
 pub const WINDOW_WIDTH: u32 = 960;
 pub const WINDOW_HEIGHT: u32 = 720;
@@ -16,7 +22,27 @@ pub const WINDOW_HEIGHT: u32 = 720;
 pub type Pool = ResourcePool<Images, Audios, Fonts>;
 
 fn main() {
-    let resource_pool = ResourcePool::new(load_image, load_audio, load_font);
+    let manifest = Manifest::load("examples/pacman/assets/manifest.ron")
+        .unwrap_or_else(|error| panic!("Failed to load asset manifest: {}", error));
+    let mut resource_pool = ResourcePool::from_manifest(manifest)
+        .unwrap_or_else(|errors| {
+            for error in &errors {
+                eprintln!("{}", error);
+            }
+            panic!("Invalid asset manifest")
+        })
+        .with_placeholders()
+        .with_budget(32 * 1024 * 1024);
+
+    if cfg!(debug_assertions) {
+        resource_pool = resource_pool.with_hot_reload(Duration::from_millis(500));
+    }
+
+    if let Err(errors) = resource_pool.preload("sprites") {
+        for error in errors {
+            eprintln!("{}", error);
+        }
+    }
 
     let win_config = WindowConfig {
         title: "Pacman".to_string(),
@@ -26,5 +52,10 @@ fn main() {
 
     let mut engine = Engine::new(Window::new(win_config));
 
-    engine.run(Game::new(resource_pool));
+    let ctx = GameCtx::new(resource_pool);
+    let title_screen = TitleScreen::new(&ctx.resource_pool);
+
+    engine.run(
+        StateStack::new(ctx, title_screen).with_frame_end(|ctx| ctx.resource_pool.end_frame()),
+    );
 }
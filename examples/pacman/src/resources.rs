@@ -1,15 +1,10 @@
-use bronze::{
-    graphics::Sprite,
-    resources::{Audio, Font, Image},
-};
-use strum_macros::EnumIter;
+use bronze::graphics::Sprite;
+use strum_macros::{AsRefStr, EnumIter, EnumString};
 
 use crate::{game::Dir, Pool};
 
-#[derive(Debug, PartialEq, Eq, Hash, EnumIter)]
+#[derive(Debug, PartialEq, Eq, Hash, AsRefStr, EnumIter, EnumString)]
 pub enum Images {
-    Icon,
-    Cursor,
     Level1Bg,
     Level2Bg,
     TitleScreenBg,
@@ -44,56 +39,6 @@ pub enum Audios {}
 #[derive(Debug, PartialEq, Eq, Hash, EnumIter)]
 pub enum Fonts {}
 
-pub fn load_image(id: &Images) -> Image {
-    use Images::*;
-
-    let path = match id {
-        Icon => todo!("create icon"),
-        Cursor => todo!("create cursor"),
-        Level1Bg => "examples/pacman/assets/images/level1_bg.jpg",
-        Level2Bg => "examples/pacman/assets/images/level2_bg.jpg",
-        TitleScreenBg => "examples/pacman/assets/images/title_screen.jpg",
-        PacmanUp => "examples/pacman/assets/images/pacman_up.png",
-        PacmanDown => "examples/pacman/assets/images/pacman_down.png",
-        PacmanLeft => "examples/pacman/assets/images/pacman_left.png",
-        PacmanRight => "examples/pacman/assets/images/pacman_right.png",
-        GhostBlueUp => "examples/pacman/assets/images/ghost_blue_up.png",
-        GhostBlueDown => "examples/pacman/assets/images/ghost_blue_down.png",
-        GhostBlueLeft => "examples/pacman/assets/images/ghost_blue_left.png",
-        GhostBlueRight => "examples/pacman/assets/images/ghost_blue_right.png",
-        GhostOrangeUp => "examples/pacman/assets/images/ghost_orange_up.png",
-        GhostOrangeDown => "examples/pacman/assets/images/ghost_orange_down.png",
-        GhostOrangeLeft => "examples/pacman/assets/images/ghost_orange_left.png",
-        GhostOrangeRight => "examples/pacman/assets/images/ghost_orange_right.png",
-        GhostPinkUp => "examples/pacman/assets/images/ghost_pink_up.png",
-        GhostPinkDown => "examples/pacman/assets/images/ghost_pink_down.png",
-        GhostPinkLeft => "examples/pacman/assets/images/ghost_pink_left.png",
-        GhostPinkRight => "examples/pacman/assets/images/ghost_pink_right.png",
-        GhostRedUp => "examples/pacman/assets/images/ghost_red_up.png",
-        GhostRedDown => "examples/pacman/assets/images/ghost_red_down.png",
-        GhostRedLeft => "examples/pacman/assets/images/ghost_red_left.png",
-        GhostRedRight => "examples/pacman/assets/images/ghost_red_right.png",
-        GhostShock => "examples/pacman/assets/images/ghost_shock.png",
-        Food => "examples/pacman/assets/images/food.png",
-        Special => "examples/pacman/assets/images/special.png",
-    };
-
-    if let Some(image) = Image::new(path) {
-        println!("Loaded image {:?} from {}", id, path);
-        image
-    } else {
-        panic!("Failed to load image {:?} from {}", id, path)
-    }
-}
-
-pub fn load_audio(_id: &Audios) -> Audio {
-    panic!("Audio not implemented");
-}
-
-pub fn load_font(_id: &Fonts) -> Font {
-    panic!("Font not implemented");
-}
-
 #[derive(Clone)]
 pub struct PacmanSprites {
     up: Sprite,
@@ -122,6 +67,8 @@ impl PacmanSprites {
     }
 }
 
+#[derive(AsRefStr, EnumIter, EnumString)]
+#[strum(serialize_all = "lowercase")]
 pub enum GhostColor {
     Blue,
     Orange,